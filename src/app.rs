@@ -0,0 +1,791 @@
+use graphics::ellipse::Ellipse;
+use graphics::rectangle::Rectangle;
+use graphics::{clear, line, rectangle, text, Transformed};
+use opengl_graphics::{GlGraphics, GlyphCache, TextureSettings};
+use piston::input::{Key, RenderArgs, UpdateArgs};
+
+use crate::behavior::{Seek, Target, Walker, AI};
+use crate::collision::Wall;
+use crate::color::brighten;
+use crate::coords::CoordinateSystem;
+use crate::entity::{EntityKind, KindId};
+use crate::error::NatureError;
+use crate::forces::Wind;
+use crate::generator::Gaussian;
+use crate::render::{
+    axis_ticks, fade_quad_color, label_position, on_screen, pulse, render_arena,
+    render_connections, render_density, render_minimap, render_view, render_walls, render_wells,
+    screen_positions, view_size, Camera, Connections, Downsample, Highlight, Overlay, RenderOrder,
+    Shadow, DEFAULT_OVERLAYS,
+};
+use crate::shapes::{Circle, DEFAULT_SIZE};
+use crate::sprite::{Atlas, AtlasId, AtlasLayout};
+use crate::stats::Stats;
+use crate::tuning::{Parameter, Tunable};
+use crate::world::{FlashEvent, World};
+
+const SELECTION_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const LABEL_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+const LABEL_FONT_SIZE: u32 = 10;
+const HUD_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 1.0];
+const HUD_FONT_SIZE: u32 = 12;
+// Top-left corner of the first HUD line and the spacing between lines, in window points.
+const HUD_ORIGIN: [f64; 2] = [10.0, 20.0];
+const HUD_LINE_HEIGHT: f64 = 16.0;
+pub const PAINT_SPACING: f64 = 20.0;
+pub const AXIS_SPACING: f64 = 100.0;
+const AXIS_COLOR: [f32; 4] = [0.2, 0.2, 0.2, 1.0];
+// Length of the tick marks across the axes, in window points.
+const AXIS_TICK_LENGTH: f64 = 4.0;
+
+// Corners of a selection box being dragged, in window coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Drag {
+    pub start: [f64; 2],
+    pub current: [f64; 2],
+}
+
+impl Drag {
+    // Top-left and bottom-right corners whatever the drag direction.
+    pub fn corners(&self) -> ([f64; 2], [f64; 2]) {
+        (
+            [
+                self.start[0].min(self.current[0]),
+                self.start[1].min(self.current[1]),
+            ],
+            [
+                self.start[0].max(self.current[0]),
+                self.start[1].max(self.current[1]),
+            ],
+        )
+    }
+}
+
+// Points every `spacing` along the path, the first one `until_next` in from its start.
+// Also returns how much further the path would need to go for the next sample, so a
+// stroke drawn in pieces keeps its spacing across them.
+pub fn sample_path(path: &[[f64; 2]], spacing: f64, until_next: f64) -> (Vec<[f64; 2]>, f64) {
+    let mut points = Vec::new();
+    let mut until_next = until_next.max(0.0);
+
+    if !(spacing.is_finite() && spacing > 0.0) {
+        return (points, until_next);
+    }
+
+    for pair in path.windows(2) {
+        let [from, to] = [pair[0], pair[1]];
+        let dx = to[0] - from[0];
+        let dy = to[1] - from[1];
+        let length = (dx * dx + dy * dy).sqrt();
+        let mut travelled = until_next;
+
+        while travelled <= length && length > 0.0 {
+            let t = travelled / length;
+            points.push([from[0] + dx * t, from[1] + dy * t]);
+            travelled += spacing;
+        }
+
+        until_next = travelled - length;
+    }
+
+    (points, until_next)
+}
+
+// Paused and focus paused flags after the window gains or loses focus, when pausing on
+// focus loss is `enabled`. Losing focus pauses a running app, and regaining it only
+// resumes a pause that losing it caused.
+pub fn focus_pause(enabled: bool, focused: bool, paused: bool, focus_paused: bool) -> (bool, bool) {
+    if !enabled {
+        (paused, focus_paused)
+    } else if !focused && !paused {
+        (true, true)
+    } else if focused && focus_paused {
+        (false, false)
+    } else {
+        (paused, focus_paused)
+    }
+}
+
+// Stroke being painted with the middle button, in world coordinates.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Paint {
+    pub last: [f64; 2],
+    pub until_next: f64,
+}
+
+pub struct App {
+    pub gl: GlGraphics, // OpenGL drawing backend.
+    pub world: World,
+    pub camera: Camera,
+    pub coordinates: CoordinateSystem,
+    pub render_order: RenderOrder,
+    // Entity count above which they are drawn as one batch, see `should_batch`.
+    pub batch_threshold: Option<usize>,
+    // Draws a sample of the entities in dense scenes, see `Downsample`.
+    pub downsample: Option<Downsample>,
+    // Drawn beneath the entities when set.
+    pub shadow: Option<Shadow>,
+    // Drawn in this order after the background, see `overlay_order`.
+    pub overlays: Vec<Overlay>,
+    pub background_color: [f32; 4],
+    // When set, fade the previous frame by this much instead of clearing it.
+    pub fade_alpha: Option<f32>,
+    pub drag: Option<Drag>,
+    pub paint: Option<Paint>,
+    // Distance between entities painted along a stroke, in world units.
+    pub paint_spacing: f64,
+    // Shared by every painted entity, added on the first stroke.
+    paint_kind: Option<KindId>,
+    // The left button draws walls instead of selecting while set.
+    pub wall_mode: bool,
+    // World point where the wall being drawn starts.
+    pub wall_start: Option<[f64; 2]>,
+    // Ids of the selected entities.
+    pub selection: Vec<u32>,
+    pub highlight: Highlight,
+    // Last known cursor position and window size, in window coordinates.
+    pub cursor: [f64; 2],
+    pub window_size: [f64; 2],
+    // Entity id labels, only drawn once a font is loaded.
+    pub show_labels: bool,
+    pub glyphs: Option<GlyphCache<'static>>,
+    // Textures shared by sprites, indexed by `AtlasId`.
+    pub atlases: Vec<Atlas>,
+    // Overlay showing the pull of each attractor.
+    pub show_wells: bool,
+    // Outline of the world boundary.
+    pub show_arena: bool,
+    pub show_minimap: bool,
+    // Lines between nearest neighbors.
+    pub show_connections: bool,
+    pub connections: Connections,
+    // Axes through the world origin, ticked and labelled every `axis_spacing` world units.
+    pub show_axes: bool,
+    pub axis_spacing: f64,
+    // Distribution the scene was spawned from, in world coordinates, with a toggle for
+    // showing its density contours.
+    pub distribution: Option<Gaussian>,
+    pub show_density: bool,
+    // Live tuning panel, also needs the font.
+    pub show_hud: bool,
+    pub parameters: Vec<Parameter>,
+    pub selected_parameter: usize,
+    // Simulated seconds per real second.
+    pub time_scale: f64,
+    // Draw entities between their last two steps, by how far into the next step the
+    // frame is, instead of where the last step left them.
+    pub interpolate: bool,
+    // Real seconds between updates, from the last one.
+    update_dt: f64,
+    // What the world did between the last two frames, printed each frame when logging.
+    pub stats: Stats,
+    pub log_stats: bool,
+    // No updates run while paused.
+    pub paused: bool,
+    pub pause_on_focus_loss: bool,
+    // Whether the current pause came from losing focus, so regaining it only undoes that.
+    focus_paused: bool,
+}
+
+impl App {
+    pub fn new(gl: GlGraphics, world: World, background_color: [f32; 4]) -> App {
+        let window_size = [world.width, world.height];
+        let parameters = vec![
+            Parameter::new(Tunable::TimeScale, 1.0, 0.25, 0.0, 4.0),
+            Parameter::new(Tunable::Damping, world.damping, 0.1, 0.0, 5.0),
+            Parameter::new(
+                Tunable::WindStrength,
+                world.wind.map_or(0.0, |wind| wind.strength),
+                10.0,
+                0.0,
+                500.0,
+            ),
+        ];
+
+        App {
+            gl,
+            camera: Camera::fit(world.width, world.height, world.coordinates, window_size),
+            coordinates: world.coordinates,
+            world,
+            render_order: RenderOrder::Layer,
+            batch_threshold: None,
+            downsample: None,
+            shadow: None,
+            overlays: DEFAULT_OVERLAYS.to_vec(),
+            background_color,
+            fade_alpha: None,
+            drag: None,
+            paint: None,
+            paint_spacing: PAINT_SPACING,
+            paint_kind: None,
+            wall_mode: false,
+            wall_start: None,
+            selection: Vec::new(),
+            highlight: Highlight::Ring,
+            cursor: [0.0, 0.0],
+            window_size,
+            show_labels: false,
+            glyphs: None,
+            atlases: Vec::new(),
+            show_wells: false,
+            show_arena: false,
+            show_minimap: false,
+            show_connections: false,
+            connections: Connections::default(),
+            show_axes: false,
+            axis_spacing: AXIS_SPACING,
+            distribution: None,
+            show_density: false,
+            show_hud: false,
+            parameters,
+            selected_parameter: 0,
+            time_scale: 1.0,
+            interpolate: false,
+            update_dt: 1.0 / 60.0,
+            stats: Stats::default(),
+            log_stats: false,
+            paused: false,
+            pause_on_focus_loss: false,
+            focus_paused: false,
+        }
+    }
+
+    // Switches the world and the view to another coordinate system, moving everything
+    // already placed so nothing jumps on screen.
+    pub fn set_coordinates(&mut self, coordinates: CoordinateSystem) {
+        self.world.set_coordinates(coordinates);
+        self.coordinates = coordinates;
+        self.camera = Camera::fit(
+            self.world.width,
+            self.world.height,
+            coordinates,
+            self.window_size,
+        );
+    }
+
+    pub fn load_font(&mut self, path: &str) -> Result<(), NatureError> {
+        let glyphs = GlyphCache::new(path, (), TextureSettings::new())
+            .map_err(|error| NatureError::Font(format!("{}: {}", path, error)))?;
+
+        self.glyphs = Some(glyphs);
+        Ok(())
+    }
+
+    pub fn load_atlas(&mut self, path: &str, layout: AtlasLayout) -> Result<AtlasId, NatureError> {
+        self.atlases.push(Atlas::load(path, layout)?);
+        Ok(AtlasId(self.atlases.len() - 1))
+    }
+
+    pub fn render(&mut self, args: &RenderArgs) {
+        let background_color: [f32; 4] = self.background_color;
+        let fade_alpha = self.fade_alpha;
+        let size = view_size(&args.viewport());
+
+        self.window_size = args.window_size;
+
+        self.gl.draw(args.viewport(), |c, gl| match fade_alpha {
+            Some(alpha) => {
+                let color = fade_quad_color(background_color, alpha);
+                rectangle(color, [0.0, 0.0, size[0], size[1]], c.transform, gl);
+            }
+            // Clear the screen.
+            None => clear(background_color, gl),
+        });
+
+        for overlay in self.overlays.clone() {
+            self.render_overlay(overlay, args);
+        }
+
+        self.stats = self.world.take_stats();
+        if self.log_stats {
+            eprintln!("{:?}", self.stats);
+        }
+    }
+
+    fn render_overlay(&mut self, overlay: Overlay, args: &RenderArgs) {
+        match overlay {
+            Overlay::Wells if self.show_wells => render_wells(
+                &mut self.gl,
+                &self.world.attractors,
+                args.viewport(),
+                &self.camera,
+            ),
+            Overlay::Wells => {}
+            Overlay::Arena if self.show_arena => {
+                render_arena(&mut self.gl, &self.world, args.viewport(), &self.camera)
+            }
+            Overlay::Arena => {}
+            Overlay::Axes if self.show_axes => self.render_axes(args),
+            Overlay::Axes => {}
+            Overlay::Walls => {
+                let pending = self.wall_start.map(|from| Wall {
+                    from,
+                    to: self.camera.view_to_world(self.cursor, self.window_size),
+                });
+
+                render_walls(
+                    &mut self.gl,
+                    &self.world.walls,
+                    pending,
+                    args.viewport(),
+                    &self.camera,
+                )
+            }
+            Overlay::Connections if self.show_connections => render_connections(
+                &mut self.gl,
+                &self.world,
+                self.connections,
+                args.viewport(),
+                &self.camera,
+            ),
+            Overlay::Connections => {}
+            Overlay::Minimap if self.show_minimap => {
+                render_minimap(&mut self.gl, &self.world, args.viewport(), &self.camera)
+            }
+            Overlay::Minimap => {}
+            Overlay::Density => {
+                if let Some(gaussian) = self.distribution.as_ref().filter(|_| self.show_density) {
+                    render_density(&mut self.gl, gaussian, args.viewport(), &self.camera);
+                }
+            }
+            Overlay::Entities => render_view(
+                &mut self.gl,
+                &self.world,
+                args.viewport(),
+                &self.camera,
+                self.render_order,
+                self.batch_threshold,
+                self.downsample,
+                self.shadow,
+                &self.atlases,
+                self.interpolate
+                    .then_some((args.ext_dt / self.update_dt).clamp(0.0, 1.0)),
+            ),
+            Overlay::Selection => self.render_selection(args),
+            Overlay::Labels => self.render_labels(args),
+            Overlay::Hud => self.render_hud(args),
+        }
+    }
+
+    fn render_selection(&mut self, args: &RenderArgs) {
+        let world = &self.world;
+        let selection = &self.selection;
+        let camera = &self.camera;
+        let window_size = self.window_size;
+        let drag = self.drag;
+        let highlight = self.highlight;
+
+        self.gl.draw(args.viewport(), |c, gl| {
+            let world_c = camera.apply(c, window_size);
+            let ring = Ellipse::new_border(SELECTION_COLOR, 1.0 / camera.zoom);
+
+            for entity in world.entities.iter() {
+                if !selection.contains(&entity._id) {
+                    continue;
+                }
+
+                let size = entity.physics.size + 4.0 / camera.zoom;
+                let bounds = [
+                    entity.physics.x - size / 2.0,
+                    entity.physics.y - size / 2.0,
+                    size,
+                    size,
+                ];
+
+                match highlight {
+                    Highlight::Ring => {
+                        ring.draw(bounds, &world_c.draw_state, world_c.transform, gl)
+                    }
+                    Highlight::Brighten { amount } => {
+                        if let Some(color) = world.color_of(entity) {
+                            world.renderer_of(entity).draw_colored(
+                                gl,
+                                world_c,
+                                entity.physics,
+                                brighten(color, amount),
+                            );
+                        }
+                    }
+                    Highlight::Pulse { min, max, period } => {
+                        let alpha = pulse(world.sim_time, min, max, period).clamp(0.0, 1.0);
+                        let glow = [
+                            SELECTION_COLOR[0],
+                            SELECTION_COLOR[1],
+                            SELECTION_COLOR[2],
+                            alpha as f32,
+                        ];
+
+                        Ellipse::new(glow).draw(bounds, &world_c.draw_state, world_c.transform, gl);
+                    }
+                }
+            }
+
+            if let Some(drag) = drag {
+                let (min, max) = drag.corners();
+                let bounds = [min[0], min[1], max[0] - min[0], max[1] - min[1]];
+                let fill = [
+                    SELECTION_COLOR[0],
+                    SELECTION_COLOR[1],
+                    SELECTION_COLOR[2],
+                    0.2,
+                ];
+
+                rectangle(fill, bounds, c.transform, gl);
+                Rectangle::new_border(SELECTION_COLOR, 1.0).draw(
+                    bounds,
+                    &c.draw_state,
+                    c.transform,
+                    gl,
+                );
+            }
+        });
+    }
+
+    fn render_hud(&mut self, args: &RenderArgs) {
+        let glyphs = match self.glyphs.as_mut() {
+            Some(glyphs) if self.show_hud => glyphs,
+            _ => return,
+        };
+        let parameters = &self.parameters;
+        let selected = self.selected_parameter;
+
+        self.gl.draw(args.viewport(), |c, gl| {
+            for (index, parameter) in parameters.iter().enumerate() {
+                let marker = if index == selected { ">" } else { " " };
+                let line = format!(
+                    "{} {}: {:.2}",
+                    marker,
+                    parameter.tunable.label(),
+                    parameter.value
+                );
+                let transform = c.transform.trans(
+                    HUD_ORIGIN[0],
+                    HUD_ORIGIN[1] + index as f64 * HUD_LINE_HEIGHT,
+                );
+
+                let _ = text(HUD_COLOR, HUD_FONT_SIZE, &line, glyphs, transform, gl);
+            }
+        });
+    }
+
+    // Drawn in window coordinates so lines and labels keep their size whatever the zoom.
+    // The axes run through the origin, or along the nearest edge when it's outside.
+    fn render_axes(&mut self, args: &RenderArgs) {
+        let (min, max) = self.world.bounds();
+        let origin = [0.0f64.clamp(min[0], max[0]), 0.0f64.clamp(min[1], max[1])];
+        let ticks = [
+            axis_ticks(min[0], max[0], self.axis_spacing),
+            axis_ticks(min[1], max[1], self.axis_spacing),
+        ];
+        let camera = &self.camera;
+        let window_size = self.window_size;
+        let mut glyphs = self.glyphs.as_mut();
+        let view = |point| camera.world_to_view(point, window_size);
+
+        self.gl.draw(args.viewport(), |c, gl| {
+            let half = AXIS_TICK_LENGTH / 2.0;
+            let [x1, y1] = view([min[0], origin[1]]);
+            let [x2, y2] = view([max[0], origin[1]]);
+            line(AXIS_COLOR, 0.5, [x1, y1, x2, y2], c.transform, gl);
+            let [x1, y1] = view([origin[0], min[1]]);
+            let [x2, y2] = view([origin[0], max[1]]);
+            line(AXIS_COLOR, 0.5, [x1, y1, x2, y2], c.transform, gl);
+
+            for (axis, ticks) in ticks.iter().enumerate() {
+                for &tick in ticks {
+                    let point = if axis == 0 {
+                        [tick, origin[1]]
+                    } else {
+                        [origin[0], tick]
+                    };
+                    let [x, y] = view(point);
+                    let mark = if axis == 0 {
+                        [x, y - half, x, y + half]
+                    } else {
+                        [x - half, y, x + half, y]
+                    };
+                    line(AXIS_COLOR, 0.5, mark, c.transform, gl);
+
+                    if let Some(glyphs) = glyphs.as_mut() {
+                        let transform = c.transform.trans(x + half, y - half);
+                        let label = tick.to_string();
+                        let _ = text(AXIS_COLOR, LABEL_FONT_SIZE, &label, *glyphs, transform, gl);
+                    }
+                }
+            }
+        });
+    }
+
+    fn render_labels(&mut self, args: &RenderArgs) {
+        let glyphs = match self.glyphs.as_mut() {
+            Some(glyphs) if self.show_labels => glyphs,
+            _ => return,
+        };
+        let world = &self.world;
+        let zoom = self.camera.zoom;
+        let window_size = self.window_size;
+        let centers = screen_positions(&world.entities, &self.camera, window_size);
+
+        self.gl.draw(args.viewport(), |c, gl| {
+            for (entity, (id, center)) in world.entities.iter().zip(centers) {
+                if !on_screen(center, window_size) {
+                    continue;
+                }
+
+                let [x, y] = label_position(center, entity.physics.size * zoom);
+                let label = id.to_string();
+                let transform = c.transform.trans(x, y);
+
+                // A glyph that fails to render only costs that one label.
+                let _ = text(LABEL_COLOR, LABEL_FONT_SIZE, &label, glyphs, transform, gl);
+            }
+        });
+    }
+
+    // Id and window position of every entity, see `screen_positions`.
+    pub fn entity_screen_positions(&self) -> Vec<(u32, [f64; 2])> {
+        screen_positions(&self.world.entities, &self.camera, self.window_size)
+    }
+
+    pub fn update(&mut self, args: &UpdateArgs) {
+        self.update_dt = args.dt;
+        if self.paused {
+            return;
+        }
+
+        self.world.step(args.dt * self.time_scale);
+    }
+
+    pub fn focus_changed(&mut self, focused: bool) {
+        let (paused, focus_paused) = focus_pause(
+            self.pause_on_focus_loss,
+            focused,
+            self.paused,
+            self.focus_paused,
+        );
+
+        self.paused = paused;
+        self.focus_paused = focus_paused;
+    }
+
+    // Moves the selected HUD parameter by `steps` and pushes the new value to the world.
+    fn nudge_parameter(&mut self, steps: i32) {
+        let parameter = match self.parameters.get_mut(self.selected_parameter) {
+            Some(parameter) => parameter,
+            None => return,
+        };
+
+        parameter.nudge(steps);
+
+        let value = parameter.value;
+        match parameter.tunable {
+            Tunable::TimeScale => self.time_scale = value,
+            Tunable::Damping => self.world.damping = value,
+            Tunable::WindStrength => match self.world.wind.as_mut() {
+                Some(wind) => wind.strength = value,
+                None => {
+                    self.world.wind = Some(Wind {
+                        direction: 0.0,
+                        strength: value,
+                        period: None,
+                    })
+                }
+            },
+        }
+    }
+
+    // Space pauses, delete removes the selection, the number keys give it a new behavior:
+    // 1 walk, 2 seek the cursor, 3 no behavior, 4 seek the goal. G shows the gravity
+    // wells, B the arena outline, M the minimap, C the spawn density and H the HUD, whose
+    // parameter is picked with up and down and changed with left and right. W switches the
+    // left button between selecting and drawing walls, X shows the axes.
+    pub fn key_pressed(&mut self, key: Key) {
+        match key {
+            Key::Space => {
+                self.paused = !self.paused;
+                self.focus_paused = false;
+            }
+            Key::Delete | Key::Backspace => {
+                for id in self.selection.drain(..) {
+                    self.world.despawn(id);
+                }
+            }
+            Key::D1 => self
+                .world
+                .set_behavior(&self.selection, || Some(Box::new(Walker::default()))),
+            Key::D2 => {
+                let target = self.camera.view_to_world(self.cursor, self.window_size);
+
+                self.world.set_behavior(&self.selection, || {
+                    Some(Box::new(Seek {
+                        target: Target::Point(target),
+                        speed: 100.0,
+                    }) as Box<dyn AI>)
+                })
+            }
+            Key::D3 => self.world.set_behavior(&self.selection, || None),
+            Key::L => self.show_labels = !self.show_labels,
+            Key::D4 => self.world.set_behavior(&self.selection, || {
+                Some(Box::new(Seek {
+                    target: Target::Goal,
+                    speed: 100.0,
+                }))
+            }),
+            Key::G => self.show_wells = !self.show_wells,
+            Key::B => self.show_arena = !self.show_arena,
+            Key::M => self.show_minimap = !self.show_minimap,
+            Key::N => self.show_connections = !self.show_connections,
+            Key::X => self.show_axes = !self.show_axes,
+            Key::W => {
+                self.wall_mode = !self.wall_mode;
+                self.wall_start = None;
+            }
+            Key::C => self.show_density = !self.show_density,
+            Key::H => self.show_hud = !self.show_hud,
+            Key::Up if !self.parameters.is_empty() => {
+                let count = self.parameters.len();
+                self.selected_parameter = (self.selected_parameter + count - 1) % count;
+            }
+            Key::Down if !self.parameters.is_empty() => {
+                self.selected_parameter = (self.selected_parameter + 1) % self.parameters.len();
+            }
+            Key::Left => self.nudge_parameter(-1),
+            Key::Right => self.nudge_parameter(1),
+            _ => {}
+        }
+    }
+
+    pub fn cursor_moved(&mut self, position: [f64; 2]) {
+        self.cursor = position;
+
+        if let Some(drag) = self.drag.as_mut() {
+            drag.current = position;
+        }
+
+        if let Some(paint) = self.paint {
+            let point = self.camera.view_to_world(position, self.window_size);
+            let (points, until_next) =
+                sample_path(&[paint.last, point], self.paint_spacing, paint.until_next);
+
+            self.paint = Some(Paint {
+                last: point,
+                until_next,
+            });
+            for point in points {
+                self.spawn_painted(point);
+            }
+        }
+    }
+
+    // Starts a stroke under the cursor, spawning the first entity right away.
+    pub fn paint_pressed(&mut self) {
+        let point = self.camera.view_to_world(self.cursor, self.window_size);
+
+        self.spawn_painted(point);
+        self.paint = Some(Paint {
+            last: point,
+            until_next: self.paint_spacing,
+        });
+    }
+
+    pub fn paint_released(&mut self) {
+        self.paint = None;
+    }
+
+    fn spawn_painted(&mut self, [x, y]: [f64; 2]) {
+        let kind = match self.paint_kind {
+            Some(kind) => kind,
+            None => {
+                let kind = self.world.add_kind(EntityKind {
+                    renderer: Box::new(Circle::default()),
+                    size: DEFAULT_SIZE,
+                });
+                self.paint_kind = Some(kind);
+                kind
+            }
+        };
+        let entity = self.world.entity_of_kind(0, kind, x, y);
+
+        self.world.try_spawn(entity);
+    }
+
+    pub fn mouse_pressed(&mut self) {
+        if self.wall_mode {
+            self.wall_start = Some(self.camera.view_to_world(self.cursor, self.window_size));
+            return;
+        }
+
+        self.drag = Some(Drag {
+            start: self.cursor,
+            current: self.cursor,
+        });
+    }
+
+    // Moves the world goal under the cursor.
+    pub fn set_goal(&mut self) {
+        self.world.goal = Some(self.camera.view_to_world(self.cursor, self.window_size));
+    }
+
+    pub fn mouse_released(&mut self) {
+        if let Some(from) = self.wall_start.take() {
+            let to = self.camera.view_to_world(self.cursor, self.window_size);
+
+            // A click without a drag would leave a wall too short to see.
+            if from != to {
+                self.world.walls.push(Wall { from, to });
+            }
+            return;
+        }
+
+        if let Some(drag) = self.drag.take() {
+            let (min, max) = drag.corners();
+            let a = self.camera.view_to_world(min, self.window_size);
+            let b = self.camera.view_to_world(max, self.window_size);
+
+            // With y up the screen's top edge maps to the larger world y.
+            self.selection = self.world.entities_in_rect(
+                [a[0].min(b[0]), a[1].min(b[1])],
+                [a[0].max(b[0]), a[1].max(b[1])],
+            );
+            for &id in self.selection.iter() {
+                self.world.flash(id, FlashEvent::Selection);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn focus_loss_pauses_and_regaining_it_resumes_only_that_pause() {
+        // Running, then losing and regaining focus.
+        assert_eq!(focus_pause(true, false, false, false), (true, true));
+        assert_eq!(focus_pause(true, true, true, true), (false, false));
+
+        // Paused by hand, regaining focus leaves it paused.
+        assert_eq!(focus_pause(true, false, true, false), (true, false));
+        assert_eq!(focus_pause(true, true, true, false), (true, false));
+
+        // With the option off, focus changes nothing.
+        assert_eq!(focus_pause(false, false, false, false), (false, false));
+        assert_eq!(focus_pause(false, true, true, true), (true, true));
+    }
+
+    #[test]
+    fn path_samples_keep_their_spacing_across_pieces() {
+        let (points, until_next) = sample_path(&[[0.0, 0.0], [25.0, 0.0]], 10.0, 0.0);
+        assert_eq!(points, vec![[0.0, 0.0], [10.0, 0.0], [20.0, 0.0]]);
+        assert_eq!(until_next, 5.0);
+
+        // The next piece of the stroke picks up where that one stopped, round a corner.
+        let (points, until_next) = sample_path(&[[25.0, 0.0], [25.0, 20.0]], 10.0, until_next);
+        assert_eq!(points, vec![[25.0, 5.0], [25.0, 15.0]]);
+        assert_eq!(until_next, 5.0);
+
+        assert!(sample_path(&[[0.0, 0.0], [25.0, 0.0]], 0.0, 0.0)
+            .0
+            .is_empty());
+    }
+}
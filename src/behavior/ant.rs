@@ -0,0 +1,88 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+use crate::entity::Physics;
+
+// Lays pheromone wherever it goes and turns up the pheromone gradient, so ants end up
+// walking each other's trails. Where the grid is flat the ant keeps going as it was.
+pub struct Ant {
+    // Units per second along the gradient.
+    pub speed: f64,
+    // How quickly the velocity turns towards the gradient, per second.
+    pub strength: f64,
+    // Pheromone laid per second.
+    pub deposit: f64,
+}
+
+impl Ant {
+    // Acceleration bringing the velocity towards `speed` up `gradient`, none where it
+    // vanishes.
+    pub fn steering(&self, physics: &Physics, gradient: [f64; 2]) -> Option<[f64; 2]> {
+        let length = (gradient[0] * gradient[0] + gradient[1] * gradient[1]).sqrt();
+
+        if !(length.is_finite() && length > f64::EPSILON) {
+            return None;
+        }
+
+        Some([
+            (gradient[0] / length * self.speed - physics.vx) * self.strength,
+            (gradient[1] / length * self.speed - physics.vy) * self.strength,
+        ])
+    }
+}
+
+impl AI for Ant {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let entity = &entity.physics;
+        let acceleration = context
+            .pheromones
+            .and_then(|grid| self.steering(entity, grid.gradient([entity.x, entity.y])))
+            .unwrap_or([0.0, 0.0]);
+
+        Steering {
+            acceleration,
+            deposit: self.deposit,
+            ..Steering::default()
+        }
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Ant {
+            speed: self.speed,
+            strength: self.strength,
+            deposit: self.deposit,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{snapshot, steer, with_context};
+    use super::*;
+    use crate::pheromone::PheromoneGrid;
+
+    #[test]
+    fn ants_turn_up_the_trail_and_lay_their_own() {
+        let mut grid = PheromoneGrid::new([0.0, 0.0], 10.0, 10, 10, 1.0).unwrap();
+        grid.deposit([65.0, 55.0], 4.0);
+        let ant = Ant {
+            speed: 3.0,
+            strength: 2.0,
+            deposit: 1.5,
+        };
+
+        let snapshot = [snapshot(0, 55.0, 55.0)];
+        let steering = with_context(&snapshot, |context| {
+            steer(
+                &ant,
+                0,
+                &BehaviorContext {
+                    pheromones: Some(&grid),
+                    ..context
+                },
+            )
+        });
+
+        assert_eq!(steering.acceleration, [6.0, 0.0]);
+        assert_eq!(steering.deposit, 1.5);
+    }
+}
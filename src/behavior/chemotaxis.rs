@@ -0,0 +1,91 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+
+// Climbs the world's scalar field, or heads down it when `descend` is set. Where the
+// field is flat the entity keeps going as it was.
+pub struct Chemotaxis {
+    // Units per second.
+    pub speed: f64,
+    pub descend: bool,
+}
+
+impl Chemotaxis {
+    // Velocity along the gradient, none where it vanishes.
+    pub fn velocity(&self, gradient: [f64; 2]) -> Option<[f64; 2]> {
+        let length = (gradient[0] * gradient[0] + gradient[1] * gradient[1]).sqrt();
+
+        if !(length.is_finite() && length > f64::EPSILON) {
+            return None;
+        }
+
+        let sign = if self.descend { -1.0 } else { 1.0 };
+        Some([
+            sign * gradient[0] / length * self.speed,
+            sign * gradient[1] / length * self.speed,
+        ])
+    }
+}
+
+impl AI for Chemotaxis {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let entity = &entity.physics;
+        let gradient = match context.field {
+            Some(field) => field.gradient([entity.x, entity.y]),
+            None => return Steering::default(),
+        };
+
+        match self.velocity(gradient) {
+            Some([vx, vy]) => Steering::velocity(vx, vy),
+            None => Steering::default(),
+        }
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Chemotaxis {
+            speed: self.speed,
+            descend: self.descend,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{snapshot, steer, with_context};
+    use super::*;
+    use crate::field::{ScalarField, Source};
+
+    #[test]
+    fn chemotaxis_heads_for_the_peak_of_a_radial_field() {
+        let field = ScalarField {
+            sources: vec![Source {
+                position: [50.0, 50.0],
+                strength: 1.0,
+                radius: 20.0,
+            }],
+        };
+        let climber = Chemotaxis {
+            speed: 5.0,
+            descend: false,
+        };
+
+        for &(x, y) in &[(30.0, 50.0), (70.0, 40.0), (45.0, 80.0)] {
+            let snapshot = [snapshot(0, x, y)];
+            let steering = with_context(&snapshot, |context| {
+                steer(
+                    &climber,
+                    0,
+                    &BehaviorContext {
+                        field: Some(&field),
+                        ..context
+                    },
+                )
+            });
+
+            let [vx, vy] = steering.velocity.unwrap();
+            let (dx, dy) = (50.0 - x, 50.0 - y);
+            let distance = (dx * dx + dy * dy).sqrt();
+            assert!((vx - dx / distance * 5.0).abs() < 1e-3);
+            assert!((vy - dy / distance * 5.0).abs() < 1e-3);
+        }
+    }
+}
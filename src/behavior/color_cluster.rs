@@ -0,0 +1,135 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::color::color_distance;
+use crate::config::BehaviorConfig;
+use crate::entity::Physics;
+
+// Birds of a feather: drawn towards neighbors of a similar color and away from the rest,
+// so entities sort themselves into clusters by color.
+pub struct ColorCluster {
+    pub perception_radius: f64,
+    pub max_neighbors: Option<usize>,
+    // Color distance, from 0 to 1, under which a neighbor counts as similar.
+    pub threshold: f64,
+    pub attraction: f64,
+    pub repulsion: f64,
+}
+
+impl ColorCluster {
+    // Acceleration from neighbors paired with their color distance to the entity. The
+    // pull of a similar neighbor weakens as its color gets closer to the threshold.
+    pub fn steering(&self, physics: &Physics, neighbors: &[(Physics, f64)]) -> [f64; 2] {
+        let (mut ax, mut ay) = (0.0, 0.0);
+
+        for (other, distance) in neighbors {
+            let dx = other.x - physics.x;
+            let dy = other.y - physics.y;
+            let length = (dx * dx + dy * dy).sqrt();
+
+            if length <= f64::EPSILON {
+                continue;
+            }
+
+            let weight = if *distance < self.threshold {
+                self.attraction * (1.0 - distance / self.threshold)
+            } else {
+                -self.repulsion
+            };
+
+            ax += dx / length * weight;
+            ay += dy / length * weight;
+        }
+
+        [ax, ay]
+    }
+}
+
+impl AI for ColorCluster {
+    fn compute(
+        &self,
+        entity: &Snapshot,
+        neighbors: &[Snapshot],
+        context: &BehaviorContext,
+    ) -> Steering {
+        let color = match context.color_of(entity.id) {
+            Some(color) => color,
+            None => return Steering::default(),
+        };
+        let colored: Vec<(Physics, f64)> = neighbors
+            .iter()
+            .filter_map(|other| {
+                context
+                    .color_of(other.id)
+                    .map(|other_color| (other.physics, color_distance(color, other_color)))
+            })
+            .collect();
+        let [ax, ay] = self.steering(&entity.physics, &colored);
+
+        Steering::acceleration(ax, ay)
+    }
+
+    fn perception_radius(&self) -> Option<f64> {
+        Some(self.perception_radius)
+    }
+
+    fn max_neighbors(&self) -> Option<usize> {
+        self.max_neighbors
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::ColorCluster {
+            perception_radius: self.perception_radius,
+            max_neighbors: self.max_neighbors,
+            threshold: self.threshold,
+            attraction: self.attraction,
+            repulsion: self.repulsion,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{snapshot, steer, with_context};
+    use super::*;
+
+    #[test]
+    fn drawn_to_similar_colors_and_away_from_the_outlier() {
+        assert_eq!(
+            color_distance([0.0, 0.0, 0.0, 1.0], [1.0, 1.0, 1.0, 0.0]),
+            1.0
+        );
+        assert_eq!(
+            color_distance([0.3, 0.2, 0.1, 1.0], [0.3, 0.2, 0.1, 1.0]),
+            0.0
+        );
+
+        let cluster = ColorCluster {
+            perception_radius: 30.0,
+            max_neighbors: None,
+            threshold: 0.2,
+            attraction: 2.0,
+            repulsion: 1.0,
+        };
+        // A similar red towards +x and a blue outlier towards -y.
+        let snapshot = [
+            snapshot(0, 50.0, 50.0),
+            snapshot(1, 60.0, 50.0),
+            snapshot(2, 50.0, 40.0),
+        ];
+        let colors = [
+            Some([1.0, 0.0, 0.0, 1.0]),
+            Some([0.9, 0.1, 0.0, 1.0]),
+            Some([0.0, 0.0, 1.0, 1.0]),
+        ];
+
+        with_context(&snapshot, |context| {
+            let context = BehaviorContext {
+                colors: &colors,
+                ..context
+            };
+            let [ax, ay] = steer(&cluster, 0, &context).acceleration;
+
+            assert!(ax > 0.0);
+            assert_eq!(ay, 1.0);
+        });
+    }
+}
@@ -0,0 +1,65 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+
+// Pushes entities back inside the world, harder the deeper they get into the margin.
+pub struct Containment {
+    // Width of the band along each edge where the push applies.
+    pub margin: f64,
+    // Acceleration at the very edge, in units per second squared.
+    pub strength: f64,
+}
+
+impl Containment {
+    // Acceleration along one axis for a coordinate within [0, extent].
+    pub fn push(&self, position: f64, extent: f64) -> f64 {
+        if self.margin <= 0.0 {
+            return 0.0;
+        }
+
+        if position < self.margin {
+            (self.margin - position) / self.margin * self.strength
+        } else if position > extent - self.margin {
+            -(position - (extent - self.margin)) / self.margin * self.strength
+        } else {
+            0.0
+        }
+    }
+}
+
+impl AI for Containment {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let entity = &entity.physics;
+        let (min, _) = context.bounds;
+
+        Steering::acceleration(
+            self.push(entity.x - min[0], context.width),
+            self.push(entity.y - min[1], context.height),
+        )
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Containment {
+            margin: self.margin,
+            strength: self.strength,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_grows_through_the_margin_and_vanishes_inside() {
+        let containment = Containment {
+            margin: 10.0,
+            strength: 4.0,
+        };
+
+        assert_eq!(containment.push(50.0, 100.0), 0.0);
+        assert_eq!(containment.push(90.0, 100.0), 0.0);
+        assert_eq!(containment.push(95.0, 100.0), -2.0);
+        assert_eq!(containment.push(100.0, 100.0), -4.0);
+        assert_eq!(containment.push(2.5, 100.0), 3.0);
+    }
+}
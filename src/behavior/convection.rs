@@ -0,0 +1,120 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+
+// Weather-like cycling: the world's scalar field is read as heat, cooling off with
+// height, and entities warmer than `neutral` rise while cooler ones sink. With `sizes`
+// set they also evaporate as they warm and condense as they cool.
+pub struct Convection {
+    pub neutral: f64,
+    // Temperature lost per unit of height above the bottom of the world.
+    pub lapse: f64,
+    // Upward acceleration per degree above `neutral`.
+    pub buoyancy: f64,
+    // Sizes when evaporated and condensed, eased between over `range` degrees either
+    // side of `neutral`.
+    pub sizes: Option<[f64; 2]>,
+    pub range: f64,
+}
+
+impl Convection {
+    pub fn temperature(&self, heat: f64, height: f64) -> f64 {
+        heat - self.lapse * height
+    }
+
+    // Vertical acceleration, upwards being positive y only when `y_up`.
+    pub fn acceleration(&self, temperature: f64, y_up: bool) -> [f64; 2] {
+        let up = self.buoyancy * (temperature - self.neutral);
+
+        [0.0, if y_up { up } else { -up }]
+    }
+
+    pub fn size(&self, temperature: f64) -> Option<f64> {
+        let [vapor, drop] = self.sizes?;
+        let warmth = ((temperature - self.neutral) / self.range).clamp(-1.0, 1.0) * 0.5 + 0.5;
+
+        Some(drop + (vapor - drop) * warmth)
+    }
+}
+
+impl AI for Convection {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let entity = &entity.physics;
+        let (min, max) = context.bounds;
+        let y_up = context.coordinates.y_up();
+        let height = if y_up {
+            entity.y - min[1]
+        } else {
+            max[1] - entity.y
+        };
+        let heat = context
+            .field
+            .map(|field| field.sample([entity.x, entity.y]))
+            .unwrap_or(0.0);
+        let temperature = self.temperature(heat, height);
+
+        Steering {
+            acceleration: self.acceleration(temperature, y_up),
+            size: self.size(temperature),
+            ..Steering::default()
+        }
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Convection {
+            neutral: self.neutral,
+            lapse: self.lapse,
+            buoyancy: self.buoyancy,
+            sizes: self.sizes,
+            range: self.range,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{snapshot, steer, with_context};
+    use super::*;
+    use crate::field::{ScalarField, Source};
+
+    #[test]
+    fn hot_entities_rise_and_cool_ones_sink() {
+        // A heater at the bottom of the top-left world, where y grows downwards.
+        let field = ScalarField {
+            sources: vec![Source {
+                position: [50.0, 100.0],
+                strength: 100.0,
+                radius: 10.0,
+            }],
+        };
+        let convection = Convection {
+            neutral: 20.0,
+            lapse: 1.0,
+            buoyancy: 0.1,
+            sizes: Some([6.0, 2.0]),
+            range: 10.0,
+        };
+        let steering = |y| {
+            let snapshot = [snapshot(0, 50.0, y)];
+            with_context(&snapshot, |context| {
+                steer(
+                    &convection,
+                    0,
+                    &BehaviorContext {
+                        field: Some(&field),
+                        ..context
+                    },
+                )
+            })
+        };
+
+        let hot = steering(100.0);
+        assert_eq!(hot.acceleration, [0.0, -8.0]);
+        assert_eq!(hot.size, Some(6.0));
+
+        let temperature = field.sample([50.0, 10.0]) - 90.0;
+        let cool = steering(10.0);
+        assert!(cool.acceleration[1] > 0.0);
+        assert!((cool.acceleration[1] + 0.1 * (temperature - 20.0)).abs() < 1e-12);
+        assert_eq!(cool.size, Some(2.0));
+    }
+}
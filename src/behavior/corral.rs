@@ -0,0 +1,96 @@
+use super::{BehaviorContext, Snapshot, Steering, Target, AI};
+use crate::config::BehaviorConfig;
+
+// Keeps entities inside a circle that can move with the goal or a leader, pulling them
+// back towards its center once they get within `margin` of the edge. The pull keeps
+// growing past the edge so strays are reeled back in.
+pub struct Corral {
+    pub center: Target,
+    pub radius: f64,
+    pub margin: f64,
+    // Acceleration at the very edge, in units per second squared.
+    pub strength: f64,
+}
+
+impl Corral {
+    // Acceleration towards `center`, none well inside the circle.
+    pub fn pull(&self, position: [f64; 2], center: [f64; 2]) -> [f64; 2] {
+        let dx = center[0] - position[0];
+        let dy = center[1] - position[1];
+        let distance = (dx * dx + dy * dy).sqrt();
+        let inner = (self.radius - self.margin).max(0.0);
+
+        if distance <= inner || distance < f64::EPSILON {
+            return [0.0, 0.0];
+        }
+
+        let depth = (distance - inner) / self.margin.max(f64::EPSILON);
+        let pull = depth * self.strength;
+
+        [dx / distance * pull, dy / distance * pull]
+    }
+}
+
+impl AI for Corral {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let entity = &entity.physics;
+
+        match self.center.resolve(context) {
+            Some(center) => {
+                let [ax, ay] = self.pull([entity.x, entity.y], center);
+                Steering::acceleration(ax, ay)
+            }
+            None => Steering::default(),
+        }
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        let (center, leader_id) = self.center.config();
+
+        Some(BehaviorConfig::Corral {
+            center,
+            leader_id,
+            radius: self.radius,
+            margin: self.margin,
+            strength: self.strength,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{snapshot, steer, with_context};
+    use super::*;
+
+    #[test]
+    fn corral_pulls_in_near_the_edge_only() {
+        let corral = Corral {
+            center: Target::Goal,
+            radius: 30.0,
+            margin: 10.0,
+            strength: 4.0,
+        };
+        let pull = |x| {
+            let snapshot = [snapshot(0, x, 50.0)];
+            with_context(&snapshot, |context| {
+                steer(
+                    &corral,
+                    0,
+                    &BehaviorContext {
+                        goal: Some([50.0, 50.0]),
+                        ..context
+                    },
+                )
+            })
+            .acceleration
+        };
+
+        assert_eq!(pull(50.0), [0.0, 0.0]);
+        assert_eq!(pull(65.0), [0.0, 0.0]);
+        assert_eq!(pull(75.0), [-2.0, 0.0]);
+        assert_eq!(pull(80.0), [-4.0, 0.0]);
+        assert_eq!(pull(25.0), [2.0, 0.0]);
+        // Past the edge the pull keeps growing.
+        assert_eq!(pull(90.0), [-8.0, 0.0]);
+    }
+}
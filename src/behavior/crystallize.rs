@@ -0,0 +1,99 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+use crate::entity::Physics;
+
+// Holds every neighbor at `spacing`, like a spring pulling in when they are farther and
+// pushing out when closer. With a few nearest neighbors each, six for a hexagonal
+// packing, entities settle into a lattice.
+pub struct Crystallize {
+    pub perception_radius: f64,
+    pub max_neighbors: Option<usize>,
+    // Preferred distance between centers.
+    pub spacing: f64,
+    // Acceleration per unit of distance off the spacing.
+    pub strength: f64,
+    // Fraction of the velocity lost per second, so the lattice comes to rest.
+    pub damping: f64,
+}
+
+impl Crystallize {
+    // Spring acceleration from the neighbors, towards those beyond the spacing and away
+    // from those within it.
+    pub fn force(&self, physics: &Physics, neighbors: &[Physics]) -> [f64; 2] {
+        let mut force = [0.0, 0.0];
+
+        for other in neighbors {
+            let dx = other.x - physics.x;
+            let dy = other.y - physics.y;
+            let distance = (dx * dx + dy * dy).sqrt();
+
+            if distance < f64::EPSILON {
+                continue;
+            }
+
+            let pull = self.strength * (distance - self.spacing);
+            force[0] += pull * dx / distance;
+            force[1] += pull * dy / distance;
+        }
+
+        force
+    }
+}
+
+impl AI for Crystallize {
+    fn compute(&self, entity: &Snapshot, neighbors: &[Snapshot], _: &BehaviorContext) -> Steering {
+        let neighbors: Vec<Physics> = neighbors.iter().map(|other| other.physics).collect();
+        let physics = &entity.physics;
+        let [ax, ay] = self.force(physics, &neighbors);
+
+        Steering::acceleration(
+            ax - self.damping * physics.vx,
+            ay - self.damping * physics.vy,
+        )
+    }
+
+    fn perception_radius(&self) -> Option<f64> {
+        Some(self.perception_radius)
+    }
+
+    fn max_neighbors(&self) -> Option<usize> {
+        self.max_neighbors
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Crystallize {
+            perception_radius: self.perception_radius,
+            max_neighbors: self.max_neighbors,
+            spacing: self.spacing,
+            strength: self.strength,
+            damping: self.damping,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{snapshot, steer, with_context};
+    use super::*;
+
+    #[test]
+    fn neighbors_are_held_at_the_spacing() {
+        let crystal = Crystallize {
+            perception_radius: 30.0,
+            max_neighbors: None,
+            spacing: 10.0,
+            strength: 2.0,
+            damping: 0.0,
+        };
+        let steering = |other_x| {
+            let snapshot = [snapshot(0, 50.0, 50.0), snapshot(1, other_x, 50.0)];
+            with_context(&snapshot, |context| steer(&crystal, 0, &context)).acceleration
+        };
+
+        assert_eq!(steering(54.0), [-12.0, 0.0]);
+        assert_eq!(steering(60.0), [0.0, 0.0]);
+        assert_eq!(steering(65.0), [10.0, 0.0]);
+        // Out of sight.
+        assert_eq!(steering(85.0), [0.0, 0.0]);
+    }
+}
@@ -0,0 +1,71 @@
+use rand::Rng;
+use rand_distr::StandardNormal;
+
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+
+// Biased random walk, a steady drift plus brownian noise.
+pub struct Diffuse {
+    // Units per second.
+    pub drift: [f64; 2],
+    // Standard deviation of the noise after one second, it grows with the square root
+    // of time like real diffusion.
+    pub noise: f64,
+}
+
+impl Diffuse {
+    pub fn displacement<R: Rng>(&self, dt: f64, rng: &mut R) -> [f64; 2] {
+        let spread = self.noise * dt.max(0.0).sqrt();
+        let nx: f64 = rng.sample(StandardNormal);
+        let ny: f64 = rng.sample(StandardNormal);
+
+        [
+            self.drift[0] * dt + nx * spread,
+            self.drift[1] * dt + ny * spread,
+        ]
+    }
+}
+
+impl AI for Diffuse {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        Steering {
+            displacement: self.displacement(context.dt, &mut context.rng(entity.id)),
+            ..Steering::default()
+        }
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Diffuse {
+            drift: self.drift,
+            noise: self.noise,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn mean_displacement_approaches_the_drift() {
+        let diffuse = Diffuse {
+            drift: [2.0, -1.0],
+            noise: 1.0,
+        };
+        let mut rng = StdRng::seed_from_u64(5);
+        let samples = 10_000;
+        let mut total = [0.0, 0.0];
+
+        for _ in 0..samples {
+            let [dx, dy] = diffuse.displacement(0.5, &mut rng);
+            total = [total[0] + dx, total[1] + dy];
+        }
+
+        let mean = [total[0] / samples as f64, total[1] / samples as f64];
+        assert!((mean[0] - 1.0).abs() < 0.05, "{:?}", mean);
+        assert!((mean[1] + 0.5).abs() < 0.05, "{:?}", mean);
+    }
+}
@@ -0,0 +1,103 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+
+// Probes around the entity, one per direction.
+const PROBES: usize = 8;
+
+// Spreads entities out to fill space evenly: counts the entities around points on a
+// ring about the entity and heads for the emptiest, staying put where it's no emptier
+// than where it is.
+pub struct Disperse {
+    // Distance of the probes from the entity, and the radius each counts within.
+    pub probe_distance: f64,
+    pub radius: f64,
+    // Units per second.
+    pub speed: f64,
+}
+
+impl Disperse {
+    // Unit direction to the least crowded probe around `center`, by `density` counting
+    // the entities near a point. None when no probe is less crowded than the center.
+    pub fn direction<F>(&self, center: [f64; 2], density: F) -> Option<[f64; 2]>
+    where
+        F: Fn([f64; 2]) -> usize,
+    {
+        let here = density(center);
+
+        (0..PROBES)
+            .map(|probe| {
+                let angle = probe as f64 * std::f64::consts::TAU / PROBES as f64;
+                let direction = [angle.cos(), angle.sin()];
+                let point = [
+                    center[0] + direction[0] * self.probe_distance,
+                    center[1] + direction[1] * self.probe_distance,
+                ];
+
+                (density(point), direction)
+            })
+            .filter(|&(count, _)| count < here)
+            .min_by_key(|&(count, _)| count)
+            .map(|(_, direction)| direction)
+    }
+}
+
+impl AI for Disperse {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let center = [entity.physics.x, entity.physics.y];
+        // The entity itself is left out wherever it falls.
+        let density = |point: [f64; 2]| {
+            context
+                .neighbor_index
+                .within_radius(point, self.radius)
+                .into_iter()
+                .filter(|&index| context.snapshot[index].id != entity.id)
+                .count()
+        };
+
+        match self.direction(center, density) {
+            Some([dx, dy]) => Steering::velocity(dx * self.speed, dy * self.speed),
+            None => Steering::velocity(0.0, 0.0),
+        }
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Disperse {
+            probe_distance: self.probe_distance,
+            radius: self.radius,
+            speed: self.speed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{snapshot, steer, with_context};
+    use super::*;
+
+    #[test]
+    fn entity_at_the_edge_of_a_crowd_heads_for_the_empty_side() {
+        let disperse = Disperse {
+            probe_distance: 10.0,
+            radius: 6.0,
+            speed: 4.0,
+        };
+        // A block of entities right of the disperser, nothing to its left.
+        let mut snapshot = vec![snapshot(0, 50.0, 50.0)];
+        for (i, x) in [52.0, 56.0, 60.0].iter().enumerate() {
+            for (j, y) in [44.0, 48.0, 52.0, 56.0].iter().enumerate() {
+                snapshot.push(super::super::testing::snapshot(
+                    1 + (i * 4 + j) as u32,
+                    *x,
+                    *y,
+                ));
+            }
+        }
+
+        let [vx, vy] = with_context(&snapshot, |context| steer(&disperse, 0, &context))
+            .velocity
+            .unwrap();
+
+        assert!(vx < 0.0);
+        assert!((vx.hypot(vy) - 4.0).abs() < 1e-12);
+    }
+}
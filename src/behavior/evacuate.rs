@@ -0,0 +1,143 @@
+use super::seek::seek_velocity;
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+use crate::entity::Physics;
+
+// Crowd heading for the nearest exit, keeping apart from each other, steering around
+// neighbors tagged `obstacle` and slowing down in a crush. The world goal counts as one
+// more exit when set, so exits can be added while it runs.
+pub struct Evacuate {
+    pub exits: Vec<[f64; 2]>,
+    // Units per second.
+    pub speed: f64,
+    pub perception_radius: f64,
+    pub max_neighbors: Option<usize>,
+    pub separation: f64,
+    pub obstacle: Option<String>,
+    pub avoidance: f64,
+    // Fraction of the speed lost to each neighbor in the crowd around it.
+    pub congestion: f64,
+}
+
+// The exit closest to `position`, none without exits.
+pub fn nearest_exit(position: [f64; 2], exits: &[[f64; 2]]) -> Option<[f64; 2]> {
+    let distance = |exit: &[f64; 2]| {
+        let dx = exit[0] - position[0];
+        let dy = exit[1] - position[1];
+        dx * dx + dy * dy
+    };
+
+    exits
+        .iter()
+        .copied()
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+}
+
+// Sum of the directions away from `others`, weighted by the inverse distance squared.
+fn away(physics: &Physics, others: &[Physics]) -> [f64; 2] {
+    others.iter().fold([0.0, 0.0], |[x, y], other| {
+        let dx = physics.x - other.x;
+        let dy = physics.y - other.y;
+        let distance_squared = dx * dx + dy * dy;
+
+        if distance_squared > f64::EPSILON {
+            [x + dx / distance_squared, y + dy / distance_squared]
+        } else {
+            [x, y]
+        }
+    })
+}
+
+impl Evacuate {
+    // Velocity towards `exit`, pushed off the crowd and obstacles, at most `speed`.
+    pub fn velocity(
+        &self,
+        physics: &Physics,
+        exit: Option<[f64; 2]>,
+        crowd: &[Physics],
+        obstacles: &[Physics],
+    ) -> [f64; 2] {
+        let speed = self.speed / (1.0 + self.congestion * crowd.len() as f64);
+        let [mut vx, mut vy] = match exit {
+            Some(exit) => seek_velocity([physics.x, physics.y], exit, speed),
+            None => [0.0, 0.0],
+        };
+        let [sx, sy] = away(physics, crowd);
+        let [ox, oy] = away(physics, obstacles);
+
+        vx += self.separation * sx + self.avoidance * ox;
+        vy += self.separation * sy + self.avoidance * oy;
+
+        let length = (vx * vx + vy * vy).sqrt();
+        if length > self.speed {
+            vx *= self.speed / length;
+            vy *= self.speed / length;
+        }
+
+        [vx, vy]
+    }
+}
+
+impl AI for Evacuate {
+    fn compute(
+        &self,
+        entity: &Snapshot,
+        neighbors: &[Snapshot],
+        context: &BehaviorContext,
+    ) -> Steering {
+        let entity = &entity.physics;
+        let mut exits = self.exits.clone();
+        exits.extend(context.goal);
+
+        let is_obstacle = |other: &Snapshot| match self.obstacle.as_ref() {
+            Some(tag) => context.tags_of(other.id).contains(tag),
+            None => false,
+        };
+        let (obstacles, crowd): (Vec<&Snapshot>, Vec<&Snapshot>) =
+            neighbors.iter().partition(|other| is_obstacle(other));
+        let crowd: Vec<Physics> = crowd.iter().map(|other| other.physics).collect();
+        let obstacles: Vec<Physics> = obstacles.iter().map(|other| other.physics).collect();
+        let exit = nearest_exit([entity.x, entity.y], &exits);
+        let [vx, vy] = self.velocity(entity, exit, &crowd, &obstacles);
+
+        Steering::velocity(vx, vy)
+    }
+
+    fn perception_radius(&self) -> Option<f64> {
+        Some(self.perception_radius)
+    }
+
+    fn max_neighbors(&self) -> Option<usize> {
+        self.max_neighbors
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Evacuate {
+            exits: self.exits.clone(),
+            speed: self.speed,
+            perception_radius: self.perception_radius,
+            max_neighbors: self.max_neighbors,
+            separation: self.separation,
+            obstacle: self.obstacle.clone(),
+            avoidance: self.avoidance,
+            congestion: self.congestion,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_exit_switches_when_a_closer_one_opens() {
+        let position = [50.0, 50.0];
+        let mut exits = vec![[0.0, 50.0], [100.0, 60.0]];
+
+        assert_eq!(nearest_exit(position, &[]), None);
+        assert_eq!(nearest_exit(position, &exits), Some([0.0, 50.0]));
+
+        exits.push([60.0, 45.0]);
+        assert_eq!(nearest_exit(position, &exits), Some([60.0, 45.0]));
+    }
+}
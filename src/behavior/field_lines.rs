@@ -0,0 +1,118 @@
+use serde::{Deserialize, Serialize};
+
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+use crate::entity::Physics;
+
+// Point magnet, its moment pointing from its south pole to its north pole.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct Dipole {
+    pub position: [f64; 2],
+    pub moment: [f64; 2],
+}
+
+// Field of the dipoles at `point`, each adding (3(m·r̂)r̂ - m) / r³. Points on top of a
+// dipole get nothing from it.
+pub fn dipole_field(dipoles: &[Dipole], point: [f64; 2]) -> [f64; 2] {
+    let mut field = [0.0, 0.0];
+
+    for dipole in dipoles {
+        let (dx, dy) = (point[0] - dipole.position[0], point[1] - dipole.position[1]);
+        let distance = dx.hypot(dy);
+        if distance <= f64::EPSILON {
+            continue;
+        }
+
+        let (nx, ny) = (dx / distance, dy / distance);
+        let along = dipole.moment[0] * nx + dipole.moment[1] * ny;
+        let cube = distance * distance * distance;
+        field[0] += (3.0 * along * nx - dipole.moment[0]) / cube;
+        field[1] += (3.0 * along * ny - dipole.moment[1]) / cube;
+    }
+
+    field
+}
+
+// Travels along the field lines of a set of dipoles, steering tangent to the field
+// wherever it is, from north to south poles.
+pub struct FieldLines {
+    pub dipoles: Vec<Dipole>,
+    // Units per second.
+    pub speed: f64,
+    // How quickly the velocity turns towards the field, per second.
+    pub strength: f64,
+}
+
+impl FieldLines {
+    // Acceleration bringing the velocity towards `speed` along the field, none where it
+    // vanishes.
+    pub fn steering(&self, physics: &Physics) -> [f64; 2] {
+        let [bx, by] = dipole_field(&self.dipoles, [physics.x, physics.y]);
+        let length = bx.hypot(by);
+        if !(length.is_finite() && length > f64::EPSILON) {
+            return [0.0, 0.0];
+        }
+
+        [
+            (bx / length * self.speed - physics.vx) * self.strength,
+            (by / length * self.speed - physics.vy) * self.strength,
+        ]
+    }
+}
+
+impl AI for FieldLines {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], _: &BehaviorContext) -> Steering {
+        let [ax, ay] = self.steering(&entity.physics);
+
+        Steering::acceleration(ax, ay)
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::FieldLines {
+            dipoles: self.dipoles.clone(),
+            speed: self.speed,
+            strength: self.strength,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: [f64; 2], b: [f64; 2]) -> bool {
+        (a[0] - b[0]).abs() < 1e-12 && (a[1] - b[1]).abs() < 1e-12
+    }
+
+    #[test]
+    fn dipole_field_matches_the_analytic_dipole() {
+        let magnet = Dipole {
+            position: [10.0, 10.0],
+            moment: [2.0, 0.0],
+        };
+        let dipoles = [magnet];
+
+        // 2m/r³ along the axis, -m/r³ across it.
+        assert!(close(dipole_field(&dipoles, [12.0, 10.0]), [0.5, 0.0]));
+        assert!(close(dipole_field(&dipoles, [8.0, 10.0]), [0.5, 0.0]));
+        assert!(close(dipole_field(&dipoles, [10.0, 12.0]), [-0.25, 0.0]));
+        // (3(m·r̂)r̂ - m) / r³ at 45°, r = √2.
+        let diagonal = 2.0 * std::f64::consts::SQRT_2;
+        assert!(close(
+            dipole_field(&dipoles, [11.0, 11.0]),
+            [1.0 / diagonal, 3.0 / diagonal]
+        ));
+        assert_eq!(dipole_field(&dipoles, [10.0, 10.0]), [0.0, 0.0]);
+
+        // Fields of several dipoles add up.
+        let mirrored = Dipole {
+            position: [14.0, 10.0],
+            ..magnet
+        };
+        assert!(close(
+            dipole_field(&[magnet, mirrored], [12.0, 10.0]),
+            [1.0, 0.0]
+        ));
+    }
+}
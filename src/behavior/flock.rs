@@ -0,0 +1,371 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+use crate::entity::Physics;
+
+// Boids style flocking: keep apart, match heading and stay together with the neighbors
+// inside `perception_radius`, and scatter from any predators among them. With a leader
+// the flock is also drawn towards it wherever it goes, a guided swarm, and with a
+// migration weight it travels along the world's migration path.
+pub struct Flock {
+    pub perception_radius: f64,
+    // Only the nearest this many count, so dense clumps stay cheap and responsive.
+    pub max_neighbors: Option<usize>,
+    pub separation: f64,
+    pub alignment: f64,
+    pub cohesion: f64,
+    // Units per second.
+    pub max_speed: f64,
+    // Neighbors with this tag are fled from rather than flocked with.
+    pub predator: Option<String>,
+    pub flee: f64,
+    // Id of the entity the flock follows, which doesn't follow itself.
+    pub leader: Option<u32>,
+    // Acceleration towards the leader per unit of distance.
+    pub leader_pull: f64,
+    // Acceleration towards the point a perception radius ahead on the migration path.
+    pub migration: f64,
+}
+
+// Point `lookahead` further along `path` than the point of it nearest `point`, the end
+// of the path once past it. None for an empty path.
+pub fn path_point_ahead(path: &[[f64; 2]], point: [f64; 2], lookahead: f64) -> Option<[f64; 2]> {
+    let (&first, rest) = path.split_first()?;
+    let length = |a: [f64; 2], b: [f64; 2]| (b[0] - a[0]).hypot(b[1] - a[1]);
+    let lerp =
+        |a: [f64; 2], b: [f64; 2], t: f64| [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t];
+
+    // Segment and how far along it the nearest point is.
+    let mut nearest = (0, 0.0, length(first, point));
+    for (index, pair) in path.windows(2).enumerate() {
+        let (a, b) = (pair[0], pair[1]);
+        let segment = length(a, b);
+        let t = if segment > f64::EPSILON {
+            (((point[0] - a[0]) * (b[0] - a[0]) + (point[1] - a[1]) * (b[1] - a[1]))
+                / (segment * segment))
+                .clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let distance = length(lerp(a, b, t), point);
+        if distance < nearest.2 {
+            nearest = (index, t, distance);
+        }
+    }
+
+    let (start, t, _) = nearest;
+    let mut left = lookahead;
+    let mut from = match rest.get(start) {
+        Some(&b) => lerp(path[start], b, t),
+        None => first,
+    };
+    for &to in rest.iter().skip(start) {
+        let segment = length(from, to);
+        if segment >= left {
+            return Some(lerp(from, to, left / segment.max(f64::EPSILON)));
+        }
+        left -= segment;
+        from = to;
+    }
+
+    Some(from)
+}
+
+impl Flock {
+    // Acceleration from the neighbors, who must already exclude the entity itself.
+    pub fn steering(&self, physics: &Physics, neighbors: &[Physics]) -> [f64; 2] {
+        if neighbors.is_empty() {
+            return [0.0, 0.0];
+        }
+
+        let count = neighbors.len() as f64;
+        let (mut away_x, mut away_y) = (0.0, 0.0);
+        let (mut vx, mut vy) = (0.0, 0.0);
+        let (mut x, mut y) = (0.0, 0.0);
+
+        for other in neighbors {
+            let dx = physics.x - other.x;
+            let dy = physics.y - other.y;
+            let distance_squared = dx * dx + dy * dy;
+
+            // Closer neighbors push harder, coincident ones can't give a direction.
+            if distance_squared > f64::EPSILON {
+                away_x += dx / distance_squared;
+                away_y += dy / distance_squared;
+            }
+
+            vx += other.vx;
+            vy += other.vy;
+            x += other.x;
+            y += other.y;
+        }
+
+        [
+            self.separation * away_x
+                + self.alignment * (vx / count - physics.vx)
+                + self.cohesion * (x / count - physics.x),
+            self.separation * away_y
+                + self.alignment * (vy / count - physics.vy)
+                + self.cohesion * (y / count - physics.y),
+        ]
+    }
+
+    // Acceleration towards the leader, on top of the flocking, none without one.
+    pub fn follow(&self, physics: &Physics, leader: Option<[f64; 2]>) -> [f64; 2] {
+        match leader {
+            Some([x, y]) => [
+                self.leader_pull * (x - physics.x),
+                self.leader_pull * (y - physics.y),
+            ],
+            None => [0.0, 0.0],
+        }
+    }
+
+    // Acceleration towards `ahead`, the point to head for on the migration path, none
+    // without one.
+    pub fn migrate(&self, physics: &Physics, ahead: Option<[f64; 2]>) -> [f64; 2] {
+        let [x, y] = match ahead {
+            Some(ahead) => ahead,
+            None => return [0.0, 0.0],
+        };
+        let (dx, dy) = (x - physics.x, y - physics.y);
+        let distance = dx.hypot(dy);
+        if distance <= f64::EPSILON {
+            return [0.0, 0.0];
+        }
+
+        [
+            self.migration * dx / distance,
+            self.migration * dy / distance,
+        ]
+    }
+
+    // Acceleration away from the predators, strongest up close and fading out at the
+    // edge of the perception radius.
+    pub fn flee(&self, physics: &Physics, predators: &[Physics]) -> [f64; 2] {
+        let (mut ax, mut ay) = (0.0, 0.0);
+
+        for predator in predators {
+            let dx = physics.x - predator.x;
+            let dy = physics.y - predator.y;
+            let distance = (dx * dx + dy * dy).sqrt();
+
+            if distance <= f64::EPSILON || distance >= self.perception_radius {
+                continue;
+            }
+
+            let urgency = 1.0 - distance / self.perception_radius;
+            ax += dx / distance * urgency;
+            ay += dy / distance * urgency;
+        }
+
+        [self.flee * ax, self.flee * ay]
+    }
+}
+
+impl AI for Flock {
+    fn compute(
+        &self,
+        entity: &Snapshot,
+        neighbors: &[Snapshot],
+        context: &BehaviorContext,
+    ) -> Steering {
+        let leader = self
+            .leader
+            .filter(|&id| id != entity.id)
+            .and_then(|id| context.find(id))
+            .map(|leader| [leader.physics.x, leader.physics.y]);
+        let entity = &entity.physics;
+        let ahead = if self.migration > 0.0 {
+            path_point_ahead(
+                context.migration_path,
+                [entity.x, entity.y],
+                self.perception_radius,
+            )
+        } else {
+            None
+        };
+        let is_predator = |other: &Snapshot| match self.predator.as_ref() {
+            Some(tag) => context.tags_of(other.id).contains(tag),
+            None => false,
+        };
+        let (predators, flockmates): (Vec<&Snapshot>, Vec<&Snapshot>) =
+            neighbors.iter().partition(|other| is_predator(other));
+        let flockmates: Vec<Physics> = flockmates.iter().map(|other| other.physics).collect();
+        let predators: Vec<Physics> = predators.iter().map(|other| other.physics).collect();
+        let [ax, ay] = self.steering(entity, &flockmates);
+        let [fx, fy] = self.flee(entity, &predators);
+        let [lx, ly] = self.follow(entity, leader);
+        let [mx, my] = self.migrate(entity, ahead);
+        let mut vx = entity.vx + (ax + fx + lx + mx) * context.dt;
+        let mut vy = entity.vy + (ay + fy + ly + my) * context.dt;
+
+        let speed = (vx * vx + vy * vy).sqrt();
+        if speed > self.max_speed {
+            vx *= self.max_speed / speed;
+            vy *= self.max_speed / speed;
+        }
+
+        Steering::velocity(vx, vy)
+    }
+
+    fn perception_radius(&self) -> Option<f64> {
+        Some(self.perception_radius)
+    }
+
+    fn max_neighbors(&self) -> Option<usize> {
+        self.max_neighbors
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Flock {
+            perception_radius: self.perception_radius,
+            max_neighbors: self.max_neighbors,
+            separation: self.separation,
+            alignment: self.alignment,
+            cohesion: self.cohesion,
+            max_speed: self.max_speed,
+            predator: self.predator.clone(),
+            flee: self.flee,
+            leader_id: self.leader,
+            leader_pull: self.leader_pull,
+            migration: self.migration,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{snapshot, steer, with_context};
+    use super::*;
+
+    // Pure cohesion, so the steering points at the neighbors' center.
+    fn cohesive(perception_radius: f64) -> Flock {
+        Flock {
+            perception_radius,
+            max_neighbors: None,
+            separation: 0.0,
+            alignment: 0.0,
+            cohesion: 1.0,
+            max_speed: 1000.0,
+            predator: None,
+            flee: 0.0,
+            leader: None,
+            leader_pull: 0.0,
+            migration: 0.0,
+        }
+    }
+
+    #[test]
+    fn perception_radius_decides_who_counts() {
+        let snapshot = [snapshot(0, 50.0, 50.0), snapshot(1, 70.0, 50.0)];
+
+        with_context(&snapshot, |context| {
+            assert_eq!(
+                steer(&cohesive(10.0), 0, &context),
+                Steering::velocity(0.0, 0.0)
+            );
+            assert_eq!(
+                steer(&cohesive(30.0), 0, &context),
+                Steering::velocity(2.0, 0.0)
+            );
+        });
+    }
+
+    #[test]
+    fn max_neighbors_keeps_only_the_nearest() {
+        let snapshot: Vec<Snapshot> = [50.0, 52.0, 54.0, 56.0, 70.0, 80.0]
+            .iter()
+            .enumerate()
+            .map(|(id, &x)| snapshot(id as u32, x, 50.0))
+            .collect();
+        let nearest_three = Flock {
+            max_neighbors: Some(3),
+            ..cohesive(40.0)
+        };
+
+        with_context(&snapshot, |context| {
+            let vx = |flock: &Flock| steer(flock, 0, &context).velocity.unwrap()[0];
+
+            // Towards x = 54 rather than the mean of all five at 62.4.
+            assert!((vx(&nearest_three) - 0.4).abs() < 1e-9);
+            assert!((vx(&cohesive(40.0)) - 1.24).abs() < 1e-9);
+        });
+    }
+
+    #[test]
+    fn fleeing_a_close_predator_outweighs_flocking() {
+        let snapshot = [
+            snapshot(0, 50.0, 50.0),
+            snapshot(1, 55.0, 50.0),
+            snapshot(2, 65.0, 50.0),
+        ];
+        let tags = [vec![], vec!["shark".to_string()], vec![]];
+        let tags: Vec<&[String]> = tags.iter().map(Vec::as_slice).collect();
+        let wary = Flock {
+            predator: Some("shark".to_string()),
+            flee: 100.0,
+            ..cohesive(30.0)
+        };
+
+        with_context(&snapshot, |context| {
+            let context = BehaviorContext {
+                tags: &tags,
+                ..context
+            };
+            let vx = |flock: &Flock| steer(flock, 0, &context).velocity.unwrap()[0];
+
+            // Both others are towards +x, the predator closest.
+            assert!(vx(&cohesive(30.0)) > 0.0);
+            assert!(vx(&wary) < 0.0);
+        });
+    }
+
+    #[test]
+    fn the_leader_pull_adds_to_the_flocking() {
+        let snapshot = [
+            snapshot(0, 50.0, 50.0),
+            snapshot(1, 60.0, 50.0),
+            snapshot(2, 50.0, 80.0),
+        ];
+        let guided = Flock {
+            leader: Some(2),
+            leader_pull: 0.5,
+            ..cohesive(15.0)
+        };
+
+        with_context(&snapshot, |context| {
+            // Cohesion towards the flockmate along x, the leader out of sight along y.
+            assert_eq!(steer(&guided, 0, &context), Steering::velocity(1.0, 1.5));
+            // The leader itself only flocks.
+            assert_eq!(steer(&guided, 2, &context), Steering::velocity(0.0, 0.0));
+        });
+    }
+
+    #[test]
+    fn migration_along_the_path_adds_to_the_flocking() {
+        let snapshot = [snapshot(0, 50.0, 60.0), snapshot(1, 40.0, 60.0)];
+        let path = [[0.0, 50.0], [100.0, 50.0]];
+        let migrating = Flock {
+            migration: 2.0,
+            ..cohesive(15.0)
+        };
+
+        // A perception radius past the nearest point of the path.
+        assert_eq!(
+            path_point_ahead(&path, [50.0, 60.0], 15.0),
+            Some([65.0, 50.0])
+        );
+        with_context(&snapshot, |context| {
+            let context = BehaviorContext {
+                migration_path: &path,
+                ..context
+            };
+            let [vx, vy] = steer(&migrating, 0, &context).velocity.unwrap();
+            // Cohesion of -1 along x, then 2 towards (65, 50) for a tenth of a second.
+            let (dx, dy) = (15.0 / 325f64.sqrt(), -10.0 / 325f64.sqrt());
+
+            assert!((vx - (-1.0 + 0.2 * dx)).abs() < 1e-9);
+            assert!((vy - 0.2 * dy).abs() < 1e-9);
+        });
+    }
+}
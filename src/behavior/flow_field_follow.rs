@@ -0,0 +1,84 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+use crate::entity::Physics;
+
+// Paths to the goal of the world's flow field, turning towards the direction of
+// whichever cell the entity is in. Entities on the goal or off the field keep going.
+pub struct FlowFieldFollow {
+    // Units per second along the field.
+    pub speed: f64,
+    // How quickly the velocity turns towards the field's, per second.
+    pub strength: f64,
+}
+
+impl FlowFieldFollow {
+    // Acceleration bringing the velocity towards `speed` along `direction`.
+    pub fn steering(&self, physics: &Physics, direction: [f64; 2]) -> [f64; 2] {
+        [
+            (direction[0] * self.speed - physics.vx) * self.strength,
+            (direction[1] * self.speed - physics.vy) * self.strength,
+        ]
+    }
+}
+
+impl AI for FlowFieldFollow {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let entity = &entity.physics;
+        let direction = match context
+            .flow_field
+            .and_then(|field| field.direction_at([entity.x, entity.y]))
+        {
+            Some(direction) => direction,
+            None => return Steering::default(),
+        };
+        let [ax, ay] = self.steering(entity, direction);
+
+        Steering::acceleration(ax, ay)
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::FlowFieldFollow {
+            speed: self.speed,
+            strength: self.strength,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{Entity, Renderer};
+    use crate::flowfield::FlowField;
+    use crate::shapes::Circle;
+    use crate::world::World;
+
+    #[test]
+    fn following_the_field_reaches_the_goal_cell() {
+        let follower = Entity::new(
+            0,
+            Physics::new(15.0, 85.0, 2.0).unwrap(),
+            Renderer::Owned(Box::new(Circle::new([1.0; 4]))),
+        )
+        .with_behavior(Box::new(FlowFieldFollow {
+            speed: 20.0,
+            strength: 10.0,
+        }));
+        let mut world = World::new(vec![follower], 100.0, 100.0).unwrap();
+        let field = FlowField::new([0.0, 0.0], 10.0, 10, 10, [55.0, 55.0], &[]).unwrap();
+        let goal = field.cell_of([55.0, 55.0]);
+        world.flow_field = Some(field);
+
+        let reached = (0..100).any(|_| {
+            world.step(0.1);
+            let physics = &world.entities[0].physics;
+            world
+                .flow_field
+                .as_ref()
+                .unwrap()
+                .cell_of([physics.x, physics.y])
+                == goal
+        });
+
+        assert!(reached);
+    }
+}
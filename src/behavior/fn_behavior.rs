@@ -0,0 +1,63 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::entity::Entity;
+
+pub type Script = Box<dyn FnMut(&mut Entity, f64) + Send + Sync>;
+
+// Runs a closure as a behavior, for quick experiments without an `AI` struct. It gets
+// the entity and dt once the entity has been steered, the changes it makes are kept as
+// the world moves the entity on as usual.
+pub struct FnBehavior(pub Script);
+
+impl FnBehavior {
+    pub fn new<F>(script: F) -> FnBehavior
+    where
+        F: FnMut(&mut Entity, f64) + Send + Sync + 'static,
+    {
+        FnBehavior(Box::new(script))
+    }
+}
+
+impl AI for FnBehavior {
+    // The closure needs the entity itself, so it runs in `update` instead.
+    fn compute(&self, _: &Snapshot, _: &[Snapshot], _: &BehaviorContext) -> Steering {
+        Steering::default()
+    }
+
+    fn update(&mut self, entity: &mut Entity, dt: f64) {
+        (self.0)(entity, dt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{Physics, Renderer};
+    use crate::shapes::Circle;
+    use crate::world::World;
+
+    #[test]
+    fn boxed_closure_moves_its_entity() {
+        let behavior: Box<dyn AI> = Box::new(FnBehavior::new(|entity: &mut Entity, dt| {
+            entity.physics.x += dt;
+        }));
+        let entity = Entity::new(
+            0,
+            Physics::new(10.0, 10.0, 2.0).unwrap(),
+            Renderer::Owned(Box::new(Circle::new([1.0; 4]))),
+        )
+        .with_behavior(behavior);
+        let mut world = World::new(vec![entity], 100.0, 100.0).unwrap();
+
+        for _ in 0..4 {
+            world.step(0.25);
+        }
+
+        assert!((world.entities[0].physics.x - 11.0).abs() < 1e-9);
+        assert!(world.entities[0]
+            .behavior
+            .as_ref()
+            .unwrap()
+            .to_tag()
+            .is_none());
+    }
+}
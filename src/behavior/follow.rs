@@ -0,0 +1,124 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+
+// Trails another entity at a fixed distance, chaining these gives a snake.
+pub struct Follow {
+    pub leader_id: u32,
+    pub distance: f64,
+    // Units per second.
+    pub speed: f64,
+}
+
+impl Follow {
+    // Point `distance` behind the leader, opposite to where it is heading, or on the
+    // line from the leader to the follower when the leader stands still.
+    pub fn slot(
+        &self,
+        leader: [f64; 2],
+        leader_velocity: [f64; 2],
+        follower: [f64; 2],
+    ) -> [f64; 2] {
+        let speed = (leader_velocity[0].powi(2) + leader_velocity[1].powi(2)).sqrt();
+        let (dx, dy) = if speed > f64::EPSILON {
+            (-leader_velocity[0] / speed, -leader_velocity[1] / speed)
+        } else {
+            let (dx, dy) = (follower[0] - leader[0], follower[1] - leader[1]);
+            let length = (dx * dx + dy * dy).sqrt();
+
+            if length > f64::EPSILON {
+                (dx / length, dy / length)
+            } else {
+                (0.0, 0.0)
+            }
+        };
+
+        [
+            leader[0] + dx * self.distance,
+            leader[1] + dy * self.distance,
+        ]
+    }
+}
+
+impl AI for Follow {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let entity = &entity.physics;
+        let leader = match context.find(self.leader_id) {
+            Some(leader) => leader.physics,
+            None => return Steering::velocity(0.0, 0.0),
+        };
+
+        let target = self.slot(
+            [leader.x, leader.y],
+            [leader.vx, leader.vy],
+            [entity.x, entity.y],
+        );
+        let dx = target[0] - entity.x;
+        let dy = target[1] - entity.y;
+        let distance = (dx * dx + dy * dy).sqrt();
+
+        if distance < f64::EPSILON {
+            return Steering::velocity(leader.vx, leader.vy);
+        }
+
+        let speed = self.speed.min(distance);
+        Steering::velocity(dx / distance * speed, dy / distance * speed)
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Follow {
+            leader_id: self.leader_id,
+            distance: self.distance,
+            speed: self.speed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{Entity, Physics, Renderer};
+    use crate::shapes::Circle;
+    use crate::world::World;
+
+    fn follower(id: u32, x: f64, leader_id: u32) -> Entity {
+        Entity::new(
+            id,
+            Physics::new(x, 50.0, 2.0).unwrap(),
+            Renderer::Owned(Box::new(Circle::new([1.0; 4]))),
+        )
+        .with_behavior(Box::new(Follow {
+            leader_id,
+            distance: 10.0,
+            speed: 100.0,
+        }))
+    }
+
+    #[test]
+    fn chain_holds_the_distance_and_idles_without_a_leader() {
+        let leader = Entity::new(
+            0,
+            Physics::new(20.0, 50.0, 2.0).unwrap(),
+            Renderer::Owned(Box::new(Circle::new([1.0; 4]))),
+        );
+        let mut world = World::new(
+            vec![
+                leader,
+                follower(1, 60.0, 0),
+                follower(2, 90.0, 1),
+                follower(3, 70.0, 9),
+            ],
+            200.0,
+            100.0,
+        )
+        .unwrap();
+
+        for _ in 0..300 {
+            world.step(0.1);
+        }
+
+        let x = |id: u32| world.get_by_id(id).unwrap().physics.x;
+        assert!((x(1) - x(0) - 10.0).abs() < 1e-3);
+        assert!((x(2) - x(1) - 10.0).abs() < 1e-3);
+        assert_eq!(x(3), 70.0);
+    }
+}
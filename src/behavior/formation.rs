@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+
+use super::seek::seek_velocity;
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FormationShape {
+    // Slots alternate left and right, each pair a rank further back.
+    V,
+    // Single file behind the leader.
+    Line,
+}
+
+// Offset of a slot from the leader as (behind, right), in multiples of the spacing.
+pub fn slot_offset(shape: FormationShape, slot: usize) -> [f64; 2] {
+    match shape {
+        FormationShape::V => {
+            let rank = (slot / 2 + 1) as f64;
+            let side = if slot.is_multiple_of(2) { -1.0 } else { 1.0 };
+            [rank, side * rank]
+        }
+        FormationShape::Line => [(slot + 1) as f64, 0.0],
+    }
+}
+
+// Holds a slot behind a leader, moving with it and closing in on the slot.
+pub struct Formation {
+    pub leader_id: u32,
+    pub shape: FormationShape,
+    // Which slot this entity takes, 0 being closest to the leader.
+    pub slot: usize,
+    // Distance between neighboring slots.
+    pub spacing: f64,
+    // Units per second on top of the leader's velocity.
+    pub speed: f64,
+}
+
+impl Formation {
+    // Where the slot is for a leader at `position` heading along the unit `heading`.
+    pub fn target(&self, position: [f64; 2], heading: [f64; 2]) -> [f64; 2] {
+        let [behind, right] = slot_offset(self.shape, self.slot);
+        // Right of the heading with y down, as on screen.
+        let side = [-heading[1], heading[0]];
+
+        [
+            position[0] - heading[0] * behind * self.spacing + side[0] * right * self.spacing,
+            position[1] - heading[1] * behind * self.spacing + side[1] * right * self.spacing,
+        ]
+    }
+}
+
+impl AI for Formation {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let entity = &entity.physics;
+        let leader = match context.find(self.leader_id) {
+            Some(leader) => leader.physics,
+            None => return Steering::velocity(0.0, 0.0),
+        };
+
+        // A leader standing still keeps the formation pointing where it faces.
+        let speed = (leader.vx * leader.vx + leader.vy * leader.vy).sqrt();
+        let heading = if speed > f64::EPSILON {
+            [leader.vx / speed, leader.vy / speed]
+        } else {
+            [leader.rotation.sin(), -leader.rotation.cos()]
+        };
+        let target = self.target([leader.x, leader.y], heading);
+        let [vx, vy] = seek_velocity([entity.x, entity.y], target, self.speed);
+
+        Steering::velocity(leader.vx + vx, leader.vy + vy)
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Formation {
+            leader_id: self.leader_id,
+            shape: self.shape,
+            slot: Some(self.slot),
+            spacing: self.spacing,
+            speed: self.speed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{snapshot, steer, with_context};
+    use super::*;
+
+    #[test]
+    fn v_followers_steer_to_their_own_side() {
+        let mut leader = snapshot(0, 50.0, 50.0);
+        leader.physics.vx = 10.0;
+        let snapshot = [leader, snapshot(1, 40.0, 50.0), snapshot(2, 40.0, 50.0)];
+        let follower = |slot| Formation {
+            leader_id: 0,
+            shape: FormationShape::V,
+            slot,
+            spacing: 10.0,
+            speed: 5.0,
+        };
+
+        assert_eq!(slot_offset(FormationShape::V, 0), [1.0, -1.0]);
+        assert_eq!(slot_offset(FormationShape::V, 1), [1.0, 1.0]);
+        assert_eq!(slot_offset(FormationShape::V, 2), [2.0, -2.0]);
+        assert_eq!(slot_offset(FormationShape::Line, 2), [3.0, 0.0]);
+
+        let (left, right) = with_context(&snapshot, |context| {
+            (
+                steer(&follower(0), 1, &context),
+                steer(&follower(1), 2, &context),
+            )
+        });
+
+        // Heading along +x with y down, the left slot is above the leader's track.
+        assert_eq!(left.velocity, Some([10.0, -5.0]));
+        assert_eq!(right.velocity, Some([10.0, 5.0]));
+    }
+}
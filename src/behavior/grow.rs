@@ -0,0 +1,54 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+
+// Increases the entity's size over time, renderer and collisions follow `physics.size`.
+pub struct Grow {
+    // Size gained per second.
+    pub rate: f64,
+    pub max_size: f64,
+}
+
+impl Grow {
+    pub fn grown(&self, size: f64, dt: f64) -> f64 {
+        if size >= self.max_size {
+            return size;
+        }
+
+        (size + self.rate * dt).min(self.max_size)
+    }
+}
+
+impl AI for Grow {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let entity = &entity.physics;
+        Steering {
+            size: Some(self.grown(entity.size, context.dt)),
+            ..Steering::default()
+        }
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Grow {
+            rate: self.rate,
+            max_size: self.max_size,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grows_by_the_rate_until_the_max_size() {
+        let grow = Grow {
+            rate: 2.0,
+            max_size: 10.0,
+        };
+
+        assert_eq!(grow.grown(4.0, 0.5), 5.0);
+        assert_eq!(grow.grown(9.5, 0.5), 10.0);
+        assert_eq!(grow.grown(10.0, 0.5), 10.0);
+        assert_eq!(grow.grown(12.0, 0.5), 12.0);
+    }
+}
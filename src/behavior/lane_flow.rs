@@ -0,0 +1,76 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+use crate::entity::Physics;
+
+// Follows the world's lane field, turning towards the flow of whichever cell the entity
+// is in. Off-lane entities keep going as they were.
+pub struct LaneFlow {
+    // Units per second along the lane.
+    pub speed: f64,
+    // How quickly the velocity turns towards the lane's, per second.
+    pub strength: f64,
+}
+
+impl LaneFlow {
+    // Acceleration bringing the velocity towards `speed` along `direction`.
+    pub fn steering(&self, physics: &Physics, direction: [f64; 2]) -> [f64; 2] {
+        [
+            (direction[0] * self.speed - physics.vx) * self.strength,
+            (direction[1] * self.speed - physics.vy) * self.strength,
+        ]
+    }
+}
+
+impl AI for LaneFlow {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let entity = &entity.physics;
+        let direction = match context
+            .lanes
+            .and_then(|lanes| lanes.direction_at([entity.x, entity.y]))
+        {
+            Some(direction) => direction,
+            None => return Steering::default(),
+        };
+        let [ax, ay] = self.steering(entity, direction);
+
+        Steering::acceleration(ax, ay)
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::LaneFlow {
+            speed: self.speed,
+            strength: self.strength,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{snapshot, steer, with_context};
+    use super::*;
+    use crate::lanes::LaneField;
+
+    #[test]
+    fn entity_in_a_lane_cell_takes_its_direction() {
+        // Two cells: flowing up on the left, off-lane on the right.
+        let lanes = LaneField::new([0.0, 0.0], 50.0, 2, vec![[0.0, -3.0], [0.0, 0.0]]).unwrap();
+        let lane_flow = LaneFlow {
+            speed: 4.0,
+            strength: 2.0,
+        };
+        let snapshot = [snapshot(0, 20.0, 20.0), snapshot(1, 70.0, 20.0)];
+
+        with_context(&snapshot, |context| {
+            let context = BehaviorContext {
+                lanes: Some(&lanes),
+                ..context
+            };
+
+            assert_eq!(
+                steer(&lane_flow, 0, &context),
+                Steering::acceleration(0.0, -8.0)
+            );
+            assert_eq!(steer(&lane_flow, 1, &context), Steering::default());
+        });
+    }
+}
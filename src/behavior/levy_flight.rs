@@ -0,0 +1,149 @@
+use std::f64::consts::PI;
+use std::sync::Mutex;
+
+use rand::Rng;
+
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+use crate::error::NatureError;
+
+// Step length drawn from a Pareto distribution, at least `min_step` and with a tail
+// heavier the smaller `alpha` is: the chance of a step past L falls as L^-alpha.
+pub fn levy_step<R: Rng>(rng: &mut R, min_step: f64, alpha: f64, max_step: Option<f64>) -> f64 {
+    // 1 - u is in (0, 1], which keeps the power finite.
+    let u: f64 = 1.0 - rng.gen::<f64>();
+    let step = min_step * u.powf(-1.0 / alpha);
+
+    match max_step {
+        Some(max_step) => step.min(max_step),
+        None => step,
+    }
+}
+
+// Leg of the flight in progress.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Leg {
+    direction: [f64; 2],
+    remaining: f64,
+}
+
+// Forages in straight legs of heavy-tailed length, many short ones and the odd long
+// jump, each in a uniformly random direction and covered at a constant speed.
+pub struct LevyFlight {
+    // Units per second along a leg.
+    pub speed: f64,
+    pub min_step: f64,
+    // Tail exponent, between 1 and 3 for the classic foraging regime.
+    pub alpha: f64,
+    // Caps the length of a leg, unbounded when not set.
+    pub max_step: Option<f64>,
+    leg: Mutex<Option<Leg>>,
+}
+
+impl LevyFlight {
+    pub fn new(
+        speed: f64,
+        min_step: f64,
+        alpha: f64,
+        max_step: Option<f64>,
+    ) -> Result<LevyFlight, NatureError> {
+        if !(speed.is_finite() && speed > 0.0 && min_step.is_finite() && min_step > 0.0) {
+            return Err(NatureError::InvalidParameter(format!(
+                "levy flight speed and min_step must be positive, got {} and {}",
+                speed, min_step
+            )));
+        }
+        if !(alpha.is_finite() && alpha > 0.0) {
+            return Err(NatureError::InvalidParameter(format!(
+                "levy flight alpha must be positive, got {}",
+                alpha
+            )));
+        }
+        if let Some(max_step) =
+            max_step.filter(|&max_step| max_step.is_nan() || max_step < min_step)
+        {
+            return Err(NatureError::InvalidParameter(format!(
+                "levy flight max_step must be at least min_step, got {}",
+                max_step
+            )));
+        }
+
+        Ok(LevyFlight {
+            speed,
+            min_step,
+            alpha,
+            max_step,
+            leg: Mutex::new(None),
+        })
+    }
+}
+
+impl AI for LevyFlight {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let mut leg = self
+            .leg
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let current = match *leg {
+            Some(current) if current.remaining > 0.0 => current,
+            _ => {
+                let mut rng = context.rng(entity.id);
+                let angle = rng.gen_range(0.0..2.0 * PI);
+
+                Leg {
+                    direction: [angle.cos(), angle.sin()],
+                    remaining: levy_step(&mut rng, self.min_step, self.alpha, self.max_step),
+                }
+            }
+        };
+        let [dx, dy] = current.direction;
+
+        *leg = Some(Leg {
+            remaining: current.remaining - self.speed * context.dt,
+            ..current
+        });
+
+        Steering {
+            velocity: Some([dx * self.speed, dy * self.speed]),
+            rotation: Some(dx.atan2(-dy)),
+            ..Steering::default()
+        }
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::LevyFlight {
+            speed: self.speed,
+            min_step: self.min_step,
+            alpha: self.alpha,
+            max_step: self.max_step,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn levy_steps_have_a_heavy_tail() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let steps: Vec<f64> = (0..20_000)
+            .map(|_| levy_step(&mut rng, 1.0, 1.5, None))
+            .collect();
+        let past = |length: f64| {
+            steps.iter().filter(|&&step| step > length).count() as f64 / steps.len() as f64
+        };
+
+        assert!(steps.iter().all(|&step| step >= 1.0));
+        // P(step > L) = L^-1.5, about 3.2% past 10 and 0.1% past 100.
+        assert!((past(10.0) - 0.0316).abs() < 0.005);
+        assert!(past(100.0) > 0.0003 && past(100.0) < 0.002);
+
+        let capped = levy_step(&mut StdRng::seed_from_u64(11), 1.0, 0.01, Some(50.0));
+        assert_eq!(capped, 50.0);
+    }
+}
@@ -0,0 +1,100 @@
+use std::f64::consts::TAU;
+
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::color::id_phase;
+use crate::config::BehaviorConfig;
+
+// Carries the entity along a Lissajous curve around `center` as simulated time goes on,
+// ignoring where it was. Entities are spread along the curve by id.
+pub struct Lissajous {
+    // Angular frequencies along x and y, the curve closing when their ratio is rational.
+    pub a: f64,
+    pub b: f64,
+    // Phase of x ahead of y, in radians.
+    pub delta: f64,
+    pub scale: f64,
+    pub center: [f64; 2],
+}
+
+impl Lissajous {
+    // Point on the curve at `time`, `phase` seconds further along.
+    pub fn position(&self, time: f64, phase: f64) -> [f64; 2] {
+        let t = time + phase;
+
+        [
+            self.center[0] + self.scale * (self.a * t + self.delta).sin(),
+            self.center[1] + self.scale * (self.b * t).sin(),
+        ]
+    }
+
+    // How far along the curve the entity `id` runs, in seconds.
+    pub fn phase(id: u32) -> f64 {
+        id_phase(id) * TAU
+    }
+}
+
+impl AI for Lissajous {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        // Where the curve is once this step is over.
+        let [x, y] = self.position(context.sim_time + context.dt, Lissajous::phase(entity.id));
+
+        Steering {
+            velocity: Some([0.0, 0.0]),
+            displacement: [x - entity.physics.x, y - entity.physics.y],
+            ..Steering::default()
+        }
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Lissajous {
+            a: self.a,
+            b: self.b,
+            delta: self.delta,
+            scale: self.scale,
+            center: self.center,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use super::*;
+    use crate::entity::{Entity, Physics, Renderer};
+    use crate::shapes::Circle;
+    use crate::world::World;
+
+    #[test]
+    fn follows_the_analytic_curve_over_simulated_time() {
+        let curve = Lissajous {
+            a: 3.0,
+            b: 2.0,
+            delta: FRAC_PI_2,
+            scale: 20.0,
+            center: [50.0, 50.0],
+        };
+        let analytic = |t: f64| {
+            [
+                50.0 + 20.0 * (3.0 * t + FRAC_PI_2).sin(),
+                50.0 + 20.0 * (2.0 * t).sin(),
+            ]
+        };
+        assert_eq!(curve.position(0.0, 0.0), [70.0, 50.0]);
+
+        let entity = Entity::new(
+            3,
+            Physics::new(10.0, 10.0, 2.0).unwrap(),
+            Renderer::Owned(Box::new(Circle::new([1.0; 4]))),
+        )
+        .with_behavior(Box::new(curve));
+        let mut world = World::new(vec![entity], 100.0, 100.0).unwrap();
+        for _ in 0..5 {
+            world.step(0.1);
+        }
+
+        let physics = world.get_by_id(3).unwrap().physics;
+        let [x, y] = analytic(0.5 + Lissajous::phase(3));
+        assert!((physics.x - x).abs() < 1e-9 && (physics.y - y).abs() < 1e-9);
+    }
+}
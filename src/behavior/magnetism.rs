@@ -0,0 +1,132 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+use crate::entity::Physics;
+
+// Keeps the force finite when two charges sit on top of each other.
+const SOFTENING: f64 = 5.0;
+
+// Coulomb-style force between tagged entities, opposite tags attract and equal tags
+// repel. Untagged entities neither feel nor exert it.
+pub struct Magnetism {
+    pub perception_radius: f64,
+    pub max_neighbors: Option<usize>,
+    // Acceleration between two unit charges at unit distance.
+    pub strength: f64,
+    pub positive: String,
+    pub negative: String,
+}
+
+impl Magnetism {
+    pub fn charge(&self, tags: &[String]) -> f64 {
+        if tags.contains(&self.positive) {
+            1.0
+        } else if tags.contains(&self.negative) {
+            -1.0
+        } else {
+            0.0
+        }
+    }
+
+    // Acceleration on a body of charge `charge` from charged neighbors.
+    pub fn force(&self, physics: &Physics, charge: f64, neighbors: &[(Physics, f64)]) -> [f64; 2] {
+        let mut force = [0.0, 0.0];
+
+        for (other, other_charge) in neighbors {
+            let dx = physics.x - other.x;
+            let dy = physics.y - other.y;
+            let distance_squared = dx * dx + dy * dy;
+            let distance = distance_squared.sqrt();
+
+            if distance < f64::EPSILON {
+                continue;
+            }
+
+            // Positive for like charges, pushing away from the other.
+            let push =
+                self.strength * charge * other_charge / (distance_squared + SOFTENING * SOFTENING);
+            force[0] += push * dx / distance;
+            force[1] += push * dy / distance;
+        }
+
+        force
+    }
+}
+
+impl AI for Magnetism {
+    fn compute(
+        &self,
+        entity: &Snapshot,
+        neighbors: &[Snapshot],
+        context: &BehaviorContext,
+    ) -> Steering {
+        let charge = self.charge(context.tags_of(entity.id));
+
+        if charge == 0.0 {
+            return Steering::default();
+        }
+
+        let charged: Vec<(Physics, f64)> = neighbors
+            .iter()
+            .map(|other| (other.physics, self.charge(context.tags_of(other.id))))
+            .filter(|&(_, other_charge)| other_charge != 0.0)
+            .collect();
+        let [ax, ay] = self.force(&entity.physics, charge, &charged);
+
+        Steering::acceleration(ax, ay)
+    }
+
+    fn perception_radius(&self) -> Option<f64> {
+        Some(self.perception_radius)
+    }
+
+    fn max_neighbors(&self) -> Option<usize> {
+        self.max_neighbors
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Magnetism {
+            perception_radius: self.perception_radius,
+            max_neighbors: self.max_neighbors,
+            strength: self.strength,
+            positive: self.positive.clone(),
+            negative: self.negative.clone(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{snapshot, steer, with_context};
+    use super::*;
+
+    #[test]
+    fn opposite_tags_attract_and_equal_tags_repel() {
+        let magnetism = Magnetism {
+            perception_radius: 50.0,
+            max_neighbors: None,
+            strength: 100.0,
+            positive: "positive".to_string(),
+            negative: "negative".to_string(),
+        };
+        let snapshot = [snapshot(0, 40.0, 50.0), snapshot(1, 60.0, 50.0)];
+        let charges =
+            |first: &str, second: &str| vec![vec![first.to_string()], vec![second.to_string()]];
+        let ax = |tags: Vec<Vec<String>>| {
+            let tags: Vec<&[String]> = tags.iter().map(Vec::as_slice).collect();
+            with_context(&snapshot, |context| {
+                let context = BehaviorContext {
+                    tags: &tags,
+                    ..context
+                };
+                steer(&magnetism, 0, &context).acceleration[0]
+            })
+        };
+
+        // The other entity is towards +x.
+        assert!(ax(charges("positive", "negative")) > 0.0);
+        assert!(ax(charges("negative", "positive")) > 0.0);
+        assert!(ax(charges("positive", "positive")) < 0.0);
+        assert!(ax(charges("negative", "negative")) < 0.0);
+        assert_eq!(ax(charges("positive", "neutral")), 0.0);
+    }
+}
@@ -0,0 +1,560 @@
+use std::collections::HashMap;
+use std::f64::consts::{PI, TAU};
+use std::sync::OnceLock;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::collision::mass;
+use crate::config::BehaviorConfig;
+use crate::coords::CoordinateSystem;
+use crate::entity::{Entity, Physics};
+use crate::field::ScalarField;
+use crate::flowfield::FlowField;
+use crate::forces::Attractor;
+use crate::lanes::LaneField;
+use crate::pathfinding::NavGrid;
+use crate::pheromone::PheromoneGrid;
+use crate::spatial::{NeighborQuery, QuadTree};
+use crate::vectorfield::VectorField;
+
+mod ant;
+mod chemotaxis;
+mod color_cluster;
+mod containment;
+mod convection;
+mod corral;
+mod crystallize;
+mod diffuse;
+mod disperse;
+mod evacuate;
+mod field_lines;
+mod flock;
+mod flow_field_follow;
+mod fn_behavior;
+mod follow;
+mod formation;
+mod grow;
+mod lane_flow;
+mod levy_flight;
+mod lissajous;
+mod magnetism;
+mod nbody;
+mod orbit;
+mod path_follow;
+mod patrol;
+mod pen;
+mod rendezvous;
+mod scatter_gather;
+mod school;
+mod seek;
+mod sph;
+mod territory;
+mod vector_field_follow;
+mod walker;
+
+pub use self::ant::Ant;
+pub use self::chemotaxis::Chemotaxis;
+pub use self::color_cluster::ColorCluster;
+pub use self::containment::Containment;
+pub use self::convection::Convection;
+pub use self::corral::Corral;
+pub use self::crystallize::Crystallize;
+pub use self::diffuse::Diffuse;
+pub use self::disperse::Disperse;
+pub use self::evacuate::{nearest_exit, Evacuate};
+pub use self::field_lines::{dipole_field, Dipole, FieldLines};
+pub use self::flock::{path_point_ahead, Flock};
+pub use self::flow_field_follow::FlowFieldFollow;
+pub use self::fn_behavior::FnBehavior;
+pub use self::follow::Follow;
+pub use self::formation::{slot_offset, Formation, FormationShape};
+pub use self::grow::Grow;
+pub use self::lane_flow::LaneFlow;
+pub use self::levy_flight::{levy_step, LevyFlight};
+pub use self::lissajous::Lissajous;
+pub use self::magnetism::Magnetism;
+pub use self::nbody::{gravity_force, NBodyGravity};
+pub use self::orbit::{nearest_attractor, orbit_velocity, Orbit};
+pub use self::path_follow::{plan_path, remaining_waypoints, PathFollow};
+pub use self::patrol::Patrol;
+pub use self::pen::Pen;
+pub use self::rendezvous::Rendezvous;
+pub use self::scatter_gather::ScatterGather;
+pub use self::school::School;
+pub use self::seek::{seek_velocity, Seek, Target};
+pub use self::sph::{poly6, spiky_gradient, viscosity_laplacian, Sph};
+pub use self::territory::Territory;
+pub use self::vector_field_follow::VectorFieldFollow;
+pub use self::walker::{direction_to_rotation, Walker};
+
+// State of an entity at the start of the step, as seen by the others' behaviors.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Snapshot {
+    pub id: u32,
+    pub physics: Physics,
+}
+
+// What a behavior can see of the world beyond its own entity.
+#[derive(Clone, Copy)]
+pub struct BehaviorContext<'a> {
+    pub dt: f64,
+    pub width: f64,
+    pub height: f64,
+    // Corners of the world rectangle in its coordinate system, minimum first.
+    pub bounds: ([f64; 2], [f64; 2]),
+    // Tells which way is up.
+    pub coordinates: CoordinateSystem,
+    pub sim_time: f64,
+    // Steps completed before this one, for periodic actions.
+    pub tick: u64,
+    pub snapshot: &'a [Snapshot],
+    // Id to position in `snapshot`.
+    pub index_of: &'a HashMap<u32, usize>,
+    // Mean position of every entity.
+    pub centroid: [f64; 2],
+    pub goal: Option<[f64; 2]>,
+    // Tags of each entity, indices match `snapshot`.
+    pub tags: &'a [&'a [String]],
+    // Drawn color of each entity, indices match `snapshot`.
+    pub colors: &'a [Option<[f32; 4]>],
+    // Built over the snapshot positions, indices match `snapshot`.
+    pub neighbor_index: &'a dyn NeighborQuery,
+    pub lanes: Option<&'a LaneField>,
+    pub field: Option<&'a ScalarField>,
+    pub flow_field: Option<&'a FlowField>,
+    pub vector_field: Option<&'a VectorField>,
+    pub nav_grid: Option<&'a NavGrid>,
+    pub pheromones: Option<&'a PheromoneGrid>,
+    pub attractors: &'a [Attractor],
+    pub migration_path: &'a [[f64; 2]],
+    // Makes random behaviors reproducible when set, see `step_rng`.
+    pub seed: Option<u64>,
+    // Built on first use, see `mass_tree`.
+    pub mass_tree: &'a OnceLock<QuadTree>,
+}
+
+// Points per leaf of the mass tree.
+const MASS_TREE_CAPACITY: usize = 4;
+
+// Random numbers for one stream of one step. With a seed the stream only depends on the
+// seed, tick and stream, so runs repeat whatever order entities are computed in.
+pub fn step_rng(seed: Option<u64>, tick: u64, stream: u64) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(
+            seed ^ tick.wrapping_mul(0x9e37_79b9_7f4a_7c15)
+                ^ stream.wrapping_mul(0xd1b5_4a32_d192_ed03),
+        ),
+        None => StdRng::from_entropy(),
+    }
+}
+
+impl<'a> BehaviorContext<'a> {
+    pub fn find(&self, id: u32) -> Option<&'a Snapshot> {
+        self.index_of.get(&id).map(|&index| &self.snapshot[index])
+    }
+
+    pub fn tags_of(&self, id: u32) -> &'a [String] {
+        match self.index_of.get(&id) {
+            Some(&index) => self.tags[index],
+            None => &[],
+        }
+    }
+
+    // Snapshot positions weighted by mass, indices matching `snapshot`. Built once per
+    // step by whichever behavior first asks for it.
+    pub fn mass_tree(&self) -> &'a QuadTree {
+        self.mass_tree.get_or_init(|| {
+            let positions: Vec<[f64; 2]> = self
+                .snapshot
+                .iter()
+                .map(|other| [other.physics.x, other.physics.y])
+                .collect();
+            let masses: Vec<f64> = self
+                .snapshot
+                .iter()
+                .map(|other| mass(&other.physics))
+                .collect();
+
+            QuadTree::build_weighted(&positions, &masses, MASS_TREE_CAPACITY)
+        })
+    }
+
+    // Random numbers for the entity `id` this step.
+    pub fn rng(&self, id: u32) -> StdRng {
+        step_rng(self.seed, self.tick, id as u64)
+    }
+
+    pub fn color_of(&self, id: u32) -> Option<[f32; 4]> {
+        self.index_of.get(&id).and_then(|&index| self.colors[index])
+    }
+
+    // Everyone within `radius` of the entity `id`, leaving out the entity itself. Each
+    // behavior passes its own perception radius.
+    pub fn neighbors(&self, id: u32, radius: f64) -> Vec<Snapshot> {
+        let center = match self.find(id) {
+            Some(snapshot) => snapshot.physics,
+            None => return Vec::new(),
+        };
+
+        self.neighbor_index
+            .within_radius([center.x, center.y], radius)
+            .into_iter()
+            .map(|index| self.snapshot[index])
+            .filter(|other| other.id != id)
+            .collect()
+    }
+}
+
+pub fn centroid(snapshot: &[Snapshot]) -> [f64; 2] {
+    if snapshot.is_empty() {
+        return [0.0, 0.0];
+    }
+
+    let count = snapshot.len() as f64;
+    let (x, y) = snapshot.iter().fold((0.0, 0.0), |(x, y), other| {
+        (x + other.physics.x, y + other.physics.y)
+    });
+
+    [x / count, y / count]
+}
+
+// What a behavior wants done to its entity, applied once every behavior has run.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Steering {
+    // Replaces the velocity when set, before `acceleration` is added.
+    pub velocity: Option<[f64; 2]>,
+    pub acceleration: [f64; 2],
+    // Moved by directly, on top of the velocity.
+    pub displacement: [f64; 2],
+    pub rotation: Option<f64>,
+    // Radians per second squared, spinning the shape up or down.
+    pub torque: f64,
+    pub size: Option<f64>,
+    // Pheromone laid per second where the entity ends up, see `World::pheromones`.
+    pub deposit: f64,
+    // Leaves a dot of this size where the entity ends up, drawn like the entity.
+    pub mark: Option<f64>,
+}
+
+impl Steering {
+    pub fn velocity(vx: f64, vy: f64) -> Steering {
+        Steering {
+            velocity: Some([vx, vy]),
+            ..Steering::default()
+        }
+    }
+
+    pub fn acceleration(ax: f64, ay: f64) -> Steering {
+        Steering {
+            acceleration: [ax, ay],
+            ..Steering::default()
+        }
+    }
+
+    pub fn apply(&self, physics: &mut Physics, dt: f64) {
+        if let Some([vx, vy]) = self.velocity {
+            physics.vx = vx;
+            physics.vy = vy;
+        }
+
+        physics.vx += self.acceleration[0] * dt;
+        physics.vy += self.acceleration[1] * dt;
+        physics.x += self.displacement[0];
+        physics.y += self.displacement[1];
+
+        if let Some(rotation) = self.rotation {
+            physics.rotation = rotation;
+        }
+        physics.angular_velocity += self.torque * dt;
+        if let Some(size) = self.size {
+            physics.size = size;
+        }
+    }
+}
+
+// Rotation turned from `current` towards `target` the short way round, by at most
+// `max_turn` radians.
+pub fn turn_towards(current: f64, target: f64, max_turn: f64) -> f64 {
+    let difference = (target - current + PI).rem_euclid(TAU) - PI;
+
+    if difference.abs() <= max_turn {
+        target
+    } else {
+        current + max_turn.copysign(difference)
+    }
+}
+
+// Behaviors only read the snapshot, so every entity's steering can be computed in
+// parallel before any of them is applied. That's also why `compute` only gets `&self`,
+// behaviors keeping state from one tick to the next hold it in a `Mutex`.
+pub trait AI: Send + Sync {
+    fn compute(
+        &self,
+        entity: &Snapshot,
+        neighbors: &[Snapshot],
+        context: &BehaviorContext,
+    ) -> Steering;
+
+    // Radius the world gathers `neighbors` within, none means no neighbors are needed.
+    fn perception_radius(&self) -> Option<f64> {
+        None
+    }
+
+    // Only this many of the nearest neighbors are passed on, when set.
+    fn max_neighbors(&self) -> Option<usize> {
+        None
+    }
+
+    // Runs on the entity itself once its steering is applied, one entity after the
+    // other, so it can change what a steering can't. Most behaviors only steer.
+    fn update(&mut self, _entity: &mut Entity, _dt: f64) {}
+
+    // Called once the behavior is given to an entity, with where the entity is then.
+    fn on_spawn(&self, _physics: &Physics) {}
+
+    // Parameters that build this behavior again through `BehaviorConfig::build`, so it
+    // can be saved. None for behaviors that aren't data, like closures.
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        None
+    }
+}
+
+// The `count` neighbors closest to `center`, nearest first.
+pub fn nearest(center: [f64; 2], mut neighbors: Vec<Snapshot>, count: usize) -> Vec<Snapshot> {
+    let distance = |other: &Snapshot| {
+        let dx = other.physics.x - center[0];
+        let dy = other.physics.y - center[1];
+        dx * dx + dy * dy
+    };
+
+    neighbors.sort_by(|a, b| distance(a).total_cmp(&distance(b)));
+    neighbors.truncate(count);
+    neighbors
+}
+
+// Steering of the `index`th snapshot entity, none without a behavior.
+fn steering_of(
+    index: usize,
+    behavior: Option<&dyn AI>,
+    context: &BehaviorContext,
+) -> Option<Steering> {
+    behavior.map(|ai| {
+        let snapshot = &context.snapshot[index];
+        let mut neighbors = ai
+            .perception_radius()
+            .map(|radius| context.neighbors(snapshot.id, radius))
+            .unwrap_or_default();
+
+        if let Some(count) = ai.max_neighbors() {
+            let center = [snapshot.physics.x, snapshot.physics.y];
+            neighbors = nearest(center, neighbors, count);
+        }
+
+        ai.compute(snapshot, &neighbors, context)
+    })
+}
+
+// First phase of a step, `behaviors` lines up with `context.snapshot`.
+pub fn compute_steerings(
+    behaviors: &[Option<&dyn AI>],
+    context: &BehaviorContext,
+) -> Vec<Option<Steering>> {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+
+        behaviors
+            .par_iter()
+            .enumerate()
+            .map(|(index, behavior)| steering_of(index, *behavior, context))
+            .collect()
+    }
+
+    #[cfg(not(feature = "parallel"))]
+    {
+        compute_steerings_sequential(behaviors, context)
+    }
+}
+
+// `compute_steerings` one entity after the other, whatever the features.
+pub fn compute_steerings_sequential(
+    behaviors: &[Option<&dyn AI>],
+    context: &BehaviorContext,
+) -> Vec<Option<Steering>> {
+    behaviors
+        .iter()
+        .enumerate()
+        .map(|(index, behavior)| steering_of(index, *behavior, context))
+        .collect()
+}
+
+// Behavior test fixtures: contexts over a hand-made snapshot without stepping a world.
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+    use crate::spatial::QuadTree;
+
+    pub fn snapshot(id: u32, x: f64, y: f64) -> Snapshot {
+        Snapshot {
+            id,
+            physics: Physics::new(x, y, 2.0).unwrap(),
+        }
+    }
+
+    // Calls `f` with the context of a 100 by 100 world holding only `snapshot`, at the
+    // first tick. Other fields can be filled in with struct update syntax.
+    pub fn with_context<R>(snapshot: &[Snapshot], f: impl FnOnce(BehaviorContext) -> R) -> R {
+        let index_of: HashMap<u32, usize> = snapshot
+            .iter()
+            .enumerate()
+            .map(|(index, other)| (other.id, index))
+            .collect();
+        let positions: Vec<[f64; 2]> = snapshot
+            .iter()
+            .map(|other| [other.physics.x, other.physics.y])
+            .collect();
+        let neighbor_index = QuadTree::build(&positions, 4);
+        let tags: Vec<&[String]> = vec![&[]; snapshot.len()];
+        let colors = vec![None; snapshot.len()];
+        let mass_tree = OnceLock::new();
+
+        f(BehaviorContext {
+            dt: 0.1,
+            width: 100.0,
+            height: 100.0,
+            bounds: ([0.0, 0.0], [100.0, 100.0]),
+            coordinates: CoordinateSystem::TopLeft,
+            sim_time: 0.0,
+            tick: 0,
+            snapshot,
+            index_of: &index_of,
+            centroid: centroid(snapshot),
+            goal: None,
+            tags: &tags,
+            colors: &colors,
+            neighbor_index: &neighbor_index,
+            lanes: None,
+            field: None,
+            flow_field: None,
+            vector_field: None,
+            nav_grid: None,
+            pheromones: None,
+            attractors: &[],
+            migration_path: &[],
+            seed: None,
+            mass_tree: &mass_tree,
+        })
+    }
+
+    // Steering of the `index`th snapshot entity, with neighbors gathered as in a step.
+    pub fn steer(ai: &dyn AI, index: usize, context: &BehaviorContext) -> Steering {
+        let mut behaviors: Vec<Option<&dyn AI>> = vec![None; context.snapshot.len()];
+        behaviors[index] = Some(ai);
+
+        compute_steerings(&behaviors, context)[index].unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::testing::{snapshot, with_context};
+    use super::*;
+    use crate::entity::{Entity, Renderer};
+    use crate::shapes::Circle;
+    use crate::world::World;
+
+    fn flock() -> Flock {
+        Flock {
+            perception_radius: 30.0,
+            max_neighbors: None,
+            separation: 5.0,
+            alignment: 0.5,
+            cohesion: 1.0,
+            max_speed: 1000.0,
+            predator: None,
+            flee: 0.0,
+            leader: None,
+            leader_pull: 0.0,
+            migration: 0.0,
+        }
+    }
+
+    #[test]
+    fn steerings_only_depend_on_the_snapshot() {
+        let flock = flock();
+        let dt = 0.1;
+        let flocker = |id: u32| {
+            let mut physics = snapshot(
+                id,
+                30.0 + 7.0 * (id % 4) as f64,
+                30.0 + 9.0 * (id / 4) as f64,
+            )
+            .physics;
+            physics.vx = (id % 3) as f64 - 1.0;
+            physics.vy = (id % 2) as f64;
+
+            Entity::new(
+                id,
+                physics,
+                Renderer::Owned(Box::new(Circle::new([1.0; 4]))),
+            )
+            .with_behavior(Box::new(self::flock()))
+        };
+        let mut world = World::new((0..12).map(flocker).collect(), 100.0, 100.0).unwrap();
+        let before: Vec<Physics> = world.entities.iter().map(|entity| entity.physics).collect();
+
+        // Each entity steered one at a time by the others as they were before the step,
+        // with neighbors found by brute force.
+        let expected: Vec<Physics> = before
+            .iter()
+            .enumerate()
+            .map(|(index, physics)| {
+                let neighbors: Vec<Physics> = before
+                    .iter()
+                    .enumerate()
+                    .filter(|&(other, neighbor)| {
+                        other != index
+                            && (neighbor.x - physics.x).hypot(neighbor.y - physics.y)
+                                <= flock.perception_radius
+                    })
+                    .map(|(_, neighbor)| *neighbor)
+                    .collect();
+                let [ax, ay] = flock.steering(physics, &neighbors);
+                let mut physics = *physics;
+
+                physics.vx += ax * dt;
+                physics.vy += ay * dt;
+                physics.x += physics.vx * dt;
+                physics.y += physics.vy * dt;
+                physics
+            })
+            .collect();
+
+        world.step(dt);
+
+        for (entity, expected) in world.entities.iter().zip(expected) {
+            assert!((entity.physics.x - expected.x).abs() < 1e-9);
+            assert!((entity.physics.y - expected.y).abs() < 1e-9);
+            assert!((entity.physics.vx - expected.vx).abs() < 1e-9);
+            assert!((entity.physics.vy - expected.vy).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn compute_steerings_matches_the_sequential_path() {
+        let flock = flock();
+        let snapshot: Vec<Snapshot> = (0..12)
+            .map(|i| snapshot(i, 10.0 + 7.0 * (i % 4) as f64, 10.0 + 9.0 * (i / 4) as f64))
+            .collect();
+
+        with_context(&snapshot, |context| {
+            let behaviors: Vec<Option<&dyn AI>> = vec![Some(&flock); snapshot.len()];
+
+            assert_eq!(
+                compute_steerings(&behaviors, &context),
+                compute_steerings_sequential(&behaviors, &context)
+            );
+        });
+    }
+}
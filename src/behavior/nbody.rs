@@ -0,0 +1,87 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::collision::mass;
+use crate::config::BehaviorConfig;
+use crate::entity::Physics;
+use crate::forces::softened_pull;
+
+// Pull of every other entity by mass over squared distance. Exact by default, which is
+// quadratic in the entity count, or Barnes-Hut approximated when `theta` is set.
+pub struct NBodyGravity {
+    // Gravitational constant.
+    pub strength: f64,
+    // Added to distances, so that close bodies don't get infinite forces.
+    pub softening: f64,
+    // Opening angle, groups of entities seen under a smaller one pull as a single body
+    // at their center of mass. 0 is exact, 0.5 a common tradeoff.
+    pub theta: Option<f64>,
+}
+
+// Force `b` exerts on `a`, along the line from `a` to `b`, see `softened_pull`.
+pub fn gravity_force(a: &Physics, b: &Physics, strength: f64, softening: f64) -> [f64; 2] {
+    let [ax, ay] = softened_pull([a.x, a.y], [b.x, b.y], mass(b), strength, softening);
+
+    [ax * mass(a), ay * mass(a)]
+}
+
+impl AI for NBodyGravity {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let body = &entity.physics;
+
+        if let Some(theta) = self.theta {
+            let [ax, ay] = context.mass_tree().gravity(
+                [body.x, body.y],
+                context.index_of.get(&entity.id).copied(),
+                theta,
+                self.strength,
+                self.softening,
+            );
+            return Steering::acceleration(ax, ay);
+        }
+
+        let mut force = [0.0, 0.0];
+
+        for other in context
+            .snapshot
+            .iter()
+            .filter(|other| other.id != entity.id)
+        {
+            let [fx, fy] = gravity_force(body, &other.physics, self.strength, self.softening);
+            force[0] += fx;
+            force[1] += fy;
+        }
+
+        let mass = mass(body);
+        Steering::acceleration(force[0] / mass, force[1] / mass)
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::NbodyGravity {
+            strength: self.strength,
+            softening: self.softening,
+            theta: self.theta,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn two_bodies_pull_equally_and_oppositely_along_their_line() {
+        let a = Physics::new(10.0, 20.0, 2.0).unwrap();
+        let b = Physics::new(40.0, 60.0, 6.0).unwrap();
+
+        let on_a = gravity_force(&a, &b, 100.0, 1.0);
+        let on_b = gravity_force(&b, &a, 100.0, 1.0);
+
+        assert!((on_a[0] + on_b[0]).abs() < 1e-12);
+        assert!((on_a[1] + on_b[1]).abs() < 1e-12);
+        // Towards b, along (30, 40).
+        assert!(on_a[0] > 0.0);
+        assert!((on_a[0] * 40.0 - on_a[1] * 30.0).abs() < 1e-12);
+        let magnitude = (on_a[0] * on_a[0] + on_a[1] * on_a[1]).sqrt();
+        let expected = 100.0 * mass(&a) * mass(&b) * 50.0 / (50.0f64 * 50.0 + 1.0).powf(1.5);
+        assert!((magnitude - expected).abs() < 1e-12 * expected);
+    }
+}
@@ -0,0 +1,100 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+use crate::forces::Attractor;
+
+// Settles into a circular orbit around whichever of the world's attractors is nearest,
+// switching to another once it drifts closer to it. Repelling attractors are ignored.
+pub struct Orbit {
+    // Fraction of the difference to the orbital velocity closed per second.
+    pub strength: f64,
+}
+
+// The pulling attractor closest to `point`, none without any.
+pub fn nearest_attractor(point: [f64; 2], attractors: &[Attractor]) -> Option<Attractor> {
+    let distance = |attractor: &Attractor| {
+        let dx = attractor.position[0] - point[0];
+        let dy = attractor.position[1] - point[1];
+        dx * dx + dy * dy
+    };
+
+    attractors
+        .iter()
+        .filter(|attractor| attractor.strength > 0.0)
+        .copied()
+        .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+}
+
+// Velocity of a circular orbit around `attractor` through `point`, turning the same way
+// as `velocity` does, counterclockwise in x-y terms from rest.
+pub fn orbit_velocity(attractor: &Attractor, point: [f64; 2], velocity: [f64; 2]) -> [f64; 2] {
+    let dx = point[0] - attractor.position[0];
+    let dy = point[1] - attractor.position[1];
+    let distance = (dx * dx + dy * dy).sqrt();
+
+    if distance < f64::EPSILON {
+        return velocity;
+    }
+
+    let [ax, ay] = attractor.acceleration(point);
+    let speed = ((ax * ax + ay * ay).sqrt() * distance).sqrt();
+    let (tx, ty) = (-dy / distance, dx / distance);
+    let sense = if velocity[0] * tx + velocity[1] * ty < 0.0 {
+        -1.0
+    } else {
+        1.0
+    };
+
+    [sense * tx * speed, sense * ty * speed]
+}
+
+impl AI for Orbit {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let entity = &entity.physics;
+        let point = [entity.x, entity.y];
+        let attractor = match nearest_attractor(point, context.attractors) {
+            Some(attractor) => attractor,
+            None => return Steering::default(),
+        };
+        let [vx, vy] = orbit_velocity(&attractor, point, [entity.vx, entity.vy]);
+        let blend = (self.strength * context.dt).clamp(0.0, 1.0);
+
+        Steering::velocity(
+            entity.vx + (vx - entity.vx) * blend,
+            entity.vy + (vy - entity.vy) * blend,
+        )
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Orbit {
+            strength: self.strength,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allegiance_switches_to_whichever_attractor_is_nearer() {
+        let left = Attractor {
+            position: [20.0, 50.0],
+            strength: 100.0,
+        };
+        let right = Attractor {
+            position: [80.0, 50.0],
+            strength: 50.0,
+        };
+        let repulsor = Attractor {
+            position: [45.0, 50.0],
+            strength: -100.0,
+        };
+        let attractors = [left, right, repulsor];
+
+        assert_eq!(nearest_attractor([30.0, 50.0], &attractors), Some(left));
+        // Repulsors are never orbited, however close.
+        assert_eq!(nearest_attractor([45.0, 50.0], &attractors), Some(left));
+        assert_eq!(nearest_attractor([51.0, 50.0], &attractors), Some(right));
+        assert_eq!(nearest_attractor([50.0, 50.0], &[repulsor]), None);
+    }
+}
@@ -0,0 +1,144 @@
+use std::sync::Mutex;
+
+use super::seek::{seek_velocity, Target};
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+use crate::pathfinding::{astar, NavGrid};
+
+// Waypoints left to the target and the tick they were planned on.
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Plan {
+    waypoints: Vec<[f64; 2]>,
+    planned_at: Option<u64>,
+}
+
+// Heads for its target around the blocked cells of the world's navigation grid, along an
+// A* path planned again every `replan_every` ticks so moving targets are kept up with.
+// Without a grid, or with no path, it stops.
+pub struct PathFollow {
+    pub target: Target,
+    // Units per second.
+    pub speed: f64,
+    // Distance at which a waypoint counts as reached.
+    pub threshold: f64,
+    pub replan_every: u64,
+    plan: Mutex<Plan>,
+}
+
+// Centers of the cells on the way from `from` to `to`, ending at `to` itself. The cell
+// the entity is in is left out. None when there's no path.
+pub fn plan_path(grid: &NavGrid, from: [f64; 2], to: [f64; 2]) -> Option<Vec<[f64; 2]>> {
+    let path = astar(grid, grid.cell_of(from)?, grid.cell_of(to)?)?;
+    let mut waypoints: Vec<[f64; 2]> = path
+        .into_iter()
+        .skip(1)
+        .map(|cell| grid.center_of(cell))
+        .collect();
+
+    waypoints.pop();
+    waypoints.push(to);
+    Some(waypoints)
+}
+
+// The waypoints still ahead from `position`, dropping those within `threshold`. The last
+// one is kept to arrive at.
+pub fn remaining_waypoints(
+    waypoints: &[[f64; 2]],
+    position: [f64; 2],
+    threshold: f64,
+) -> &[[f64; 2]] {
+    let reached = waypoints
+        .iter()
+        .take_while(|point| (point[0] - position[0]).hypot(point[1] - position[1]) <= threshold)
+        .count();
+
+    &waypoints[reached.min(waypoints.len().saturating_sub(1))..]
+}
+
+impl PathFollow {
+    pub fn new(target: Target, speed: f64, threshold: f64, replan_every: u64) -> PathFollow {
+        PathFollow {
+            target,
+            speed,
+            threshold,
+            replan_every,
+            plan: Mutex::new(Plan::default()),
+        }
+    }
+}
+
+impl AI for PathFollow {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let position = [entity.physics.x, entity.physics.y];
+        let (grid, target) = match (context.nav_grid, self.target.resolve(context)) {
+            (Some(grid), Some(target)) => (grid, target),
+            _ => return Steering::velocity(0.0, 0.0),
+        };
+        let mut plan = self.plan.lock().unwrap();
+
+        let due = plan
+            .planned_at
+            .is_none_or(|tick| context.tick >= tick + self.replan_every);
+        if due {
+            *plan = Plan {
+                waypoints: plan_path(grid, position, target).unwrap_or_default(),
+                planned_at: Some(context.tick),
+            };
+        }
+
+        let ahead = remaining_waypoints(&plan.waypoints, position, self.threshold).to_vec();
+        plan.waypoints = ahead;
+
+        // Full speed through the waypoints, only slowing down for the last.
+        let [vx, vy] = match plan.waypoints[..] {
+            [] => [0.0, 0.0],
+            [last] => seek_velocity(position, last, self.speed),
+            [next, ..] => {
+                let (dx, dy) = (next[0] - position[0], next[1] - position[1]);
+                let distance = dx.hypot(dy).max(f64::EPSILON);
+                [dx / distance * self.speed, dy / distance * self.speed]
+            }
+        };
+
+        Steering::velocity(vx, vy)
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        let (target, leader_id) = self.target.config();
+
+        Some(BehaviorConfig::PathFollow {
+            target,
+            leader_id,
+            speed: self.speed,
+            threshold: self.threshold,
+            replan_every: self.replan_every,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{snapshot, steer, with_context};
+    use super::*;
+
+    #[test]
+    fn heads_for_the_first_waypoint_around_a_wall() {
+        let grid = NavGrid::new([0.0, 0.0], 10.0, 3, 2, &[[1, 0]]).unwrap();
+        let entities = [snapshot(1, 5.0, 5.0)];
+        let path_follow = PathFollow::new(Target::Point([25.0, 5.0]), 4.0, 1.0, 10);
+
+        assert_eq!(
+            plan_path(&grid, [5.0, 5.0], [25.0, 5.0]),
+            Some(vec![[5.0, 15.0], [15.0, 15.0], [25.0, 15.0], [25.0, 5.0]])
+        );
+        with_context(&entities, |context| {
+            let context = BehaviorContext {
+                nav_grid: Some(&grid),
+                ..context
+            };
+            let steering = steer(&path_follow, 0, &context);
+
+            assert_eq!(steering.velocity, Some([0.0, 4.0]));
+        });
+    }
+}
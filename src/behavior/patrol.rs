@@ -0,0 +1,94 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use super::seek::seek_velocity;
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+
+// Visits the waypoints in order and starts over after the last one.
+pub struct Patrol {
+    pub waypoints: Vec<[f64; 2]>,
+    // Units per second.
+    pub speed: f64,
+    // Distance at which a waypoint counts as reached.
+    pub threshold: f64,
+    // Index of the waypoint being headed for, atomic since behaviors only get `&self`.
+    current: AtomicUsize,
+}
+
+impl Patrol {
+    pub fn new(waypoints: Vec<[f64; 2]>, speed: f64, threshold: f64) -> Patrol {
+        Patrol {
+            waypoints,
+            speed,
+            threshold,
+            current: AtomicUsize::new(0),
+        }
+    }
+
+    // Heads for the `current` waypoint first, as a saved patrol is restored.
+    pub fn with_current(self, current: usize) -> Patrol {
+        self.current.store(current, Ordering::Relaxed);
+        self
+    }
+
+    pub fn current(&self) -> usize {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    // Waypoint to head for from `position` when heading for `current`, which moves on
+    // to the next one once within the threshold.
+    pub fn next_index(&self, current: usize, position: [f64; 2]) -> usize {
+        if self.waypoints.is_empty() {
+            return 0;
+        }
+
+        let current = current % self.waypoints.len();
+        let target = self.waypoints[current];
+        let dx = target[0] - position[0];
+        let dy = target[1] - position[1];
+
+        if (dx * dx + dy * dy).sqrt() <= self.threshold {
+            (current + 1) % self.waypoints.len()
+        } else {
+            current
+        }
+    }
+}
+
+impl AI for Patrol {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], _: &BehaviorContext) -> Steering {
+        if self.waypoints.is_empty() {
+            return Steering::velocity(0.0, 0.0);
+        }
+
+        let position = [entity.physics.x, entity.physics.y];
+        let index = self.next_index(self.current(), position);
+        let [vx, vy] = seek_velocity(position, self.waypoints[index], self.speed);
+
+        self.current.store(index, Ordering::Relaxed);
+        Steering::velocity(vx, vy)
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Patrol {
+            waypoints: self.waypoints.clone(),
+            speed: self.speed,
+            threshold: self.threshold,
+            current: (!self.waypoints.is_empty()).then(|| self.current()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn moves_on_once_within_the_threshold() {
+        let patrol = Patrol::new(vec![[0.0, 0.0], [10.0, 0.0]], 1.0, 2.0);
+
+        assert_eq!(patrol.next_index(0, [5.0, 0.0]), 0);
+        assert_eq!(patrol.next_index(0, [2.0, 0.0]), 1);
+        assert_eq!(patrol.next_index(1, [9.0, 0.0]), 0);
+    }
+}
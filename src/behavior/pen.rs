@@ -0,0 +1,74 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+
+// Draws the entity's path with dots left behind every `every` ticks, which stay as plain
+// entities with no behavior rather than fading like a trail. The pen itself keeps going
+// as it was.
+pub struct Pen {
+    pub every: u64,
+    // Size of the dots.
+    pub size: f64,
+}
+
+impl Pen {
+    pub fn marks(&self, tick: u64) -> bool {
+        self.every > 0 && tick.is_multiple_of(self.every)
+    }
+}
+
+impl AI for Pen {
+    fn compute(&self, _: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        Steering {
+            mark: self.marks(context.tick).then_some(self.size),
+            ..Steering::default()
+        }
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Pen {
+            every: self.every,
+            size: self.size,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{Entity, Physics, Renderer};
+    use crate::shapes::Circle;
+    use crate::world::World;
+
+    #[test]
+    fn pen_leaves_a_dot_every_few_ticks() {
+        let mut pen = Entity::new(
+            0,
+            Physics::new(10.0, 50.0, 2.0).unwrap(),
+            Renderer::Owned(Box::new(Circle::new([1.0; 4]))),
+        )
+        .with_behavior(Box::new(Pen {
+            every: 3,
+            size: 1.0,
+        }));
+        pen.physics.vx = 10.0;
+        let mut world = World::new(vec![pen], 100.0, 100.0).unwrap();
+
+        for _ in 0..10 {
+            world.step(0.1);
+        }
+
+        // Ticks 0, 3, 6 and 9, each where the pen got to that step.
+        let dots: Vec<[f64; 2]> = world.entities[1..]
+            .iter()
+            .map(|dot| [dot.physics.x, dot.physics.y])
+            .collect();
+        assert_eq!(dots.len(), 4);
+        for (dot, x) in dots.iter().zip(&[11.0, 14.0, 17.0, 20.0]) {
+            assert!((dot[0] - x).abs() < 1e-9);
+            assert_eq!(dot[1], 50.0);
+        }
+        assert!(world.entities[1..]
+            .iter()
+            .all(|dot| dot.behavior.is_none() && dot.physics.size == 1.0));
+    }
+}
@@ -0,0 +1,125 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+
+// Consensus on a meeting point: every entity heads for the average position of its
+// neighbors, so as long as they stay in sight of each other the swarm contracts to one
+// spot.
+pub struct Rendezvous {
+    pub perception_radius: f64,
+    pub max_neighbors: Option<usize>,
+    // Fraction of the distance to the neighbors' average covered per second.
+    pub gain: f64,
+    // Units per second.
+    pub max_speed: f64,
+}
+
+impl Rendezvous {
+    // Velocity towards the average of `neighbors`, none without neighbors.
+    pub fn velocity(&self, position: [f64; 2], neighbors: &[[f64; 2]]) -> [f64; 2] {
+        if neighbors.is_empty() {
+            return [0.0, 0.0];
+        }
+
+        let count = neighbors.len() as f64;
+        let (x, y) = neighbors
+            .iter()
+            .fold((0.0, 0.0), |(x, y), other| (x + other[0], y + other[1]));
+        let vx = self.gain * (x / count - position[0]);
+        let vy = self.gain * (y / count - position[1]);
+
+        let speed = vx.hypot(vy);
+        if speed > self.max_speed {
+            return [vx * self.max_speed / speed, vy * self.max_speed / speed];
+        }
+
+        [vx, vy]
+    }
+}
+
+impl AI for Rendezvous {
+    fn compute(&self, entity: &Snapshot, neighbors: &[Snapshot], _: &BehaviorContext) -> Steering {
+        let neighbors: Vec<[f64; 2]> = neighbors
+            .iter()
+            .map(|other| [other.physics.x, other.physics.y])
+            .collect();
+        let [vx, vy] = self.velocity([entity.physics.x, entity.physics.y], &neighbors);
+
+        Steering::velocity(vx, vy)
+    }
+
+    fn perception_radius(&self) -> Option<f64> {
+        Some(self.perception_radius)
+    }
+
+    fn max_neighbors(&self) -> Option<usize> {
+        self.max_neighbors
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Rendezvous {
+            perception_radius: self.perception_radius,
+            max_neighbors: self.max_neighbors,
+            gain: self.gain,
+            max_speed: self.max_speed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{Entity, Physics, Renderer};
+    use crate::shapes::Circle;
+    use crate::world::World;
+
+    // Largest distance of an entity from the swarm's center.
+    fn spread(world: &World) -> f64 {
+        let positions = world.positions();
+        let count = positions.len() as f64;
+        let (cx, cy) = positions
+            .iter()
+            .fold((0.0, 0.0), |(x, y), p| (x + p[0] / count, y + p[1] / count));
+
+        positions
+            .iter()
+            .map(|p| (p[0] - cx).hypot(p[1] - cy))
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn the_swarm_contracts_every_step() {
+        let points = [
+            [20.0, 30.0],
+            [70.0, 20.0],
+            [50.0, 80.0],
+            [85.0, 60.0],
+            [40.0, 50.0],
+        ];
+        let entities = points
+            .iter()
+            .enumerate()
+            .map(|(id, &[x, y])| {
+                Entity::new(
+                    id as u32,
+                    Physics::new(x, y, 2.0).unwrap(),
+                    Renderer::Owned(Box::new(Circle::new([1.0; 4]))),
+                )
+                .with_behavior(Box::new(Rendezvous {
+                    perception_radius: 200.0,
+                    max_neighbors: None,
+                    gain: 1.0,
+                    max_speed: 30.0,
+                }))
+            })
+            .collect();
+        let mut world = World::new(entities, 100.0, 100.0).unwrap();
+
+        let mut last = spread(&world);
+        for _ in 0..20 {
+            world.step(0.1);
+            let now = spread(&world);
+            assert!(now < last, "spread went from {} to {}", last, now);
+            last = now;
+        }
+    }
+}
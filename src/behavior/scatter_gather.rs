@@ -0,0 +1,81 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+
+// Flees the crowd's centroid for the first half of each period, then heads back to it.
+pub struct ScatterGather {
+    // Seconds.
+    pub period: f64,
+    // Units per second.
+    pub speed: f64,
+}
+
+impl ScatterGather {
+    pub fn scattering(&self, sim_time: f64) -> bool {
+        self.period <= 0.0 || sim_time.rem_euclid(self.period) < self.period / 2.0
+    }
+}
+
+impl AI for ScatterGather {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let entity = &entity.physics;
+        let dx = context.centroid[0] - entity.x;
+        let dy = context.centroid[1] - entity.y;
+        let distance = (dx * dx + dy * dy).sqrt();
+
+        if distance < f64::EPSILON {
+            return Steering::default();
+        }
+
+        let sign = if self.scattering(context.sim_time) {
+            -1.0
+        } else {
+            1.0
+        };
+        // Slow down on arrival rather than orbiting the centroid.
+        let speed = if sign > 0.0 {
+            self.speed.min(distance)
+        } else {
+            self.speed
+        };
+
+        Steering::velocity(sign * dx / distance * speed, sign * dy / distance * speed)
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::ScatterGather {
+            period: self.period,
+            speed: self.speed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{snapshot, steer, with_context};
+    use super::*;
+
+    #[test]
+    fn steering_flips_at_each_half_period() {
+        let ai = ScatterGather {
+            period: 4.0,
+            speed: 5.0,
+        };
+        let snapshot = [snapshot(0, 40.0, 50.0), snapshot(1, 60.0, 50.0)];
+        let vx_at = |sim_time: f64| {
+            with_context(&snapshot, |context| {
+                let context = BehaviorContext {
+                    sim_time,
+                    ..context
+                };
+                steer(&ai, 0, &context).velocity.unwrap()[0]
+            })
+        };
+
+        // Away from the centroid at x = 50 while scattering, towards it while gathering.
+        assert_eq!(vx_at(0.0), -5.0);
+        assert_eq!(vx_at(1.9), -5.0);
+        assert_eq!(vx_at(2.0), 5.0);
+        assert_eq!(vx_at(3.9), 5.0);
+        assert_eq!(vx_at(4.0), -5.0);
+    }
+}
@@ -0,0 +1,114 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+use crate::entity::Physics;
+
+// A cheap stand-in for flocking: only match the average velocity of the neighbors, both
+// heading and speed, with no separation or cohesion. Good for fish-like streams.
+pub struct School {
+    pub perception_radius: f64,
+    pub max_neighbors: Option<usize>,
+    // Fraction of the velocity difference closed per second.
+    pub matching: f64,
+    // Units per second.
+    pub max_speed: f64,
+}
+
+impl School {
+    // Acceleration towards the neighbors' average velocity, none without neighbors.
+    pub fn acceleration(&self, physics: &Physics, neighbors: &[Physics]) -> [f64; 2] {
+        if neighbors.is_empty() {
+            return [0.0, 0.0];
+        }
+
+        let count = neighbors.len() as f64;
+        let (vx, vy) = neighbors
+            .iter()
+            .fold((0.0, 0.0), |(vx, vy), other| (vx + other.vx, vy + other.vy));
+
+        [
+            self.matching * (vx / count - physics.vx),
+            self.matching * (vy / count - physics.vy),
+        ]
+    }
+}
+
+impl AI for School {
+    fn compute(
+        &self,
+        entity: &Snapshot,
+        neighbors: &[Snapshot],
+        context: &BehaviorContext,
+    ) -> Steering {
+        let entity = &entity.physics;
+        let neighbors: Vec<Physics> = neighbors.iter().map(|other| other.physics).collect();
+        let [ax, ay] = self.acceleration(entity, &neighbors);
+        let mut vx = entity.vx + ax * context.dt;
+        let mut vy = entity.vy + ay * context.dt;
+
+        let speed = (vx * vx + vy * vy).sqrt();
+        if speed > self.max_speed {
+            vx *= self.max_speed / speed;
+            vy *= self.max_speed / speed;
+        }
+
+        Steering::velocity(vx, vy)
+    }
+
+    fn perception_radius(&self) -> Option<f64> {
+        Some(self.perception_radius)
+    }
+
+    fn max_neighbors(&self) -> Option<usize> {
+        self.max_neighbors
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::School {
+            perception_radius: self.perception_radius,
+            max_neighbors: self.max_neighbors,
+            matching: self.matching,
+            max_speed: self.max_speed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::testing::{snapshot, steer, with_context};
+    use super::*;
+
+    #[test]
+    fn school_speeds_up_towards_faster_neighbors() {
+        let school = School {
+            perception_radius: 20.0,
+            max_neighbors: None,
+            matching: 2.0,
+            max_speed: 100.0,
+        };
+        let mut snapshot = [
+            snapshot(0, 50.0, 50.0),
+            snapshot(1, 55.0, 50.0),
+            snapshot(2, 50.0, 55.0),
+        ];
+        snapshot[0].physics.vx = 1.0;
+        snapshot[1].physics.vx = 4.0;
+        snapshot[2].physics.vx = 6.0;
+        snapshot[2].physics.vy = 2.0;
+
+        // (5 - 1) * 2 * 0.1 and (1 - 0) * 2 * 0.1 closer to the average (5, 1).
+        let [vx, vy] = with_context(&snapshot, |context| steer(&school, 0, &context))
+            .velocity
+            .unwrap();
+        assert!((vx - 1.8).abs() < 1e-12);
+        assert!((vy - 0.2).abs() < 1e-12);
+
+        let capped = School {
+            max_speed: 1.5,
+            ..school
+        };
+        let [vx, vy] = with_context(&snapshot, |context| steer(&capped, 0, &context))
+            .velocity
+            .unwrap();
+        assert!(((vx * vx + vy * vy).sqrt() - 1.5).abs() < 1e-12);
+    }
+}
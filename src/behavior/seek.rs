@@ -0,0 +1,117 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Target {
+    Point([f64; 2]),
+    // The world goal, which can move while the simulation runs.
+    Goal,
+    // Wherever the entity with this id is.
+    Leader(u32),
+}
+
+impl Target {
+    // Where the target is this step, none without a goal or once the leader is gone.
+    pub fn resolve(&self, context: &BehaviorContext) -> Option<[f64; 2]> {
+        match *self {
+            Target::Point(point) => Some(point),
+            Target::Goal => context.goal,
+            Target::Leader(id) => context
+                .find(id)
+                .map(|leader| [leader.physics.x, leader.physics.y]),
+        }
+    }
+
+    // As the `target` point and `leader_id` of the configs taking a target, neither
+    // being the goal.
+    pub fn config(&self) -> (Option<[f64; 2]>, Option<u32>) {
+        match *self {
+            Target::Point(point) => (Some(point), None),
+            Target::Goal => (None, None),
+            Target::Leader(id) => (None, Some(id)),
+        }
+    }
+}
+
+// Heads straight for its target, slowing down over the last second of travel.
+pub struct Seek {
+    pub target: Target,
+    // Units per second.
+    pub speed: f64,
+}
+
+// Velocity heading from `from` to `to`, slowing down over the last second of travel.
+pub fn seek_velocity(from: [f64; 2], to: [f64; 2], speed: f64) -> [f64; 2] {
+    let dx = to[0] - from[0];
+    let dy = to[1] - from[1];
+    let distance = (dx * dx + dy * dy).sqrt();
+
+    if distance < f64::EPSILON {
+        return [0.0, 0.0];
+    }
+
+    let speed = speed.min(distance);
+    [dx / distance * speed, dy / distance * speed]
+}
+
+impl Seek {
+    pub fn resolve(&self, context: &BehaviorContext) -> Option<[f64; 2]> {
+        self.target.resolve(context)
+    }
+}
+
+impl AI for Seek {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let entity = &entity.physics;
+        let target = match self.resolve(context) {
+            Some(target) => target,
+            None => return Steering::velocity(0.0, 0.0),
+        };
+        let [vx, vy] = seek_velocity([entity.x, entity.y], target, self.speed);
+
+        Steering::velocity(vx, vy)
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        let (target, leader_id) = self.target.config();
+
+        Some(BehaviorConfig::Seek {
+            target,
+            leader_id,
+            speed: self.speed,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{Entity, Physics, Renderer};
+    use crate::shapes::Circle;
+    use crate::world::World;
+
+    #[test]
+    fn seeking_the_goal_follows_it_when_it_moves() {
+        let seeker = Entity::new(
+            0,
+            Physics::new(50.0, 50.0, 2.0).unwrap(),
+            Renderer::Owned(Box::new(Circle::new([1.0; 4]))),
+        )
+        .with_behavior(Box::new(Seek {
+            target: Target::Goal,
+            speed: 10.0,
+        }));
+        let mut world = World::new(vec![seeker], 100.0, 100.0).unwrap();
+
+        world.step(0.1);
+        assert_eq!(world.entities[0].physics.vx, 0.0);
+
+        world.goal = Some([90.0, 50.0]);
+        world.step(0.1);
+        assert_eq!(world.entities[0].physics.vx, 10.0);
+
+        world.goal = Some([10.0, 50.0]);
+        world.step(0.1);
+        assert_eq!(world.entities[0].physics.vx, -10.0);
+    }
+}
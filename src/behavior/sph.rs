@@ -0,0 +1,201 @@
+use std::f64::consts::PI;
+
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+
+// Smoothing kernels of Müller et al. in their 2D form, `h` being the smoothing radius.
+// Each is zero from `h` on.
+
+// Poly6, weighing neighbors for the density.
+pub fn poly6(r: f64, h: f64) -> f64 {
+    if r >= h {
+        return 0.0;
+    }
+
+    4.0 / (PI * h.powi(8)) * (h * h - r * r).powi(3)
+}
+
+// Slope of the spiky kernel along the distance, negative as it falls off. Unlike poly6's
+// it doesn't vanish up close, so pressure keeps particles from clumping.
+pub fn spiky_gradient(r: f64, h: f64) -> f64 {
+    if r >= h {
+        return 0.0;
+    }
+
+    -30.0 / (PI * h.powi(5)) * (h - r).powi(2)
+}
+
+// Laplacian of the viscosity kernel.
+pub fn viscosity_laplacian(r: f64, h: f64) -> f64 {
+    if r >= h {
+        return 0.0;
+    }
+
+    40.0 / (PI * h.powi(5)) * (h - r)
+}
+
+// Smoothed-particle hydrodynamics: every entity is a fluid particle of `particle_mass`,
+// pushed by the pressure of particles packed denser than `rest_density` and dragged
+// along by the velocities of those around it.
+pub struct Sph {
+    pub smoothing_radius: f64,
+    pub particle_mass: f64,
+    pub rest_density: f64,
+    // Pressure per unit of density over the rest density.
+    pub stiffness: f64,
+    pub viscosity: f64,
+}
+
+impl Sph {
+    // Density at `center` from the particles at `positions`, which include the one there.
+    pub fn density(&self, center: [f64; 2], positions: &[[f64; 2]]) -> f64 {
+        positions
+            .iter()
+            .map(|point| {
+                let r = (point[0] - center[0]).hypot(point[1] - center[1]);
+                self.particle_mass * poly6(r, self.smoothing_radius)
+            })
+            .sum()
+    }
+
+    pub fn pressure(&self, density: f64) -> f64 {
+        self.stiffness * (density - self.rest_density)
+    }
+
+    // Pressure force of a neighbor at `offset` from the particle, with both their
+    // pressures and the neighbor's density, per unit of the particle's density.
+    pub fn pressure_force(
+        &self,
+        offset: [f64; 2],
+        pressure: f64,
+        other_pressure: f64,
+        other_density: f64,
+    ) -> [f64; 2] {
+        let r = offset[0].hypot(offset[1]);
+        if r < f64::EPSILON || other_density <= 0.0 {
+            return [0.0, 0.0];
+        }
+
+        // Away from the neighbor when pressures are positive, the gradient being negative.
+        let push = -self.particle_mass * (pressure + other_pressure) / (2.0 * other_density)
+            * spiky_gradient(r, self.smoothing_radius)
+            / r;
+        [push * offset[0], push * offset[1]]
+    }
+
+    // Viscous force of a neighbor moving `relative_velocity` faster than the particle,
+    // per unit of the particle's density.
+    pub fn viscosity_force(
+        &self,
+        offset: [f64; 2],
+        relative_velocity: [f64; 2],
+        other_density: f64,
+    ) -> [f64; 2] {
+        if other_density <= 0.0 {
+            return [0.0, 0.0];
+        }
+
+        let r = offset[0].hypot(offset[1]);
+        let drag = self.viscosity * self.particle_mass / other_density
+            * viscosity_laplacian(r, self.smoothing_radius);
+        [drag * relative_velocity[0], drag * relative_velocity[1]]
+    }
+}
+
+impl AI for Sph {
+    fn compute(
+        &self,
+        entity: &Snapshot,
+        neighbors: &[Snapshot],
+        context: &BehaviorContext,
+    ) -> Steering {
+        // Densities aren't shared between behaviors, so each neighbor's is found again
+        // from its own neighbors.
+        let density_at = |center: [f64; 2]| {
+            let positions: Vec<[f64; 2]> = context
+                .neighbor_index
+                .within_radius(center, self.smoothing_radius)
+                .into_iter()
+                .map(|index| {
+                    let physics = context.snapshot[index].physics;
+                    [physics.x, physics.y]
+                })
+                .collect();
+            self.density(center, &positions)
+        };
+        let physics = entity.physics;
+        let density = density_at([physics.x, physics.y]);
+        if density <= 0.0 {
+            return Steering::default();
+        }
+        let pressure = self.pressure(density);
+
+        let mut force = [0.0, 0.0];
+        for other in neighbors {
+            let other_density = density_at([other.physics.x, other.physics.y]);
+            let offset = [physics.x - other.physics.x, physics.y - other.physics.y];
+            let relative_velocity = [other.physics.vx - physics.vx, other.physics.vy - physics.vy];
+
+            let [px, py] = self.pressure_force(
+                offset,
+                pressure,
+                self.pressure(other_density),
+                other_density,
+            );
+            let [vx, vy] = self.viscosity_force(offset, relative_velocity, other_density);
+            force[0] += px + vx;
+            force[1] += py + vy;
+        }
+
+        Steering::acceleration(force[0] / density, force[1] / density)
+    }
+
+    fn perception_radius(&self) -> Option<f64> {
+        Some(self.smoothing_radius)
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Sph {
+            smoothing_radius: self.smoothing_radius,
+            particle_mass: self.particle_mass,
+            rest_density: self.rest_density,
+            stiffness: self.stiffness,
+            viscosity: self.viscosity,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-12
+    }
+
+    #[test]
+    fn kernels_at_known_distances() {
+        assert!(close(poly6(0.0, 2.0), 1.0 / PI));
+        assert!(close(poly6(1.0, 2.0), 27.0 / (64.0 * PI)));
+        assert_eq!(poly6(2.0, 2.0), 0.0);
+        assert_eq!(poly6(3.0, 2.0), 0.0);
+
+        assert!(close(spiky_gradient(0.0, 2.0), -15.0 / (4.0 * PI)));
+        assert!(close(spiky_gradient(1.0, 2.0), -15.0 / (16.0 * PI)));
+        assert_eq!(spiky_gradient(2.0, 2.0), 0.0);
+    }
+
+    #[test]
+    fn poly6_weighs_to_one_over_its_disc() {
+        let (h, rings) = (2.0, 10_000);
+        let dr = h / rings as f64;
+        let total: f64 = (0..rings)
+            .map(|ring| {
+                let r = (ring as f64 + 0.5) * dr;
+                poly6(r, h) * 2.0 * PI * r * dr
+            })
+            .sum();
+
+        assert!((total - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,184 @@
+use std::sync::Mutex;
+
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+use crate::entity::Physics;
+
+// Holds a territory around where the entity spawned, its home: pulled back towards it
+// and pushed away from intruders of another owner inside it. Owners are the `owners`
+// tags, an entity belonging to the first it has.
+pub struct Territory {
+    pub owners: Vec<String>,
+    pub radius: f64,
+    // Acceleration per unit of distance from home.
+    pub attraction: f64,
+    // Acceleration from an intruder at home, falling off to nothing at the radius.
+    pub repulsion: f64,
+    home: Mutex<Option<[f64; 2]>>,
+}
+
+impl Territory {
+    pub fn new(owners: Vec<String>, radius: f64, attraction: f64, repulsion: f64) -> Territory {
+        Territory {
+            owners,
+            radius,
+            attraction,
+            repulsion,
+            home: Mutex::new(None),
+        }
+    }
+
+    // Territory around `home` from the start, as a saved one is restored.
+    pub fn with_home(self, home: [f64; 2]) -> Territory {
+        *self.home.lock().unwrap() = Some(home);
+        self
+    }
+
+    pub fn home(&self) -> Option<[f64; 2]> {
+        *self.home.lock().unwrap()
+    }
+
+    pub fn owner<'a>(&'a self, tags: &[String]) -> Option<&'a str> {
+        self.owners
+            .iter()
+            .find(|owner| tags.contains(owner))
+            .map(String::as_str)
+    }
+
+    // Whether an entity owned by `other` trespasses on territory owned by `own`.
+    // Unowned entities are never intruders.
+    pub fn intrudes(own: Option<&str>, other: Option<&str>) -> bool {
+        other.is_some() && other != own
+    }
+
+    // Acceleration back home plus away from each of `intruders`, those beyond the radius
+    // of home left out.
+    pub fn acceleration(
+        &self,
+        position: [f64; 2],
+        home: [f64; 2],
+        intruders: &[[f64; 2]],
+    ) -> [f64; 2] {
+        let mut acceleration = [
+            self.attraction * (home[0] - position[0]),
+            self.attraction * (home[1] - position[1]),
+        ];
+
+        for intruder in intruders {
+            let from_home = (intruder[0] - home[0]).hypot(intruder[1] - home[1]);
+            let (dx, dy) = (position[0] - intruder[0], position[1] - intruder[1]);
+            let distance = dx.hypot(dy);
+            if from_home > self.radius || distance < f64::EPSILON {
+                continue;
+            }
+
+            let push = self.repulsion * (1.0 - from_home / self.radius);
+            acceleration[0] += push * dx / distance;
+            acceleration[1] += push * dy / distance;
+        }
+
+        acceleration
+    }
+}
+
+impl AI for Territory {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let position = [entity.physics.x, entity.physics.y];
+        // Where it first runs when set without `with_behavior`, like a stage behavior.
+        let home = *self.home.lock().unwrap().get_or_insert(position);
+        let own = self.owner(context.tags_of(entity.id));
+
+        let intruders: Vec<[f64; 2]> = context
+            .neighbor_index
+            .within_radius(home, self.radius)
+            .into_iter()
+            .map(|index| context.snapshot[index])
+            .filter(|other| other.id != entity.id)
+            .filter(|other| Territory::intrudes(own, self.owner(context.tags_of(other.id))))
+            .map(|other| [other.physics.x, other.physics.y])
+            .collect();
+        let [ax, ay] = self.acceleration(position, home, &intruders);
+
+        Steering::acceleration(ax, ay)
+    }
+
+    // A restored home is kept.
+    fn on_spawn(&self, physics: &Physics) {
+        self.home
+            .lock()
+            .unwrap()
+            .get_or_insert([physics.x, physics.y]);
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Territory {
+            owners: self.owners.clone(),
+            radius: self.radius,
+            attraction: self.attraction,
+            repulsion: self.repulsion,
+            home: self.home(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{Entity, Renderer};
+    use crate::shapes::Circle;
+    use crate::world::World;
+
+    fn tagged(id: u32, x: f64, tag: &str) -> Entity {
+        let mut entity = Entity::new(
+            id,
+            Physics::new(x, 50.0, 2.0).unwrap(),
+            Renderer::Owned(Box::new(Circle::new([1.0; 4]))),
+        );
+        entity.tags = vec![tag.to_string()];
+        entity
+    }
+
+    fn holder(attraction: f64, repulsion: f64) -> Entity {
+        let owners = vec!["red".to_string(), "blue".to_string()];
+
+        tagged(1, 50.0, "red").with_behavior(Box::new(Territory::new(
+            owners, 20.0, attraction, repulsion,
+        )))
+    }
+
+    #[test]
+    fn home_is_the_spawn_point_and_pulls_back() {
+        let mut world = World::new(vec![holder(1.0, 0.0)], 100.0, 100.0).unwrap();
+        world.get_by_id_mut(1).unwrap().physics.x = 70.0;
+
+        world.step(0.1);
+
+        let entity = world.get_by_id(1).unwrap();
+        assert!((entity.physics.vx + 2.0).abs() < 1e-9);
+        let home = entity
+            .behavior
+            .as_ref()
+            .and_then(|behavior| behavior.to_tag());
+        assert!(matches!(
+            home,
+            Some(BehaviorConfig::Territory {
+                home: Some([50.0, 50.0]),
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn only_intruders_of_another_owner_are_repelled() {
+        let vx_next_to = |tag: &str| {
+            let entities = vec![holder(0.0, 10.0), tagged(2, 45.0, tag)];
+            let mut world = World::new(entities, 100.0, 100.0).unwrap();
+            world.step(0.1);
+            world.get_by_id(1).unwrap().physics.vx
+        };
+
+        // 10 * (1 - 5 / 20) away from the intruder, for a tenth of a second.
+        assert!((vx_next_to("blue") - 0.75).abs() < 1e-9);
+        assert_eq!(vx_next_to("red"), 0.0);
+    }
+}
@@ -0,0 +1,45 @@
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+use crate::entity::Physics;
+
+// Steers along the world's vector field where the entity is, for flow art. Entities off
+// the field keep going.
+pub struct VectorFieldFollow {
+    // Units per second along a vector of length one, the field's length scaling it.
+    pub speed: f64,
+    // How quickly the velocity turns towards the field's, per second.
+    pub strength: f64,
+}
+
+impl VectorFieldFollow {
+    // Acceleration bringing the velocity towards `speed` times `vector`.
+    pub fn steering(&self, physics: &Physics, vector: [f64; 2]) -> [f64; 2] {
+        [
+            (vector[0] * self.speed - physics.vx) * self.strength,
+            (vector[1] * self.speed - physics.vy) * self.strength,
+        ]
+    }
+}
+
+impl AI for VectorFieldFollow {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        let entity = &entity.physics;
+        let vector = match context
+            .vector_field
+            .and_then(|field| field.sample([entity.x, entity.y]))
+        {
+            Some(vector) => vector,
+            None => return Steering::default(),
+        };
+        let [ax, ay] = self.steering(entity, vector);
+
+        Steering::acceleration(ax, ay)
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::VectorFieldFollow {
+            speed: self.speed,
+            strength: self.strength,
+        })
+    }
+}
@@ -0,0 +1,109 @@
+use std::f64::consts::PI;
+
+use rand_distr::{Distribution, Normal};
+
+use super::{BehaviorContext, Snapshot, Steering, AI};
+use crate::config::BehaviorConfig;
+use crate::error::NatureError;
+use crate::generator::normal;
+
+// Heading of a step in each axis direction (-1, 0 or 1), none when standing still.
+pub fn direction_to_rotation(dx: i16, dy: i16) -> Option<f64> {
+    match (dx.signum(), dy.signum()) {
+        (-1, -1) => Some(-PI / 4.0),
+        (-1, 1) => Some(-3.0 * PI / 4.0),
+        (-1, _) => Some(-PI / 2.0),
+        (1, -1) => Some(PI / 4.0),
+        (1, 1) => Some(3.0 * PI / 4.0),
+        (1, _) => Some(PI / 2.0),
+        (_, -1) => Some(0.0),
+        (_, 1) => Some(PI),
+        _ => None,
+    }
+}
+
+pub struct Walker {
+    // Distance covered per tick.
+    speed: Normal<f64>,
+    // What `speed` was made from, which the distribution doesn't give back.
+    mean_speed: f64,
+    speed_stddev: f64,
+}
+
+impl Walker {
+    pub fn new(mean_speed: f64, speed_stddev: f64) -> Result<Walker, NatureError> {
+        let speed = normal(mean_speed, speed_stddev)?;
+
+        Ok(Walker {
+            speed,
+            mean_speed,
+            speed_stddev,
+        })
+    }
+}
+
+impl Default for Walker {
+    fn default() -> Walker {
+        Walker::new(2.0, 1.0).expect("default walker parameters are valid")
+    }
+}
+
+impl AI for Walker {
+    fn compute(&self, entity: &Snapshot, _: &[Snapshot], context: &BehaviorContext) -> Steering {
+        use rand::Rng;
+
+        let mut rng = context.rng(entity.id);
+        let random_x_direction: i16 = rng.gen_range(-1..2);
+        let random_y_direction: i16 = rng.gen_range(-1..2);
+        let speed: f64 = self.speed.sample(&mut rng);
+        let displacement = [
+            random_x_direction as f64 * speed,
+            random_y_direction as f64 * speed,
+        ];
+        let rotation = direction_to_rotation(random_x_direction, random_y_direction);
+
+        Steering {
+            displacement,
+            rotation,
+            ..Steering::default()
+        }
+    }
+
+    fn to_tag(&self) -> Option<BehaviorConfig> {
+        Some(BehaviorConfig::Walker {
+            mean_speed: self.mean_speed,
+            speed_stddev: self.speed_stddev,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn negative_speed_stddev_is_an_error() {
+        assert!(Walker::new(2.0, -1.0).is_err());
+        assert!(Walker::new(2.0, 1.0).is_ok());
+    }
+
+    #[test]
+    fn every_step_direction_has_its_heading() {
+        let cases = [
+            ((0, -1), Some(0.0)),
+            ((1, -1), Some(PI / 4.0)),
+            ((1, 0), Some(PI / 2.0)),
+            ((1, 1), Some(3.0 * PI / 4.0)),
+            ((0, 1), Some(PI)),
+            ((-1, 1), Some(-3.0 * PI / 4.0)),
+            ((-1, 0), Some(-PI / 2.0)),
+            ((-1, -1), Some(-PI / 4.0)),
+            ((0, 0), None),
+        ];
+
+        for ((dx, dy), rotation) in cases {
+            assert_eq!(direction_to_rotation(dx, dy), rotation, "({}, {})", dx, dy);
+        }
+        assert_eq!(direction_to_rotation(5, -3), Some(PI / 4.0));
+    }
+}
@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+// Weight of the latest measurement in the running cost per behavior.
+const COST_SMOOTHING: f64 = 0.2;
+
+// Caps the time behaviors take each step. Entities are taken in turn from where the last
+// step stopped and the rest keep moving without steering, so a big scene stays responsive
+// at the cost of each entity reacting less often.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TickBudget {
+    pub time: Duration,
+    // Index in `entities` of the first entity to update next step.
+    cursor: usize,
+    // Seconds one behavior was measured to take, none until the first step.
+    cost: Option<f64>,
+}
+
+impl TickBudget {
+    pub fn new(time: Duration) -> TickBudget {
+        TickBudget {
+            time,
+            cursor: 0,
+            cost: None,
+        }
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    // How many of `len` entities are updated this step, everyone until the cost is known
+    // and always at least one.
+    pub fn count(&self, len: usize) -> usize {
+        if len == 0 {
+            return 0;
+        }
+
+        match self.cost {
+            Some(cost) if cost > 0.0 => ((self.time.as_secs_f64() / cost) as usize).clamp(1, len),
+            _ => len,
+        }
+    }
+
+    // Moves on past the `count` entities updated in `elapsed` out of `len`.
+    pub fn record(&mut self, count: usize, len: usize, elapsed: Duration) {
+        if count > 0 {
+            let cost = elapsed.as_secs_f64() / count as f64;
+            self.cost = Some(match self.cost {
+                Some(previous) => previous + (cost - previous) * COST_SMOOTHING,
+                None => cost,
+            });
+        }
+
+        self.cursor = advance_cursor(self.cursor, count, len);
+    }
+}
+
+// Where the next step starts after updating `count` of `len` entities from `cursor`,
+// wrapping back to the start of the list.
+pub fn advance_cursor(cursor: usize, count: usize, len: usize) -> usize {
+    if len == 0 {
+        0
+    } else {
+        (cursor % len + count) % len
+    }
+}
+
+// Whether `index` is among the `count` entities from `cursor` on, wrapping at `len`.
+pub fn in_window(index: usize, cursor: usize, count: usize, len: usize) -> bool {
+    if len == 0 || count >= len {
+        return index < len;
+    }
+
+    (index + len - cursor % len) % len < count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_goes_round_the_entities_across_steps() {
+        let mut budget = TickBudget::new(Duration::from_micros(2500));
+        assert_eq!(budget.count(5), 5);
+
+        // One millisecond a behavior, two fit in the budget from then on.
+        budget.record(5, 5, Duration::from_millis(5));
+        assert_eq!(budget.cursor(), 0);
+
+        let mut windows = Vec::new();
+        for _ in 0..4 {
+            let (cursor, count) = (budget.cursor(), budget.count(5));
+            windows.push(
+                (0..5)
+                    .filter(|&index| in_window(index, cursor, count, 5))
+                    .collect::<Vec<usize>>(),
+            );
+            budget.record(count, 5, Duration::from_millis(count as u64));
+        }
+
+        assert_eq!(windows, [vec![0, 1], vec![2, 3], vec![0, 4], vec![1, 2]]);
+        assert_eq!(budget.cursor(), 3);
+        assert_eq!(advance_cursor(4, 3, 5), 2);
+        assert_eq!(advance_cursor(7, 1, 5), 3);
+        assert_eq!(advance_cursor(3, 1, 0), 0);
+    }
+}
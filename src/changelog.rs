@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use crate::entity::Physics;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Change {
+    Spawned { id: u32, physics: Physics },
+    Despawned { id: u32, physics: Physics },
+    Moved { id: u32, from: Physics, to: Physics },
+}
+
+// Everything that changed during one step, enough to replay it or undo it on a copy
+// of the entity states.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TickLog {
+    pub tick: u64,
+    pub changes: Vec<Change>,
+}
+
+impl TickLog {
+    // Diff between the states before and after the step, in id order.
+    pub fn diff(
+        tick: u64,
+        before: &HashMap<u32, Physics>,
+        after: &HashMap<u32, Physics>,
+    ) -> TickLog {
+        let mut changes = Vec::new();
+
+        for (&id, &to) in after.iter() {
+            match before.get(&id) {
+                None => changes.push(Change::Spawned { id, physics: to }),
+                Some(&from) if from != to => changes.push(Change::Moved { id, from, to }),
+                Some(_) => {}
+            }
+        }
+        for (&id, &physics) in before.iter() {
+            if !after.contains_key(&id) {
+                changes.push(Change::Despawned { id, physics });
+            }
+        }
+
+        changes.sort_by_key(|change| match *change {
+            Change::Spawned { id, .. }
+            | Change::Despawned { id, .. }
+            | Change::Moved { id, .. } => id,
+        });
+
+        TickLog { tick, changes }
+    }
+
+    pub fn apply(&self, states: &mut HashMap<u32, Physics>) {
+        for change in self.changes.iter() {
+            match *change {
+                Change::Spawned { id, physics } => {
+                    states.insert(id, physics);
+                }
+                Change::Despawned { id, .. } => {
+                    states.remove(&id);
+                }
+                Change::Moved { id, to, .. } => {
+                    states.insert(id, to);
+                }
+            }
+        }
+    }
+
+    pub fn revert(&self, states: &mut HashMap<u32, Physics>) {
+        for change in self.changes.iter() {
+            match *change {
+                Change::Spawned { id, .. } => {
+                    states.remove(&id);
+                }
+                Change::Despawned { id, physics } => {
+                    states.insert(id, physics);
+                }
+                Change::Moved { id, from, .. } => {
+                    states.insert(id, from);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn applying_the_diff_rebuilds_the_new_states() {
+        let at = |x: f64| Physics::new(x, 0.0, 1.0).unwrap();
+        let before: HashMap<u32, Physics> = [(0, at(1.0)), (1, at(2.0)), (2, at(3.0))].into();
+        let after: HashMap<u32, Physics> = [(0, at(1.0)), (1, at(5.0)), (3, at(4.0))].into();
+        let log = TickLog::diff(7, &before, &after);
+
+        assert_eq!(log.tick, 7);
+        assert_eq!(log.changes.len(), 3);
+
+        let mut states = before.clone();
+        log.apply(&mut states);
+        assert_eq!(states, after);
+
+        log.revert(&mut states);
+        assert_eq!(states, before);
+    }
+}
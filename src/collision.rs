@@ -0,0 +1,307 @@
+use serde::Deserialize;
+
+use crate::entity::Physics;
+use crate::spatial::NeighborQuery;
+
+// What happens when two entities overlap.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionResponse {
+    // They pass through each other.
+    #[default]
+    Ignore,
+    // Elastic bounce, heavier entities pushing lighter ones further.
+    Bounce,
+    // Entities of the same kind coalesce into one, others bounce.
+    Merge,
+}
+
+// What happens to a new entity that would overlap an existing one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpawnOverlap {
+    #[default]
+    Allow,
+    // It isn't spawned.
+    Reject,
+    // It's moved to the nearest free spot found, or not spawned without one.
+    Nudge,
+}
+
+// Rings of candidate spots tried around an overlapping spawn, and spots on each.
+const NUDGE_RINGS: usize = 16;
+const NUDGE_DIRECTIONS: usize = 12;
+
+// Nearest spot around `body` where it overlaps none of `others`, searched on rings half
+// its size apart. Its own position doesn't count, check `overlapping` first.
+pub fn free_position(body: &Physics, others: &[Physics]) -> Option<[f64; 2]> {
+    let step = body.size.max(1.0) / 2.0;
+
+    for ring in 1..=NUDGE_RINGS {
+        let distance = step * ring as f64;
+
+        for direction in 0..NUDGE_DIRECTIONS {
+            let angle = direction as f64 * std::f64::consts::TAU / NUDGE_DIRECTIONS as f64;
+            let candidate = Physics {
+                x: body.x + distance * angle.cos(),
+                y: body.y + distance * angle.sin(),
+                ..*body
+            };
+
+            if !others.iter().any(|other| overlapping(&candidate, other)) {
+                return Some([candidate.x, candidate.y]);
+            }
+        }
+    }
+
+    None
+}
+
+// Mass of an entity, proportional to its area.
+pub fn mass(physics: &Physics) -> f64 {
+    physics.size * physics.size
+}
+
+// Single body with the combined mass at the pair's center of mass, moving with their
+// total momentum.
+pub fn merged(a: &Physics, b: &Physics) -> Physics {
+    let (ma, mb) = (mass(a), mass(b));
+    let total = ma + mb;
+
+    Physics {
+        x: (a.x * ma + b.x * mb) / total,
+        y: (a.y * ma + b.y * mb) / total,
+        size: total.sqrt(),
+        rotation: if ma >= mb { a.rotation } else { b.rotation },
+        vx: (a.vx * ma + b.vx * mb) / total,
+        vy: (a.vy * ma + b.vy * mb) / total,
+        angular_velocity: if ma >= mb {
+            a.angular_velocity
+        } else {
+            b.angular_velocity
+        },
+    }
+}
+
+// Pushes an overlapping pair apart and exchanges the velocity along the line between
+// their centers, conserving momentum. `restitution` is the fraction of the closing speed
+// they separate with, 1 keeping all the energy and 0 leaving them moving together.
+pub fn bounce(a: &mut Physics, b: &mut Physics, restitution: f64) {
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let distance = (dx * dx + dy * dy).sqrt();
+
+    if distance < f64::EPSILON {
+        return;
+    }
+
+    let (nx, ny) = (dx / distance, dy / distance);
+    let (ma, mb) = (mass(a), mass(b));
+    let total = ma + mb;
+
+    // Separate in proportion to the other's mass so the heavier one moves less.
+    let overlap = (a.size + b.size) / 2.0 - distance;
+    if overlap > 0.0 {
+        a.x -= nx * overlap * mb / total;
+        a.y -= ny * overlap * mb / total;
+        b.x += nx * overlap * ma / total;
+        b.y += ny * overlap * ma / total;
+    }
+
+    let closing = (a.vx - b.vx) * nx + (a.vy - b.vy) * ny;
+    if closing <= 0.0 {
+        return;
+    }
+
+    let impulse = (1.0 + restitution.clamp(0.0, 1.0)) * closing / total;
+    a.vx -= impulse * mb * nx;
+    a.vy -= impulse * mb * ny;
+    b.vx += impulse * ma * nx;
+    b.vy += impulse * ma * ny;
+}
+
+// Like `bounce` with `obstacle` immovable, as if infinitely heavy: only `physics` is
+// pushed out and has the velocity going into the obstacle reflected.
+pub fn bounce_off(physics: &mut Physics, obstacle: &Physics, restitution: f64) {
+    let dx = physics.x - obstacle.x;
+    let dy = physics.y - obstacle.y;
+    let distance = (dx * dx + dy * dy).sqrt();
+
+    if distance < f64::EPSILON {
+        return;
+    }
+
+    let (nx, ny) = (dx / distance, dy / distance);
+    let overlap = (physics.size + obstacle.size) / 2.0 - distance;
+    if overlap > 0.0 {
+        physics.x += nx * overlap;
+        physics.y += ny * overlap;
+    }
+
+    let into = physics.vx * nx + physics.vy * ny;
+    if into < 0.0 {
+        let impulse = (1.0 + restitution.clamp(0.0, 1.0)) * into;
+        physics.vx -= impulse * nx;
+        physics.vy -= impulse * ny;
+    }
+}
+
+// Line segment entities bounce off, drawn by hand or configured.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Wall {
+    pub from: [f64; 2],
+    pub to: [f64; 2],
+}
+
+impl Wall {
+    // Point of the segment closest to `point`.
+    pub fn closest_point(&self, point: [f64; 2]) -> [f64; 2] {
+        let [dx, dy] = [self.to[0] - self.from[0], self.to[1] - self.from[1]];
+        let length_squared = dx * dx + dy * dy;
+
+        if length_squared < f64::EPSILON {
+            return self.from;
+        }
+
+        let t = (((point[0] - self.from[0]) * dx + (point[1] - self.from[1]) * dy)
+            / length_squared)
+            .clamp(0.0, 1.0);
+
+        [self.from[0] + dx * t, self.from[1] + dy * t]
+    }
+}
+
+// Pushes a circle overlapping the wall back out and reflects the part of its velocity
+// going into the wall, keeping `restitution` of it. The normal is taken from the closest
+// point, so the ends of the segment act as round caps. Returns whether they touched.
+pub fn reflect_off_wall(physics: &mut Physics, wall: &Wall, restitution: f64) -> bool {
+    let radius = physics.size / 2.0;
+    let [cx, cy] = wall.closest_point([physics.x, physics.y]);
+    let [dx, dy] = [physics.x - cx, physics.y - cy];
+    let distance = (dx * dx + dy * dy).sqrt();
+
+    if distance >= radius {
+        return false;
+    }
+
+    // A center right on the segment has no side, push it out against its motion.
+    let (nx, ny) = if distance > f64::EPSILON {
+        (dx / distance, dy / distance)
+    } else {
+        let [wx, wy] = [wall.to[0] - wall.from[0], wall.to[1] - wall.from[1]];
+        let length = (wx * wx + wy * wy).sqrt().max(f64::EPSILON);
+        let (nx, ny) = (-wy / length, wx / length);
+        if physics.vx * nx + physics.vy * ny > 0.0 {
+            (-nx, -ny)
+        } else {
+            (nx, ny)
+        }
+    };
+
+    physics.x = cx + nx * radius;
+    physics.y = cy + ny * radius;
+
+    let into = physics.vx * nx + physics.vy * ny;
+    if into < 0.0 {
+        let impulse = (1.0 + restitution.clamp(0.0, 1.0)) * into;
+        physics.vx -= impulse * nx;
+        physics.vy -= impulse * ny;
+    }
+
+    true
+}
+
+// Entities on every layer colliding with every other, by default.
+pub const DEFAULT_COLLISION_LAYER: u32 = 1;
+pub const DEFAULT_COLLISION_MASK: u32 = u32::MAX;
+
+// Whether two entities collide given their layer bits and the layers each collides with.
+// Both have to accept the other, so either one can opt out.
+pub fn layers_collide(a: (u32, u32), b: (u32, u32)) -> bool {
+    let ((layer_a, mask_a), (layer_b, mask_b)) = (a, b);
+
+    layer_a & mask_b != 0 && layer_b & mask_a != 0
+}
+
+// Entities are treated as circles of diameter `size`, touching doesn't count.
+pub fn overlapping(a: &Physics, b: &Physics) -> bool {
+    let dx = a.x - b.x;
+    let dy = a.y - b.y;
+    let reach = (a.size + b.size) / 2.0;
+
+    dx * dx + dy * dy < reach * reach
+}
+
+// Index pairs (i, j) with i < j of the overlapping bodies, `index` must be built over
+// the positions of `bodies`.
+pub fn collisions(bodies: &[Physics], index: &dyn NeighborQuery) -> Vec<(usize, usize)> {
+    let largest = bodies.iter().map(|body| body.size).fold(0.0, f64::max);
+    let mut pairs = Vec::new();
+
+    for (i, body) in bodies.iter().enumerate() {
+        let radius = (body.size + largest) / 2.0;
+        let mut neighbors = index.within_radius([body.x, body.y], radius);
+
+        neighbors.sort_unstable();
+        for j in neighbors {
+            if j > i && overlapping(body, &bodies[j]) {
+                pairs.push((i, j));
+            }
+        }
+    }
+
+    pairs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Equal bodies overlapping along x, closing at 4 units per second.
+    fn pair() -> (Physics, Physics) {
+        let mut a = Physics::new(0.0, 0.0, 2.0).unwrap();
+        let mut b = Physics::new(1.5, 0.0, 2.0).unwrap();
+        a.vx = 3.0;
+        b.vx = -1.0;
+        (a, b)
+    }
+
+    #[test]
+    fn restitution_sets_the_separating_speed() {
+        let (mut a, mut b) = pair();
+        bounce(&mut a, &mut b, 0.0);
+        assert!((b.vx - a.vx).abs() < 1e-9);
+        assert!((a.vx + b.vx - 2.0).abs() < 1e-9);
+        assert!((b.x - a.x - 2.0).abs() < 1e-9);
+
+        let (mut a, mut b) = pair();
+        bounce(&mut a, &mut b, 1.0);
+        assert!((a.vx + 1.0).abs() < 1e-9);
+        assert!((b.vx - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn wall_reflects_the_velocity_going_into_it() {
+        let wall = Wall {
+            from: [0.0, 10.0],
+            to: [20.0, 10.0],
+        };
+        let mut physics = Physics::new(5.0, 9.5, 2.0).unwrap();
+        physics.vx = 3.0;
+        physics.vy = 4.0;
+
+        assert!(reflect_off_wall(&mut physics, &wall, 0.5));
+        assert_eq!([physics.x, physics.y], [5.0, 9.0]);
+        assert_eq!([physics.vx, physics.vy], [3.0, -2.0]);
+
+        // Already leaving, only pushed out.
+        let mut physics = Physics::new(5.0, 10.5, 2.0).unwrap();
+        physics.vy = 4.0;
+        assert!(reflect_off_wall(&mut physics, &wall, 0.5));
+        assert_eq!([physics.y, physics.vy], [11.0, 4.0]);
+
+        let mut clear = Physics::new(5.0, 12.0, 2.0).unwrap();
+        assert!(!reflect_off_wall(&mut clear, &wall, 0.5));
+    }
+}
@@ -0,0 +1,356 @@
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::generator::color_generator;
+
+// Linear blend from `start` at t = 0 to `end` at t = 1, t clamped to that range.
+pub fn lerp_color(start: [f32; 4], end: [f32; 4], t: f64) -> [f32; 4] {
+    let t = t.clamp(0.0, 1.0) as f32;
+
+    [
+        start[0] + (end[0] - start[0]) * t,
+        start[1] + (end[1] - start[1]) * t,
+        start[2] + (end[2] - start[2]) * t,
+        start[3] + (end[3] - start[3]) * t,
+    ]
+}
+
+// Day and night cycle tinting every entity, `period` seconds of simulated time long and
+// starting at midnight.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Daylight {
+    pub period: f64,
+    // Multiplied into the colors at noon and at midnight, eased between.
+    pub day: [f32; 4],
+    pub night: [f32; 4],
+}
+
+impl Daylight {
+    // Tint `time` seconds into the cycle, `night` at whole periods and `day` halfway.
+    pub fn tint(&self, time: f64) -> [f32; 4] {
+        if !(self.period.is_finite() && self.period > 0.0) {
+            return self.day;
+        }
+
+        let phase = (time / self.period).rem_euclid(1.0);
+        let light = (1.0 - (phase * std::f64::consts::TAU).cos()) / 2.0;
+
+        lerp_color(self.night, self.day, light)
+    }
+}
+
+// Component-wise product, for tints.
+pub fn multiply_color(color: [f32; 4], tint: [f32; 4]) -> [f32; 4] {
+    [
+        color[0] * tint[0],
+        color[1] * tint[1],
+        color[2] * tint[2],
+        color[3] * tint[3],
+    ]
+}
+
+// Birth to death gradient, holding the end color once the lifetime is over.
+pub fn age_color(start: [f32; 4], end: [f32; 4], age: f64, lifetime: f64) -> [f32; 4] {
+    if lifetime <= 0.0 {
+        return end;
+    }
+
+    lerp_color(start, end, age / lifetime)
+}
+
+// Color of an entity `elapsed` seconds into a flash, starting at `flash` and fading back
+// to `base` over `duration`.
+pub fn flash_color(base: [f32; 4], flash: [f32; 4], elapsed: f64, duration: f64) -> [f32; 4] {
+    if duration <= 0.0 || elapsed >= duration {
+        return base;
+    }
+
+    lerp_color(flash, base, elapsed / duration)
+}
+
+// Hue in turns from 0 to 1, saturation and value from 0 to 1. Alpha is left out.
+pub fn rgb_to_hsv(color: [f32; 4]) -> [f32; 3] {
+    let [r, g, b, _] = color;
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let delta = max - min;
+
+    let hue = if delta <= 0.0 {
+        0.0
+    } else if max == r {
+        ((g - b) / delta).rem_euclid(6.0) / 6.0
+    } else if max == g {
+        ((b - r) / delta + 2.0) / 6.0
+    } else {
+        ((r - g) / delta + 4.0) / 6.0
+    };
+    let saturation = if max <= 0.0 { 0.0 } else { delta / max };
+
+    [hue, saturation, max]
+}
+
+pub fn hsv_to_rgb(hsv: [f32; 3], alpha: f32) -> [f32; 4] {
+    let [hue, saturation, value] = hsv;
+    let sector = hue.rem_euclid(1.0) * 6.0;
+    let chroma = value * saturation;
+    let x = chroma * (1.0 - (sector.rem_euclid(2.0) - 1.0).abs());
+    let [r, g, b] = match sector as u32 {
+        0 => [chroma, x, 0.0],
+        1 => [x, chroma, 0.0],
+        2 => [0.0, chroma, x],
+        3 => [0.0, x, chroma],
+        4 => [x, 0.0, chroma],
+        _ => [chroma, 0.0, x],
+    };
+    let offset = value - chroma;
+
+    [r + offset, g + offset, b + offset, alpha]
+}
+
+// Euclidean distance between the RGB parts, scaled so black to white is 1. Alpha is
+// left out.
+pub fn color_distance(a: [f32; 4], b: [f32; 4]) -> f64 {
+    let squared: f32 = (0..3).map(|i| (a[i] - b[i]) * (a[i] - b[i])).sum();
+
+    (squared as f64 / 3.0).sqrt()
+}
+
+const HEADING_SATURATION: f32 = 0.8;
+const HEADING_VALUE: f32 = 0.9;
+
+// Color wheel of headings, a full turn of rotation going once around the hues.
+pub fn rotation_color(rotation: f64) -> [f32; 4] {
+    let hue = (rotation / (2.0 * std::f64::consts::PI)).rem_euclid(1.0) as f32;
+
+    hsv_to_rgb([hue, HEADING_SATURATION, HEADING_VALUE], 1.0)
+}
+
+// Moves the color `amount` of the way to white, keeping its alpha.
+pub fn brighten(color: [f32; 4], amount: f32) -> [f32; 4] {
+    let amount = amount.clamp(0.0, 1.0);
+
+    [
+        color[0] + (1.0 - color[0]) * amount,
+        color[1] + (1.0 - color[1]) * amount,
+        color[2] + (1.0 - color[2]) * amount,
+        color[3],
+    ]
+}
+
+// Fractional part of the golden ratio, stepping the hue by it keeps neighbors apart.
+const GOLDEN_RATIO_CONJUGATE: f64 = 0.618_033_988_749_895;
+const ID_SATURATION: f32 = 0.65;
+const ID_VALUE: f32 = 0.95;
+
+// Stable color for an id, the same on every run and machine. Hues follow the golden
+// ratio so consecutive ids land far apart on the color wheel.
+pub fn color_from_id(id: u32) -> [f32; 4] {
+    hsv_to_rgb([id_phase(id) as f32, ID_SATURATION, ID_VALUE], 1.0)
+}
+
+// Phase in turns an entity's hue cycle is offset by, spread like `color_from_id`.
+pub fn id_phase(id: u32) -> f64 {
+    (id as f64 * GOLDEN_RATIO_CONJUGATE).fract()
+}
+
+// Hue in turns at `time` for a cycle of `period` seconds started `phase` turns in.
+pub fn cycle_hue(time: f64, phase: f64, period: f64) -> f32 {
+    (time / period + phase).rem_euclid(1.0) as f32
+}
+
+// Shimmer going once around the hues every `period` seconds of simulated time, each
+// entity offset by a phase from its id so they don't change in lockstep.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct HueCycle {
+    pub period: f64,
+    #[serde(default = "default_cycle_saturation")]
+    pub saturation: f32,
+    #[serde(default = "default_cycle_value")]
+    pub value: f32,
+}
+
+fn default_cycle_saturation() -> f32 {
+    ID_SATURATION
+}
+
+fn default_cycle_value() -> f32 {
+    ID_VALUE
+}
+
+impl HueCycle {
+    pub fn color(&self, id: u32, time: f64) -> [f32; 4] {
+        let hue = cycle_hue(time, id_phase(id), self.period);
+
+        hsv_to_rgb([hue, self.saturation, self.value], 1.0)
+    }
+}
+
+// Where the colors of a configured group come from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ColorSource {
+    // Red across and green down the world, see `color_generator`.
+    #[default]
+    Position,
+    // Uniformly within `spread` of `base` in HSV, hue in turns, keeping the base alpha.
+    Random {
+        base: [f32; 4],
+        spread: f32,
+    },
+}
+
+impl ColorSource {
+    pub fn sample<R: Rng>(
+        &self,
+        position: [f64; 2],
+        width: u32,
+        height: u32,
+        rng: &mut R,
+    ) -> [f32; 4] {
+        match *self {
+            ColorSource::Position => {
+                color_generator(position[0] as f32, position[1] as f32, width, height)
+            }
+            ColorSource::Random { base, spread } => {
+                let [hue, saturation, value] = rgb_to_hsv(base);
+                let spread = spread.abs();
+                let mut offset = || {
+                    if spread > 0.0 {
+                        rng.gen_range(-spread..=spread)
+                    } else {
+                        0.0
+                    }
+                };
+                let hsv = [
+                    (hue + offset()).rem_euclid(1.0),
+                    (saturation + offset()).clamp(0.0, 1.0),
+                    (value + offset()).clamp(0.0, 1.0),
+                ];
+
+                hsv_to_rgb(hsv, base[3])
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn age_color_blends_over_the_lifetime() {
+        let start = [1.0, 0.0, 0.0, 1.0];
+        let end = [0.0, 0.0, 1.0, 0.0];
+
+        assert_eq!(age_color(start, end, 0.0, 4.0), start);
+        assert_eq!(age_color(start, end, 2.0, 4.0), [0.5, 0.0, 0.5, 0.5]);
+        assert_eq!(age_color(start, end, 6.0, 4.0), end);
+    }
+
+    #[test]
+    fn random_colors_stay_within_the_spread() {
+        let base = hsv_to_rgb([0.5, 0.5, 0.5], 0.8);
+        let source = ColorSource::Random { base, spread: 0.1 };
+        let mut rng = StdRng::seed_from_u64(9);
+
+        for _ in 0..500 {
+            let color = source.sample([0.0, 0.0], 100, 100, &mut rng);
+            let [hue, saturation, value] = rgb_to_hsv(color);
+
+            assert!((hue - 0.5).abs() <= 0.1 + 1e-4, "{:?}", color);
+            assert!((saturation - 0.5).abs() <= 0.1 + 1e-4, "{:?}", color);
+            assert!((value - 0.5).abs() <= 0.1 + 1e-4, "{:?}", color);
+            assert_eq!(color[3], 0.8);
+        }
+    }
+
+    #[test]
+    fn id_colors_are_stable_and_spread_apart() {
+        assert_eq!(color_from_id(42), color_from_id(42));
+
+        for id in 0..50 {
+            let (a, b) = (
+                rgb_to_hsv(color_from_id(id)),
+                rgb_to_hsv(color_from_id(id + 1)),
+            );
+            let gap = (a[0] - b[0]).rem_euclid(1.0);
+
+            assert!(gap.min(1.0 - gap) > 0.2, "ids {} and {}", id, id + 1);
+        }
+    }
+
+    #[test]
+    fn headings_get_distinct_colors() {
+        let quarter = std::f64::consts::FRAC_PI_2;
+        let colors: Vec<[f32; 4]> = (0..4)
+            .map(|turn| rotation_color(turn as f64 * quarter))
+            .collect();
+
+        for (i, a) in colors.iter().enumerate() {
+            for b in colors.iter().skip(i + 1) {
+                assert!(color_distance(*a, *b) > 0.1, "{:?} and {:?}", a, b);
+            }
+        }
+        // A full turn comes back to the same color.
+        let full = rotation_color(4.0 * quarter);
+        assert!(color_distance(full, colors[0]) < 1e-6);
+    }
+
+    #[test]
+    fn flash_fades_from_the_flash_color_back_to_the_base() {
+        let base = [0.0, 0.0, 1.0, 1.0];
+        let flash = [1.0, 1.0, 1.0, 1.0];
+
+        assert_eq!(flash_color(base, flash, 0.0, 0.5), flash);
+        assert_eq!(flash_color(base, flash, 0.25, 0.5), [0.5, 0.5, 1.0, 1.0]);
+        assert_eq!(flash_color(base, flash, 0.5, 0.5), base);
+        assert_eq!(flash_color(base, flash, 3.0, 0.5), base);
+        assert_eq!(flash_color(base, flash, 0.0, 0.0), base);
+    }
+
+    #[test]
+    fn daylight_runs_from_night_to_day_and_back() {
+        let daylight = Daylight {
+            period: 24.0,
+            day: [1.0, 1.0, 1.0, 1.0],
+            night: [0.2, 0.2, 0.4, 1.0],
+        };
+        let close = |a: [f32; 4], b: [f32; 4]| a.iter().zip(&b).all(|(a, b)| (a - b).abs() < 1e-6);
+
+        assert!(close(daylight.tint(0.0), daylight.night));
+        assert!(close(daylight.tint(6.0), [0.6, 0.6, 0.7, 1.0]));
+        assert!(close(daylight.tint(12.0), daylight.day));
+        assert!(close(daylight.tint(18.0), [0.6, 0.6, 0.7, 1.0]));
+        assert!(close(daylight.tint(48.0), daylight.night));
+
+        let stopped = Daylight {
+            period: 0.0,
+            ..daylight
+        };
+        assert_eq!(stopped.tint(5.0), stopped.day);
+    }
+
+    #[test]
+    fn hue_cycles_are_offset_by_id_and_repeat_every_period() {
+        let cycle = HueCycle {
+            period: 4.0,
+            saturation: 0.8,
+            value: 0.9,
+        };
+
+        assert_eq!(cycle_hue(1.0, 0.0, 4.0), 0.25);
+        assert_eq!(cycle_hue(3.0, 0.5, 4.0), 0.25);
+        assert_ne!(cycle.color(1, 0.5), cycle.color(2, 0.5));
+        for id in 0..4 {
+            let (now, later) = (cycle.color(id, 0.5), cycle.color(id, 4.5));
+            for channel in 0..4 {
+                assert!((now[channel] - later[channel]).abs() < 1e-6);
+            }
+        }
+    }
+}
@@ -0,0 +1,2366 @@
+use std::fmt;
+use std::fs;
+use std::time::Duration;
+
+use glutin_window::GlutinWindow;
+use opengl_graphics::OpenGL;
+use piston::window::WindowSettings;
+use serde::{Deserialize, Serialize};
+
+use crate::behavior::{
+    step_rng, Ant, Chemotaxis, ColorCluster, Containment, Convection, Corral, Crystallize, Diffuse,
+    Dipole, Disperse, Evacuate, FieldLines, Flock, FlowFieldFollow, Follow, Formation,
+    FormationShape, Grow, LaneFlow, LevyFlight, Lissajous, Magnetism, NBodyGravity, Orbit,
+    PathFollow, Patrol, Pen, Rendezvous, ScatterGather, School, Seek, Sph, Target, Territory,
+    VectorFieldFollow, Walker, AI,
+};
+use crate::budget::TickBudget;
+use crate::collision::{
+    CollisionResponse, SpawnOverlap, Wall, DEFAULT_COLLISION_LAYER, DEFAULT_COLLISION_MASK,
+};
+use crate::color::{ColorSource, Daylight, HueCycle};
+use crate::coords::CoordinateSystem;
+use crate::entity::{Entity, EntityKind, Physics, Renderer};
+use crate::epidemic::{Epidemic, HealthState};
+use crate::error::NatureError;
+use crate::field::{ScalarField, Source};
+use crate::flowfield::FlowField;
+use crate::forces::Attractor;
+use crate::generator::{gaussian_positions, OutOfBounds};
+use crate::integrator::Integrator;
+use crate::lanes::LaneField;
+use crate::lifecycle::{Lifecycle, Stage};
+use crate::links::Linking;
+use crate::pathfinding::NavGrid;
+use crate::pheromone::PheromoneGrid;
+use crate::portal::PortalPair;
+use crate::reaction::{GrayScott, Reaction, ReactionGrid};
+use crate::regions::{Region, RegionMap};
+use crate::render::{Connections, Downsample, Glow, Highlight, Overlay, Shadow};
+use crate::shapes::{ShapeFactory, ShapeKind};
+use crate::spatial::SpatialIndexKind;
+use crate::vectorfield::VectorField;
+use crate::world::{BoundaryMode, Flash, Sleep, World};
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(String),
+    Parse(String),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigError::Io(message) => write!(f, "could not read config: {}", message),
+            ConfigError::Parse(message) => write!(f, "could not parse config: {}", message),
+            ConfigError::Invalid(message) => write!(f, "invalid config: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+fn default_width() -> u32 {
+    800
+}
+
+fn default_height() -> u32 {
+    500
+}
+
+fn default_background_color() -> [f32; 4] {
+    [0.0, 1.0, 0.0, 1.0]
+}
+
+pub const DEFAULT_UPS: u64 = 120;
+pub const MAX_UPS: u64 = 1000;
+
+fn default_ups() -> i64 {
+    DEFAULT_UPS as i64
+}
+
+pub const DEFAULT_OPENGL: OpenGL = OpenGL::V3_2;
+
+// Parses an OpenGL version written like "3.2".
+pub fn parse_opengl(text: &str) -> Result<OpenGL, ConfigError> {
+    match text.trim() {
+        "2.0" => Ok(OpenGL::V2_0),
+        "2.1" => Ok(OpenGL::V2_1),
+        "3.0" => Ok(OpenGL::V3_0),
+        "3.1" => Ok(OpenGL::V3_1),
+        "3.2" => Ok(OpenGL::V3_2),
+        "3.3" => Ok(OpenGL::V3_3),
+        "4.0" => Ok(OpenGL::V4_0),
+        "4.1" => Ok(OpenGL::V4_1),
+        "4.2" => Ok(OpenGL::V4_2),
+        "4.3" => Ok(OpenGL::V4_3),
+        "4.4" => Ok(OpenGL::V4_4),
+        "4.5" => Ok(OpenGL::V4_5),
+        other => Err(ConfigError::Invalid(format!(
+            "unsupported OpenGL version {:?}, expected 2.0 to 4.5 like \"3.2\"",
+            other
+        ))),
+    }
+}
+
+// Display flags of the window, set by the config or the command line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WindowOptions {
+    pub vsync: bool,
+    pub fullscreen: bool,
+}
+
+pub fn window_settings(
+    title: &str,
+    size: [u32; 2],
+    opengl: OpenGL,
+    options: WindowOptions,
+) -> WindowSettings {
+    WindowSettings::new(title, size)
+        .graphics_api(opengl)
+        .exit_on_esc(true)
+        .vsync(options.vsync)
+        .fullscreen(options.fullscreen)
+}
+
+// Whether a window could be shown at all. Linux and the BSDs need an X11 or Wayland
+// display, which the windowing backend aborts without rather than reporting.
+pub fn display_available() -> bool {
+    if cfg!(all(unix, not(target_os = "macos"), not(target_os = "ios"))) {
+        return ["DISPLAY", "WAYLAND_DISPLAY"]
+            .iter()
+            .any(|name| std::env::var_os(name).is_some_and(|value| !value.is_empty()));
+    }
+
+    true
+}
+
+// Why a window of `size` can't be opened before trying, so the failure is an error to
+// act on rather than a crash in the backend.
+pub fn check_window(size: [u32; 2], display_available: bool) -> Result<(), NatureError> {
+    if size[0] == 0 || size[1] == 0 {
+        return Err(NatureError::Window(format!(
+            "the window needs a positive size, got {}x{}",
+            size[0], size[1]
+        )));
+    }
+    if !display_available {
+        return Err(NatureError::Window(
+            "no display found, set DISPLAY or WAYLAND_DISPLAY or run with --headless --steps N --out PATH"
+                .to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+pub fn build_window(
+    title: &str,
+    size: [u32; 2],
+    opengl: OpenGL,
+    options: WindowOptions,
+) -> Result<GlutinWindow, NatureError> {
+    check_window(size, display_available())?;
+
+    window_settings(title, size, opengl, options)
+        .build()
+        .map_err(|error| {
+            NatureError::Window(format!(
+                "{} with {:?}, an older version like --gl 2.1 may work",
+                error, opengl
+            ))
+        })
+}
+
+// Why the options don't go with the rest of the window setup, if they don't. A
+// fullscreen window takes the size of the display, not the one configured.
+pub fn window_warning(options: WindowOptions, configured_size: Option<[u32; 2]>) -> Option<String> {
+    match configured_size {
+        Some([width, height]) if options.fullscreen => Some(format!(
+            "fullscreen ignores the configured {}x{} window size",
+            width, height
+        )),
+        _ => None,
+    }
+}
+
+// Physics updates per second, at least one and at most MAX_UPS.
+pub fn clamp_ups(ups: i64) -> u64 {
+    ups.clamp(1, MAX_UPS as i64) as u64
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    #[serde(default = "default_width")]
+    pub width: u32,
+    #[serde(default = "default_height")]
+    pub height: u32,
+    #[serde(default = "default_background_color")]
+    pub background_color: [f32; 4],
+    // Fixed physics rate, independent of how often frames are rendered.
+    #[serde(default = "default_ups")]
+    pub ups: i64,
+    // Also turned on by --vsync and --fullscreen on the command line.
+    #[serde(default)]
+    pub vsync: bool,
+    #[serde(default)]
+    pub fullscreen: bool,
+    // OpenGL version such as "3.2", overridden by --gl on the command line.
+    #[serde(default)]
+    pub gl: Option<String>,
+    // Font used for the id labels, they stay hidden without one.
+    #[serde(default)]
+    pub font: Option<String>,
+    #[serde(default)]
+    pub max_entities: Option<usize>,
+    // Seconds after which entities are despawned, see `World::max_age`.
+    pub max_age: Option<f64>,
+    #[serde(default)]
+    pub spawn_ease: f64,
+    #[serde(default)]
+    pub pause_on_focus_loss: bool,
+    // World units between the ticks of the axes overlay.
+    pub axis_spacing: Option<f64>,
+    // Distance between entities painted with the middle button.
+    pub paint_spacing: Option<f64>,
+    // Draw entities as one batch once there are more than this many.
+    pub batch_threshold: Option<usize>,
+    // Lines between each entity and its nearest neighbors, shown from the start when
+    // set and toggled with N.
+    pub connections: Option<Connections>,
+    // Draw only a sample of the entities once there are more than its threshold.
+    pub downsample: Option<Downsample>,
+    // Offset shadow drawn beneath every entity.
+    pub shadow: Option<Shadow>,
+    // Draw circles as polygons with this many sides rather than the backend's ellipses.
+    pub circle_segments: Option<usize>,
+    // Draw entities in between physics steps for smoother motion.
+    #[serde(default)]
+    pub interpolate: bool,
+    // How selected entities are marked.
+    #[serde(default)]
+    pub highlight: Highlight,
+    // Draw order of the overlays, the default one when not set.
+    pub overlays: Option<Vec<Overlay>>,
+    #[serde(default)]
+    pub collisions: CollisionResponse,
+    // Whether spawns may land inside existing entities, see `World::try_spawn`.
+    #[serde(default)]
+    pub spawn_overlap: SpawnOverlap,
+    // Lets resting entities stop being updated.
+    pub sleep: Option<Sleep>,
+    // Entities flash on the listed events.
+    pub flash: Option<Flash>,
+    // Day and night tint cycling with simulated time.
+    pub daylight: Option<Daylight>,
+    // Springs forming between nearby entities and snapping when overstretched.
+    pub linking: Option<Linking>,
+    // SIR spread among the groups given a health.
+    pub epidemic: Option<Epidemic>,
+    // Makes the scene reproducible, from the spawn positions to every random behavior.
+    // "auto" draws one from the system, printed at startup to replay the run with.
+    pub seed: Option<Seed>,
+    // Update entities by id instead of storage order, see `World::deterministic_order`.
+    #[serde(default)]
+    pub deterministic_order: bool,
+    // Milliseconds behaviors may take per step, see `World::budget`.
+    pub tick_budget: Option<f64>,
+    // Air drag per unit of entity size, see `World::drag`.
+    #[serde(default)]
+    pub drag: f64,
+    #[serde(default = "default_restitution")]
+    pub restitution: f64,
+    #[serde(default)]
+    pub integrator: Integrator,
+    // Split steps so fast entities can't go through thin obstacles, see `World::max_substeps`.
+    pub max_substeps: Option<usize>,
+    #[serde(default)]
+    pub coordinates: CoordinateSystem,
+    #[serde(default)]
+    pub lanes: Option<LaneConfig>,
+    // Sources of the scalar field climbed by chemotaxis, in the configured coordinates.
+    #[serde(default)]
+    pub field: Vec<Source>,
+    // Segments entities bounce off, in the configured coordinates.
+    #[serde(default)]
+    pub walls: Vec<Wall>,
+    // Point sources pulling every entity in, in the configured coordinates.
+    #[serde(default)]
+    pub attractors: Vec<Attractor>,
+    // Waypoints flocks with a migration weight travel along, in the configured
+    // coordinates.
+    #[serde(default)]
+    pub migration_path: Vec<[f64; 2]>,
+    // Areas entities are kept in, moving between them only through the openings, in
+    // the configured coordinates.
+    pub regions: Option<RegionMap>,
+    // Linked pairs of circles entities teleport between, in the configured coordinates.
+    #[serde(default)]
+    pub portals: Vec<PortalPair>,
+    // Paths followed by flow_field_follow, in the configured coordinates.
+    pub flow_field: Option<FlowFieldConfig>,
+    // Blocked cells path_follow finds its way around, in the configured coordinates.
+    pub nav_grid: Option<NavGridConfig>,
+    // Directions followed by vector_field_follow, in the configured coordinates.
+    pub vector_field: Option<VectorFieldConfig>,
+    // Grid ants lay pheromone in, in the configured coordinates.
+    pub pheromones: Option<PheromoneConfig>,
+    // Gray-Scott grid growing patterns of entities, in the configured coordinates.
+    pub reaction: Option<ReactionConfig>,
+    #[serde(default)]
+    pub groups: Vec<GroupConfig>,
+    // Index neighbors are looked up in, the world's default grid when not set.
+    pub spatial_index: Option<SpatialIndexKind>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SeedKeyword {
+    Auto,
+}
+
+// A fixed seed, or the keyword for one drawn from the system.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Seed {
+    Fixed(u64),
+    Keyword(SeedKeyword),
+}
+
+// Concrete seed of a run, `entropy` giving the one an auto seed draws. None stays
+// unseeded.
+pub fn resolve_seed<F>(seed: Option<Seed>, entropy: F) -> Option<u64>
+where
+    F: FnOnce() -> u64,
+{
+    match seed? {
+        Seed::Fixed(seed) => Some(seed),
+        Seed::Keyword(SeedKeyword::Auto) => Some(entropy()),
+    }
+}
+
+// Seed drawn from the system, small enough to be written back as a TOML integer.
+pub fn entropy_seed() -> u64 {
+    rand::random::<u64>() >> 1
+}
+
+// Lane field in the configured coordinate system, `directions` given row by row.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LaneConfig {
+    pub origin: [f64; 2],
+    pub cell_size: f64,
+    pub columns: usize,
+    pub directions: Vec<[f64; 2]>,
+}
+
+// Shape a group switches to at speed, see `SpeedShape`.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FastShape {
+    pub shape: ShapeKind,
+    // Units per second.
+    pub speed: f64,
+}
+
+// Flow field in the configured coordinate system, `blocked` cells as `[column, row]`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FlowFieldConfig {
+    pub origin: [f64; 2],
+    pub cell_size: f64,
+    pub columns: usize,
+    pub rows: usize,
+    pub goal: [f64; 2],
+    #[serde(default)]
+    pub blocked: Vec<[usize; 2]>,
+}
+
+// Navigation grid in the configured coordinate system, `blocked` cells as
+// `[column, row]`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct NavGridConfig {
+    pub origin: [f64; 2],
+    pub cell_size: f64,
+    pub columns: usize,
+    pub rows: usize,
+    #[serde(default)]
+    pub blocked: Vec<[usize; 2]>,
+}
+
+// Vector field in the configured coordinate system, either `vectors` given row by row
+// or read from the pixels of `image`, see `VectorField::from_image`.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VectorFieldConfig {
+    pub origin: [f64; 2],
+    pub cell_size: f64,
+    pub columns: Option<usize>,
+    #[serde(default)]
+    pub vectors: Vec<[f64; 2]>,
+    pub image: Option<String>,
+}
+
+// Reaction grid in the configured coordinate system, b seeded in discs of `seed_radius`
+// around `seeds`. Entities are squares a cell wide.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReactionConfig {
+    pub origin: [f64; 2],
+    pub cell_size: f64,
+    pub columns: usize,
+    pub rows: usize,
+    pub feed: f64,
+    pub kill: f64,
+    #[serde(default = "default_diffusion_a")]
+    pub diffusion_a: f64,
+    #[serde(default = "default_diffusion_b")]
+    pub diffusion_b: f64,
+    #[serde(default = "default_reaction_iterations")]
+    pub iterations: u32,
+    #[serde(default = "default_reaction_threshold")]
+    pub threshold: f64,
+    #[serde(default)]
+    pub seeds: Vec<[f64; 2]>,
+    pub seed_radius: f64,
+    // Drawn at the lowest and highest concentrations.
+    #[serde(default = "default_reaction_colors")]
+    pub colors: [[f32; 4]; 2],
+}
+
+fn default_diffusion_a() -> f64 {
+    1.0
+}
+
+fn default_diffusion_b() -> f64 {
+    0.5
+}
+
+fn default_reaction_iterations() -> u32 {
+    1
+}
+
+fn default_reaction_threshold() -> f64 {
+    0.25
+}
+
+fn default_reaction_colors() -> [[f32; 4]; 2] {
+    [[0.1, 0.1, 0.3, 1.0], [1.0, 0.9, 0.4, 1.0]]
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PheromoneConfig {
+    pub origin: [f64; 2],
+    pub cell_size: f64,
+    pub columns: usize,
+    pub rows: usize,
+    // Exponential fade per second.
+    pub decay: f64,
+}
+
+// A batch of entities spawned around the world center with the same shape and behavior.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GroupConfig {
+    pub count: usize,
+    pub shape: ShapeKind,
+    // Drawn as another shape when moving fast enough.
+    pub fast_shape: Option<FastShape>,
+    // Colors of the group's entities cycling through the hues, each at its own phase.
+    pub hue_cycle: Option<HueCycle>,
+    // Soft light drawn around each of the group's entities.
+    pub glow: Option<Glow>,
+    pub size: Option<f64>,
+    // Fixed color for the whole group, in place of `color_source`.
+    pub color: Option<[f32; 4]>,
+    #[serde(default)]
+    pub color_source: ColorSource,
+    pub behavior: Option<BehaviorConfig>,
+    // Stages the group's entities go through by age, replacing the behavior and size.
+    #[serde(default)]
+    pub stages: Vec<Stage>,
+    // Run the behavior only every this many ticks.
+    pub update_every: Option<u32>,
+    // What the group does at the edge of the world, the world's mode when not set.
+    pub boundary_mode: Option<BoundaryMode>,
+    // Pinned in place, see `Entity::frozen`.
+    #[serde(default)]
+    pub frozen: bool,
+    // Radians per second behaviors can turn the group's entities by, see
+    // `Entity::max_turn_rate`.
+    pub max_turn_rate: Option<f64>,
+    // Radians per second the group's entities start spinning at.
+    #[serde(default)]
+    pub angular_velocity: f64,
+    // Layer bits the group is on and collides with, see `layers_collide`.
+    #[serde(default = "default_collision_layer")]
+    pub collision_layer: u32,
+    #[serde(default = "default_collision_mask")]
+    pub collision_mask: u32,
+    // Takes part in the world's epidemic starting with this health.
+    pub health: Option<HealthState>,
+    #[serde(default)]
+    pub out_of_bounds: OutOfBounds,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+fn default_collision_layer() -> u32 {
+    DEFAULT_COLLISION_LAYER
+}
+
+fn default_collision_mask() -> u32 {
+    DEFAULT_COLLISION_MASK
+}
+
+fn default_restitution() -> f64 {
+    1.0
+}
+
+fn default_patrol_threshold() -> f64 {
+    5.0
+}
+
+fn default_mean_speed() -> f64 {
+    2.0
+}
+
+fn default_speed_stddev() -> f64 {
+    1.0
+}
+
+fn default_softening() -> f64 {
+    5.0
+}
+
+fn default_convection_range() -> f64 {
+    10.0
+}
+
+fn default_replan_every() -> u64 {
+    30
+}
+
+// Target of the behaviors taking either a point or the id of an entity to head for, the
+// world goal when given neither. `name` and `field` name them in errors.
+fn parse_target(
+    name: &str,
+    field: &str,
+    point: Option<[f64; 2]>,
+    leader_id: Option<u32>,
+) -> Result<Target, ConfigError> {
+    match (point, leader_id) {
+        (Some(_), Some(_)) => Err(ConfigError::Invalid(format!(
+            "{} takes a {} or a leader_id, not both",
+            name, field
+        ))),
+        (Some(point), None) if !(point[0].is_finite() && point[1].is_finite()) => {
+            Err(ConfigError::Invalid(format!(
+                "{} {} must be finite, got {:?}",
+                name, field, point
+            )))
+        }
+        (Some(point), None) => Ok(Target::Point(point)),
+        (None, Some(id)) => Ok(Target::Leader(id)),
+        (None, None) => Ok(Target::Goal),
+    }
+}
+
+// Built-in behaviors and their parameters, also how saves keep them, see `AI::to_tag`.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum BehaviorConfig {
+    Walker {
+        #[serde(default = "default_mean_speed")]
+        mean_speed: f64,
+        #[serde(default = "default_speed_stddev")]
+        speed_stddev: f64,
+    },
+    // Seeks `target`, or the leader, or else the world goal.
+    Seek {
+        target: Option<[f64; 2]>,
+        leader_id: Option<u32>,
+        speed: f64,
+    },
+    Containment {
+        margin: f64,
+        strength: f64,
+    },
+    // Centered on `center`, or the leader, or else the world goal.
+    Corral {
+        center: Option<[f64; 2]>,
+        leader_id: Option<u32>,
+        radius: f64,
+        margin: f64,
+        strength: f64,
+    },
+    Follow {
+        leader_id: u32,
+        distance: f64,
+        speed: f64,
+    },
+    // Members of a group take the slots in order unless `slot` is given.
+    Formation {
+        leader_id: u32,
+        shape: FormationShape,
+        #[serde(default)]
+        slot: Option<usize>,
+        spacing: f64,
+        speed: f64,
+    },
+    ScatterGather {
+        period: f64,
+        speed: f64,
+    },
+    Grow {
+        rate: f64,
+        max_size: f64,
+    },
+    Pen {
+        every: u64,
+        size: f64,
+    },
+    Diffuse {
+        drift: [f64; 2],
+        noise: f64,
+    },
+    // Heads for `target`, or the entity `leader_id`, along the world's navigation grid.
+    PathFollow {
+        target: Option<[f64; 2]>,
+        leader_id: Option<u32>,
+        speed: f64,
+        #[serde(default = "default_patrol_threshold")]
+        threshold: f64,
+        #[serde(default = "default_replan_every")]
+        replan_every: u64,
+    },
+    // `current` is the waypoint headed for, kept by saves so a patrol carries on where it
+    // was rather than starting over.
+    Patrol {
+        waypoints: Vec<[f64; 2]>,
+        speed: f64,
+        #[serde(default = "default_patrol_threshold")]
+        threshold: f64,
+        #[serde(default)]
+        current: Option<usize>,
+    },
+    FieldLines {
+        speed: f64,
+        strength: f64,
+        // Last, as TOML puts tables after plain values.
+        dipoles: Vec<Dipole>,
+    },
+    Magnetism {
+        perception_radius: f64,
+        #[serde(default)]
+        max_neighbors: Option<usize>,
+        strength: f64,
+        positive: String,
+        negative: String,
+    },
+    // `home` is kept by saves, a new territory is homed wherever its entity first runs.
+    Territory {
+        owners: Vec<String>,
+        radius: f64,
+        attraction: f64,
+        repulsion: f64,
+        #[serde(default)]
+        home: Option<[f64; 2]>,
+    },
+    Crystallize {
+        perception_radius: f64,
+        #[serde(default)]
+        max_neighbors: Option<usize>,
+        spacing: f64,
+        strength: f64,
+        #[serde(default)]
+        damping: f64,
+    },
+    Flock {
+        perception_radius: f64,
+        #[serde(default)]
+        max_neighbors: Option<usize>,
+        separation: f64,
+        alignment: f64,
+        cohesion: f64,
+        max_speed: f64,
+        #[serde(default)]
+        predator: Option<String>,
+        #[serde(default)]
+        flee: f64,
+        #[serde(default)]
+        leader_id: Option<u32>,
+        #[serde(default)]
+        leader_pull: f64,
+        #[serde(default)]
+        migration: f64,
+    },
+    Disperse {
+        probe_distance: f64,
+        radius: f64,
+        speed: f64,
+    },
+    Evacuate {
+        #[serde(default)]
+        exits: Vec<[f64; 2]>,
+        speed: f64,
+        perception_radius: f64,
+        #[serde(default)]
+        max_neighbors: Option<usize>,
+        #[serde(default)]
+        separation: f64,
+        #[serde(default)]
+        obstacle: Option<String>,
+        #[serde(default)]
+        avoidance: f64,
+        #[serde(default)]
+        congestion: f64,
+    },
+    School {
+        perception_radius: f64,
+        #[serde(default)]
+        max_neighbors: Option<usize>,
+        matching: f64,
+        max_speed: f64,
+    },
+    Rendezvous {
+        perception_radius: f64,
+        #[serde(default)]
+        max_neighbors: Option<usize>,
+        gain: f64,
+        max_speed: f64,
+    },
+    LaneFlow {
+        speed: f64,
+        strength: f64,
+    },
+    FlowFieldFollow {
+        speed: f64,
+        strength: f64,
+    },
+    VectorFieldFollow {
+        speed: f64,
+        strength: f64,
+    },
+    LevyFlight {
+        speed: f64,
+        min_step: f64,
+        alpha: f64,
+        #[serde(default)]
+        max_step: Option<f64>,
+    },
+    // Around the nearest of the world's attractors.
+    Orbit {
+        strength: f64,
+    },
+    Lissajous {
+        a: f64,
+        b: f64,
+        #[serde(default)]
+        delta: f64,
+        scale: f64,
+        center: [f64; 2],
+    },
+    NbodyGravity {
+        strength: f64,
+        #[serde(default = "default_softening")]
+        softening: f64,
+        // Barnes-Hut opening angle, exact when not set.
+        #[serde(default)]
+        theta: Option<f64>,
+    },
+    Sph {
+        smoothing_radius: f64,
+        particle_mass: f64,
+        rest_density: f64,
+        stiffness: f64,
+        #[serde(default)]
+        viscosity: f64,
+    },
+    Ant {
+        speed: f64,
+        strength: f64,
+        deposit: f64,
+    },
+    Chemotaxis {
+        speed: f64,
+        #[serde(default)]
+        descend: bool,
+    },
+    Convection {
+        #[serde(default)]
+        neutral: f64,
+        #[serde(default)]
+        lapse: f64,
+        buoyancy: f64,
+        // Evaporated then condensed size.
+        #[serde(default)]
+        sizes: Option<[f64; 2]>,
+        #[serde(default = "default_convection_range")]
+        range: f64,
+    },
+    ColorCluster {
+        perception_radius: f64,
+        #[serde(default)]
+        max_neighbors: Option<usize>,
+        threshold: f64,
+        attraction: f64,
+        repulsion: f64,
+    },
+}
+
+impl BehaviorConfig {
+    pub fn build(&self) -> Result<Box<dyn AI>, ConfigError> {
+        self.build_nth(0)
+    }
+
+    // Behavior for the `index`th member of a group, which only formations care about.
+    pub fn build_nth(&self, index: usize) -> Result<Box<dyn AI>, ConfigError> {
+        match *self {
+            BehaviorConfig::Walker {
+                mean_speed,
+                speed_stddev,
+            } => Walker::new(mean_speed, speed_stddev)
+                .map(|walker| Box::new(walker) as Box<dyn AI>)
+                .map_err(|_| {
+                    ConfigError::Invalid(format!(
+                        "walker speed_stddev must be finite and non-negative, got {}",
+                        speed_stddev
+                    ))
+                }),
+            BehaviorConfig::Seek {
+                target,
+                leader_id,
+                speed,
+            } => {
+                if !(speed.is_finite() && speed > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "seek speed must be positive, got {}",
+                        speed
+                    )));
+                }
+                let target = parse_target("seek", "target", target, leader_id)?;
+
+                Ok(Box::new(Seek { target, speed }))
+            }
+            BehaviorConfig::PathFollow {
+                target: point,
+                leader_id,
+                speed,
+                threshold,
+                replan_every,
+            } => {
+                if !(speed.is_finite() && speed > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "path_follow speed must be positive, got {}",
+                        speed
+                    )));
+                }
+                if !(threshold.is_finite() && threshold > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "path_follow threshold must be positive, got {}",
+                        threshold
+                    )));
+                }
+                if replan_every == 0 {
+                    return Err(ConfigError::Invalid(
+                        "path_follow replan_every must be at least 1".to_string(),
+                    ));
+                }
+                let target = parse_target("path_follow", "target", point, leader_id)?;
+
+                Ok(Box::new(PathFollow::new(
+                    target,
+                    speed,
+                    threshold,
+                    replan_every,
+                )))
+            }
+            BehaviorConfig::Containment { margin, strength } => {
+                if !(margin.is_finite() && margin > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "containment margin must be positive, got {}",
+                        margin
+                    )));
+                }
+
+                Ok(Box::new(Containment { margin, strength }))
+            }
+            BehaviorConfig::Corral {
+                center,
+                leader_id,
+                radius,
+                margin,
+                strength,
+            } => {
+                if !(radius.is_finite() && radius > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "corral radius must be positive, got {}",
+                        radius
+                    )));
+                }
+                if !(margin.is_finite() && margin > 0.0 && margin <= radius) {
+                    return Err(ConfigError::Invalid(format!(
+                        "corral margin must be positive and at most the radius, got {}",
+                        margin
+                    )));
+                }
+                if !strength.is_finite() {
+                    return Err(ConfigError::Invalid(format!(
+                        "corral strength must be finite, got {}",
+                        strength
+                    )));
+                }
+                let center = parse_target("corral", "center", center, leader_id)?;
+
+                Ok(Box::new(Corral {
+                    center,
+                    radius,
+                    margin,
+                    strength,
+                }))
+            }
+            BehaviorConfig::Follow {
+                leader_id,
+                distance,
+                speed,
+            } => {
+                if !(distance.is_finite() && distance >= 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "follow distance must be non-negative, got {}",
+                        distance
+                    )));
+                }
+                if !(speed.is_finite() && speed > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "follow speed must be positive, got {}",
+                        speed
+                    )));
+                }
+
+                Ok(Box::new(Follow {
+                    leader_id,
+                    distance,
+                    speed,
+                }))
+            }
+            BehaviorConfig::Formation {
+                leader_id,
+                shape,
+                slot,
+                spacing,
+                speed,
+            } => {
+                if !(spacing.is_finite() && spacing > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "formation spacing must be positive, got {}",
+                        spacing
+                    )));
+                }
+                if !(speed.is_finite() && speed > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "formation speed must be positive, got {}",
+                        speed
+                    )));
+                }
+
+                Ok(Box::new(Formation {
+                    leader_id,
+                    shape,
+                    slot: slot.unwrap_or(index),
+                    spacing,
+                    speed,
+                }))
+            }
+            BehaviorConfig::ScatterGather { period, speed } => {
+                if !(period.is_finite() && period > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "scatter_gather period must be positive, got {}",
+                        period
+                    )));
+                }
+
+                Ok(Box::new(ScatterGather { period, speed }))
+            }
+            BehaviorConfig::Grow { rate, max_size } => {
+                if !(rate.is_finite() && rate >= 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "grow rate must be non-negative, got {}",
+                        rate
+                    )));
+                }
+                if !(max_size.is_finite() && max_size > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "grow max_size must be positive, got {}",
+                        max_size
+                    )));
+                }
+
+                Ok(Box::new(Grow { rate, max_size }))
+            }
+            BehaviorConfig::Pen { every, size } => {
+                if every == 0 {
+                    return Err(ConfigError::Invalid(
+                        "pen every must be at least one tick".to_string(),
+                    ));
+                }
+                if !(size.is_finite() && size > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "pen size must be positive, got {}",
+                        size
+                    )));
+                }
+
+                Ok(Box::new(Pen { every, size }))
+            }
+            BehaviorConfig::Diffuse { drift, noise } => {
+                if !(drift[0].is_finite() && drift[1].is_finite()) {
+                    return Err(ConfigError::Invalid(format!(
+                        "diffuse drift must be finite, got {:?}",
+                        drift
+                    )));
+                }
+                if !(noise.is_finite() && noise >= 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "diffuse noise must be non-negative, got {}",
+                        noise
+                    )));
+                }
+
+                Ok(Box::new(Diffuse { drift, noise }))
+            }
+            BehaviorConfig::Patrol {
+                ref waypoints,
+                speed,
+                threshold,
+                current,
+            } => {
+                if !(speed.is_finite() && speed > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "patrol speed must be positive, got {}",
+                        speed
+                    )));
+                }
+                if !(threshold.is_finite() && threshold > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "patrol threshold must be positive, got {}",
+                        threshold
+                    )));
+                }
+
+                if let Some(current) = current.filter(|&current| current >= waypoints.len()) {
+                    return Err(ConfigError::Invalid(format!(
+                        "patrol current waypoint {} is past the {} waypoints",
+                        current,
+                        waypoints.len()
+                    )));
+                }
+
+                let patrol = Patrol::new(waypoints.clone(), speed, threshold);
+                Ok(Box::new(match current {
+                    Some(current) => patrol.with_current(current),
+                    None => patrol,
+                }))
+            }
+            BehaviorConfig::Magnetism {
+                perception_radius,
+                max_neighbors,
+                strength,
+                ref positive,
+                ref negative,
+            } => {
+                if !(perception_radius.is_finite() && perception_radius > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "magnetism perception_radius must be positive, got {}",
+                        perception_radius
+                    )));
+                }
+                if !strength.is_finite() {
+                    return Err(ConfigError::Invalid(format!(
+                        "magnetism strength must be finite, got {}",
+                        strength
+                    )));
+                }
+                if positive == negative {
+                    return Err(ConfigError::Invalid(format!(
+                        "magnetism needs two different tags, got {:?} twice",
+                        positive
+                    )));
+                }
+
+                Ok(Box::new(Magnetism {
+                    perception_radius,
+                    max_neighbors,
+                    strength,
+                    positive: positive.clone(),
+                    negative: negative.clone(),
+                }))
+            }
+            BehaviorConfig::Territory {
+                ref owners,
+                radius,
+                attraction,
+                repulsion,
+                home,
+            } => {
+                if owners.is_empty() {
+                    return Err(ConfigError::Invalid(
+                        "territory needs at least one owner tag".to_string(),
+                    ));
+                }
+                if !(radius.is_finite() && radius > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "territory radius must be positive, got {}",
+                        radius
+                    )));
+                }
+                if ![attraction, repulsion]
+                    .iter()
+                    .all(|value| value.is_finite() && *value >= 0.0)
+                {
+                    return Err(ConfigError::Invalid(format!(
+                        "territory attraction and repulsion must be non-negative, got {} and {}",
+                        attraction, repulsion
+                    )));
+                }
+
+                if let Some(home) =
+                    home.filter(|home| !(home[0].is_finite() && home[1].is_finite()))
+                {
+                    return Err(ConfigError::Invalid(format!(
+                        "territory home must be finite, got {:?}",
+                        home
+                    )));
+                }
+
+                let territory = Territory::new(owners.clone(), radius, attraction, repulsion);
+                Ok(Box::new(match home {
+                    Some(home) => territory.with_home(home),
+                    None => territory,
+                }))
+            }
+            BehaviorConfig::Crystallize {
+                perception_radius,
+                max_neighbors,
+                spacing,
+                strength,
+                damping,
+            } => {
+                if !(perception_radius.is_finite() && perception_radius > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "crystallize perception_radius must be positive, got {}",
+                        perception_radius
+                    )));
+                }
+                if !(spacing.is_finite() && spacing > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "crystallize spacing must be positive, got {}",
+                        spacing
+                    )));
+                }
+                if !(strength.is_finite() && damping.is_finite() && damping >= 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "crystallize strength must be finite and damping non-negative, got {} and {}",
+                        strength, damping
+                    )));
+                }
+
+                Ok(Box::new(Crystallize {
+                    perception_radius,
+                    max_neighbors,
+                    spacing,
+                    strength,
+                    damping,
+                }))
+            }
+            BehaviorConfig::Flock {
+                perception_radius,
+                max_neighbors,
+                separation,
+                alignment,
+                cohesion,
+                max_speed,
+                ref predator,
+                flee,
+                leader_id,
+                leader_pull,
+                migration,
+            } => {
+                if !(perception_radius.is_finite() && perception_radius > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "flock perception_radius must be positive, got {}",
+                        perception_radius
+                    )));
+                }
+                if !(max_speed.is_finite() && max_speed > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "flock max_speed must be positive, got {}",
+                        max_speed
+                    )));
+                }
+                if !(flee.is_finite() && flee >= 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "flock flee must be non-negative, got {}",
+                        flee
+                    )));
+                }
+                if !(leader_pull.is_finite() && leader_pull >= 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "flock leader_pull must be non-negative, got {}",
+                        leader_pull
+                    )));
+                }
+                if !(migration.is_finite() && migration >= 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "flock migration must be non-negative, got {}",
+                        migration
+                    )));
+                }
+
+                Ok(Box::new(Flock {
+                    perception_radius,
+                    max_neighbors,
+                    separation,
+                    alignment,
+                    cohesion,
+                    max_speed,
+                    predator: predator.clone(),
+                    flee,
+                    leader: leader_id,
+                    leader_pull,
+                    migration,
+                }))
+            }
+            BehaviorConfig::Disperse {
+                probe_distance,
+                radius,
+                speed,
+            } => {
+                if ![probe_distance, radius, speed]
+                    .iter()
+                    .all(|value| value.is_finite() && *value > 0.0)
+                {
+                    return Err(ConfigError::Invalid(format!(
+                        "disperse probe_distance, radius and speed must be positive, got {}, {} and {}",
+                        probe_distance, radius, speed
+                    )));
+                }
+
+                Ok(Box::new(Disperse {
+                    probe_distance,
+                    radius,
+                    speed,
+                }))
+            }
+            BehaviorConfig::Evacuate {
+                ref exits,
+                speed,
+                perception_radius,
+                max_neighbors,
+                separation,
+                ref obstacle,
+                avoidance,
+                congestion,
+            } => {
+                if let Some(exit) = exits
+                    .iter()
+                    .find(|exit| !(exit[0].is_finite() && exit[1].is_finite()))
+                {
+                    return Err(ConfigError::Invalid(format!(
+                        "evacuate exits must be finite, got {:?}",
+                        exit
+                    )));
+                }
+                if !(speed.is_finite() && speed > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "evacuate speed must be positive, got {}",
+                        speed
+                    )));
+                }
+                if !(perception_radius.is_finite() && perception_radius > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "evacuate perception_radius must be positive, got {}",
+                        perception_radius
+                    )));
+                }
+                if ![separation, avoidance, congestion]
+                    .iter()
+                    .all(|weight| weight.is_finite() && *weight >= 0.0)
+                {
+                    return Err(ConfigError::Invalid(format!(
+                        "evacuate separation, avoidance and congestion must be non-negative, got {}, {} and {}",
+                        separation, avoidance, congestion
+                    )));
+                }
+
+                Ok(Box::new(Evacuate {
+                    exits: exits.clone(),
+                    speed,
+                    perception_radius,
+                    max_neighbors,
+                    separation,
+                    obstacle: obstacle.clone(),
+                    avoidance,
+                    congestion,
+                }))
+            }
+            BehaviorConfig::School {
+                perception_radius,
+                max_neighbors,
+                matching,
+                max_speed,
+            } => {
+                if !(perception_radius.is_finite() && perception_radius > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "school perception_radius must be positive, got {}",
+                        perception_radius
+                    )));
+                }
+                if !(matching.is_finite() && matching >= 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "school matching must be non-negative, got {}",
+                        matching
+                    )));
+                }
+                if !(max_speed.is_finite() && max_speed > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "school max_speed must be positive, got {}",
+                        max_speed
+                    )));
+                }
+
+                Ok(Box::new(School {
+                    perception_radius,
+                    max_neighbors,
+                    matching,
+                    max_speed,
+                }))
+            }
+            BehaviorConfig::Rendezvous {
+                perception_radius,
+                max_neighbors,
+                gain,
+                max_speed,
+            } => {
+                if !(perception_radius.is_finite() && perception_radius > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "rendezvous perception_radius must be positive, got {}",
+                        perception_radius
+                    )));
+                }
+                if !(gain.is_finite() && gain > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "rendezvous gain must be positive, got {}",
+                        gain
+                    )));
+                }
+                if !(max_speed.is_finite() && max_speed > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "rendezvous max_speed must be positive, got {}",
+                        max_speed
+                    )));
+                }
+
+                Ok(Box::new(Rendezvous {
+                    perception_radius,
+                    max_neighbors,
+                    gain,
+                    max_speed,
+                }))
+            }
+            BehaviorConfig::LaneFlow { speed, strength } => {
+                if !(speed.is_finite() && speed > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "lane_flow speed must be positive, got {}",
+                        speed
+                    )));
+                }
+                if !(strength.is_finite() && strength > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "lane_flow strength must be positive, got {}",
+                        strength
+                    )));
+                }
+
+                Ok(Box::new(LaneFlow { speed, strength }))
+            }
+            BehaviorConfig::LevyFlight {
+                speed,
+                min_step,
+                alpha,
+                max_step,
+            } => LevyFlight::new(speed, min_step, alpha, max_step)
+                .map(|flight| Box::new(flight) as Box<dyn AI>)
+                .map_err(|error| ConfigError::Invalid(error.to_string())),
+            BehaviorConfig::Lissajous {
+                a,
+                b,
+                delta,
+                scale,
+                center,
+            } => {
+                if ![a, b, delta, center[0], center[1]]
+                    .iter()
+                    .all(|value| value.is_finite())
+                {
+                    return Err(ConfigError::Invalid(format!(
+                        "lissajous a, b, delta and center must be finite, got {}, {}, {} and {:?}",
+                        a, b, delta, center
+                    )));
+                }
+                if !(scale.is_finite() && scale > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "lissajous scale must be positive, got {}",
+                        scale
+                    )));
+                }
+
+                Ok(Box::new(Lissajous {
+                    a,
+                    b,
+                    delta,
+                    scale,
+                    center,
+                }))
+            }
+            BehaviorConfig::Orbit { strength } => {
+                if !(strength.is_finite() && strength > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "orbit strength must be positive, got {}",
+                        strength
+                    )));
+                }
+
+                Ok(Box::new(Orbit { strength }))
+            }
+            BehaviorConfig::NbodyGravity {
+                strength,
+                softening,
+                theta,
+            } => {
+                if let Some(theta) = theta.filter(|theta| !(theta.is_finite() && *theta >= 0.0)) {
+                    return Err(ConfigError::Invalid(format!(
+                        "nbody_gravity theta must be non-negative, got {}",
+                        theta
+                    )));
+                }
+                if !strength.is_finite() {
+                    return Err(ConfigError::Invalid(format!(
+                        "nbody_gravity strength must be finite, got {}",
+                        strength
+                    )));
+                }
+                if !(softening.is_finite() && softening > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "nbody_gravity softening must be positive, got {}",
+                        softening
+                    )));
+                }
+
+                Ok(Box::new(NBodyGravity {
+                    strength,
+                    softening,
+                    theta,
+                }))
+            }
+            BehaviorConfig::Sph {
+                smoothing_radius,
+                particle_mass,
+                rest_density,
+                stiffness,
+                viscosity,
+            } => {
+                if ![smoothing_radius, particle_mass]
+                    .iter()
+                    .all(|value| value.is_finite() && *value > 0.0)
+                {
+                    return Err(ConfigError::Invalid(format!(
+                        "sph smoothing_radius and particle_mass must be positive, got {} and {}",
+                        smoothing_radius, particle_mass
+                    )));
+                }
+                if ![rest_density, stiffness, viscosity]
+                    .iter()
+                    .all(|value| value.is_finite() && *value >= 0.0)
+                {
+                    return Err(ConfigError::Invalid(format!(
+                        "sph rest_density, stiffness and viscosity must be non-negative, got {}, {} and {}",
+                        rest_density, stiffness, viscosity
+                    )));
+                }
+
+                Ok(Box::new(Sph {
+                    smoothing_radius,
+                    particle_mass,
+                    rest_density,
+                    stiffness,
+                    viscosity,
+                }))
+            }
+            BehaviorConfig::Ant {
+                speed,
+                strength,
+                deposit,
+            } => {
+                if !(speed.is_finite() && speed > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "ant speed must be positive, got {}",
+                        speed
+                    )));
+                }
+                if !(strength.is_finite() && strength > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "ant strength must be positive, got {}",
+                        strength
+                    )));
+                }
+                if !(deposit.is_finite() && deposit >= 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "ant deposit must be non-negative, got {}",
+                        deposit
+                    )));
+                }
+
+                Ok(Box::new(Ant {
+                    speed,
+                    strength,
+                    deposit,
+                }))
+            }
+            BehaviorConfig::FieldLines {
+                ref dipoles,
+                speed,
+                strength,
+            } => {
+                if dipoles.is_empty() {
+                    return Err(ConfigError::Invalid(
+                        "field_lines needs at least one dipole".to_string(),
+                    ));
+                }
+                if !(speed.is_finite() && speed > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "field_lines speed must be positive, got {}",
+                        speed
+                    )));
+                }
+                if !(strength.is_finite() && strength > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "field_lines strength must be positive, got {}",
+                        strength
+                    )));
+                }
+
+                Ok(Box::new(FieldLines {
+                    dipoles: dipoles.clone(),
+                    speed,
+                    strength,
+                }))
+            }
+            BehaviorConfig::FlowFieldFollow { speed, strength } => {
+                if !(speed.is_finite() && speed > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "flow_field_follow speed must be positive, got {}",
+                        speed
+                    )));
+                }
+                if !(strength.is_finite() && strength > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "flow_field_follow strength must be positive, got {}",
+                        strength
+                    )));
+                }
+
+                Ok(Box::new(FlowFieldFollow { speed, strength }))
+            }
+            BehaviorConfig::VectorFieldFollow { speed, strength } => {
+                if !(speed.is_finite() && speed > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "vector_field_follow speed must be positive, got {}",
+                        speed
+                    )));
+                }
+                if !(strength.is_finite() && strength > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "vector_field_follow strength must be positive, got {}",
+                        strength
+                    )));
+                }
+
+                Ok(Box::new(VectorFieldFollow { speed, strength }))
+            }
+            BehaviorConfig::Chemotaxis { speed, descend } => {
+                if !(speed.is_finite() && speed > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "chemotaxis speed must be positive, got {}",
+                        speed
+                    )));
+                }
+
+                Ok(Box::new(Chemotaxis { speed, descend }))
+            }
+            BehaviorConfig::Convection {
+                neutral,
+                lapse,
+                buoyancy,
+                sizes,
+                range,
+            } => {
+                if !(neutral.is_finite() && lapse.is_finite() && buoyancy.is_finite()) {
+                    return Err(ConfigError::Invalid(format!(
+                        "convection neutral, lapse and buoyancy must be finite, got {}, {} and {}",
+                        neutral, lapse, buoyancy
+                    )));
+                }
+                if !(range.is_finite() && range > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "convection range must be positive, got {}",
+                        range
+                    )));
+                }
+                if let Some(sizes) = sizes {
+                    if !sizes.iter().all(|size| size.is_finite() && *size > 0.0) {
+                        return Err(ConfigError::Invalid(format!(
+                            "convection sizes must be positive, got {:?}",
+                            sizes
+                        )));
+                    }
+                }
+
+                Ok(Box::new(Convection {
+                    neutral,
+                    lapse,
+                    buoyancy,
+                    sizes,
+                    range,
+                }))
+            }
+            BehaviorConfig::ColorCluster {
+                perception_radius,
+                max_neighbors,
+                threshold,
+                attraction,
+                repulsion,
+            } => {
+                if !(perception_radius.is_finite() && perception_radius > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "color_cluster perception_radius must be positive, got {}",
+                        perception_radius
+                    )));
+                }
+                if !(threshold > 0.0 && threshold <= 1.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "color_cluster threshold must be in (0, 1], got {}",
+                        threshold
+                    )));
+                }
+                if !(attraction.is_finite() && repulsion.is_finite()) {
+                    return Err(ConfigError::Invalid(format!(
+                        "color_cluster attraction and repulsion must be finite, got {} and {}",
+                        attraction, repulsion
+                    )));
+                }
+
+                Ok(Box::new(ColorCluster {
+                    perception_radius,
+                    max_neighbors,
+                    threshold,
+                    attraction,
+                    repulsion,
+                }))
+            }
+        }
+    }
+}
+
+impl Config {
+    pub fn load(path: &str) -> Result<Config, ConfigError> {
+        let text = fs::read_to_string(path)
+            .map_err(|error| ConfigError::Io(format!("{}: {}", path, error)))?;
+
+        Config::parse(&text)
+    }
+
+    pub fn parse(text: &str) -> Result<Config, ConfigError> {
+        toml::from_str(text).map_err(|error| ConfigError::Parse(error.to_string()))
+    }
+
+    // Draws the seed of an auto seeded scene for good, so `build_world` and anything
+    // reporting it agree, and returns the seed the run uses.
+    pub fn fix_seed(&mut self) -> Option<u64> {
+        let seed = resolve_seed(self.seed, entropy_seed);
+        self.seed = seed.map(Seed::Fixed);
+        seed
+    }
+
+    pub fn updates_per_second(&self) -> u64 {
+        clamp_ups(self.ups)
+    }
+
+    pub fn build_world(&self) -> Result<World, NatureError> {
+        if let Some(segments) = self.circle_segments.filter(|&segments| segments < 3) {
+            return Err(ConfigError::Invalid(format!(
+                "circle_segments must be at least 3, got {}",
+                segments
+            ))
+            .into());
+        }
+
+        if let Some(downsample) = self
+            .downsample
+            .filter(|downsample| downsample.threshold == 0 || downsample.stride == Some(0))
+        {
+            return Err(ConfigError::Invalid(format!(
+                "downsample needs a positive threshold and stride, got {:?}",
+                downsample
+            ))
+            .into());
+        }
+
+        if let Some(shadow) = self
+            .shadow
+            .filter(|shadow| !shadow.offset.iter().all(|value| value.is_finite()))
+        {
+            return Err(ConfigError::Invalid(format!(
+                "shadow offset must be finite, got {:?}",
+                shadow.offset
+            ))
+            .into());
+        }
+
+        if let Some(connections) = self.connections.filter(|connections| {
+            connections.neighbors == 0
+                || !(connections.radius.is_finite() && connections.radius > 0.0)
+        }) {
+            return Err(ConfigError::Invalid(format!(
+                "connections need at least one neighbor and a positive radius, got {:?}",
+                connections
+            ))
+            .into());
+        }
+
+        let factory = ShapeFactory {
+            circle_segments: self.circle_segments,
+            ..ShapeFactory::default()
+        };
+        let mut entities = Vec::new();
+        // Spawning draws from a stream of its own, before the first tick.
+        let seed = resolve_seed(self.seed, entropy_seed);
+        let mut rng = step_rng(seed, 0, u64::MAX - 1);
+
+        for group in self.groups.iter() {
+            let size = group
+                .size
+                .unwrap_or_else(|| factory.defaults(group.shape).size);
+
+            if !(size.is_finite() && size > 0.0) {
+                return Err(ConfigError::Invalid(format!(
+                    "group size must be positive, got {}",
+                    size
+                ))
+                .into());
+            }
+
+            if let ColorSource::Random { spread, .. } = group.color_source {
+                if !spread.is_finite() {
+                    return Err(ConfigError::Invalid(format!(
+                        "color spread must be finite, got {}",
+                        spread
+                    ))
+                    .into());
+                }
+            }
+
+            if let Some(fast) = group.fast_shape {
+                if !(fast.speed.is_finite() && fast.speed >= 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "fast_shape speed must be non-negative, got {}",
+                        fast.speed
+                    ))
+                    .into());
+                }
+            }
+
+            let update_every = group.update_every.unwrap_or(1);
+            if update_every == 0 {
+                return Err(ConfigError::Invalid(
+                    "group update_every must be at least 1".to_string(),
+                )
+                .into());
+            }
+
+            for stage in group.stages.iter() {
+                if !(stage.age.is_finite() && stage.age >= 0.0)
+                    || stage
+                        .size
+                        .is_some_and(|size| !(size.is_finite() && size > 0.0))
+                {
+                    return Err(ConfigError::Invalid(format!(
+                        "group stages need a non-negative age and a positive size, got {:?}",
+                        stage
+                    ))
+                    .into());
+                }
+                if let Some(behavior) = stage.behavior.as_ref() {
+                    behavior.build()?;
+                }
+            }
+
+            if let Some(cycle) = group.hue_cycle {
+                if !(cycle.period.is_finite() && cycle.period > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "group hue_cycle period must be positive, got {}",
+                        cycle.period
+                    ))
+                    .into());
+                }
+            }
+
+            if let Some(glow) = group.glow {
+                if !(glow.radius.is_finite() && glow.radius > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "group glow radius must be positive, got {}",
+                        glow.radius
+                    ))
+                    .into());
+                }
+            }
+
+            if let Some(rate) = group.max_turn_rate {
+                if !(rate.is_finite() && rate > 0.0) {
+                    return Err(ConfigError::Invalid(format!(
+                        "group max_turn_rate must be positive, got {}",
+                        rate
+                    ))
+                    .into());
+                }
+            }
+
+            if !group.angular_velocity.is_finite() {
+                return Err(ConfigError::Invalid(format!(
+                    "group angular_velocity must be finite, got {}",
+                    group.angular_velocity
+                ))
+                .into());
+            }
+
+            let positions = gaussian_positions(
+                group.count,
+                self.width,
+                self.height,
+                group.out_of_bounds,
+                &mut rng,
+            )?;
+
+            for (index, [x, y]) in positions.into_iter().enumerate() {
+                let color = group.color.unwrap_or_else(|| {
+                    group
+                        .color_source
+                        .sample([x, y], self.width, self.height, &mut rng)
+                });
+                let mut physics = Physics::new(x, y, size)?;
+                physics.angular_velocity = group.angular_velocity;
+                let renderer = Renderer::Owned(match group.fast_shape {
+                    Some(fast) => {
+                        factory.build_by_speed(group.shape, fast.shape, fast.speed, color)
+                    }
+                    None => factory.build_colored(group.shape, color),
+                });
+                let mut entity = Entity::new(entities.len() as u32, physics, renderer);
+
+                entity.tags = group.tags.clone();
+                entity.update_every = update_every;
+                entity.boundary_mode = group.boundary_mode;
+                entity.frozen = group.frozen;
+                entity.max_turn_rate = group.max_turn_rate;
+                entity.glow = group.glow;
+                entity.hue_cycle = group.hue_cycle;
+                if !group.stages.is_empty() {
+                    entity.lifecycle = Some(Lifecycle::new(group.stages.clone()).with_slot(index));
+                }
+                entity.collision_layer = group.collision_layer;
+                entity.collision_mask = group.collision_mask;
+                entity.health = group.health.map(HealthState::health);
+
+                if let Some(behavior) = &group.behavior {
+                    entity = entity.with_behavior(behavior.build_nth(index)?);
+                }
+
+                entities.push(entity);
+            }
+        }
+
+        let mut world = World::new(entities, self.width as f64, self.height as f64)?;
+        if let Some(kind) = self.spatial_index {
+            match kind {
+                SpatialIndexKind::Grid { cell_size }
+                    if !(cell_size.is_finite() && cell_size > 0.0) =>
+                {
+                    return Err(ConfigError::Invalid(format!(
+                        "spatial_index cell_size must be positive, got {}",
+                        cell_size
+                    ))
+                    .into());
+                }
+                SpatialIndexKind::QuadTree { capacity: 0 } => {
+                    return Err(ConfigError::Invalid(
+                        "spatial_index capacity must be at least 1".to_string(),
+                    )
+                    .into());
+                }
+                _ => world.spatial_index = kind,
+            }
+        }
+        world.max_entities = self.max_entities;
+        if let Some(max_age) = self
+            .max_age
+            .filter(|max_age| !(max_age.is_finite() && *max_age > 0.0))
+        {
+            return Err(
+                ConfigError::Invalid(format!("max_age must be positive, got {}", max_age)).into(),
+            );
+        }
+        world.max_age = self.max_age;
+        world.spawn_ease = self.spawn_ease;
+        if !(0.0..=1.0).contains(&self.restitution) {
+            return Err(ConfigError::Invalid(format!(
+                "restitution must be between 0 and 1, got {}",
+                self.restitution
+            ))
+            .into());
+        }
+
+        if let Some(sleep) = self.sleep {
+            if !(sleep.speed.is_finite() && sleep.speed >= 0.0 && sleep.ticks > 0) {
+                return Err(ConfigError::Invalid(format!(
+                    "sleep needs a non-negative speed and at least one tick, got {:?}",
+                    sleep
+                ))
+                .into());
+            }
+        }
+
+        if !(self.drag.is_finite() && self.drag >= 0.0) {
+            return Err(ConfigError::Invalid(format!(
+                "drag must be non-negative, got {}",
+                self.drag
+            ))
+            .into());
+        }
+
+        if let Some(flash) = self.flash.as_ref() {
+            if !(flash.duration.is_finite() && flash.duration > 0.0) {
+                return Err(ConfigError::Invalid(format!(
+                    "flash duration must be positive, got {}",
+                    flash.duration
+                ))
+                .into());
+            }
+        }
+
+        if let Some(budget) = self.tick_budget {
+            if !(budget.is_finite() && budget > 0.0) {
+                return Err(ConfigError::Invalid(format!(
+                    "tick_budget must be positive, got {}",
+                    budget
+                ))
+                .into());
+            }
+
+            world.budget = Some(TickBudget::new(Duration::from_secs_f64(budget / 1000.0)));
+        }
+
+        world.drag = self.drag;
+        world.flash = self.flash.clone();
+        if let Some(daylight) = self.daylight {
+            if !(daylight.period.is_finite() && daylight.period > 0.0) {
+                return Err(ConfigError::Invalid(format!(
+                    "daylight period must be positive, got {}",
+                    daylight.period
+                ))
+                .into());
+            }
+        }
+        world.daylight = self.daylight;
+        if let Some(epidemic) = self.epidemic {
+            if !(epidemic.radius.is_finite()
+                && epidemic.radius > 0.0
+                && (0.0..=1.0).contains(&epidemic.chance)
+                && epidemic.recovery.is_finite()
+                && epidemic.recovery >= 0.0)
+            {
+                return Err(ConfigError::Invalid(format!(
+                    "epidemic needs a positive radius, a chance between 0 and 1 and a non-negative recovery, got {:?}",
+                    epidemic
+                ))
+                .into());
+            }
+        }
+        world.epidemic = self.epidemic;
+        if let Some(linking) = self.linking {
+            if !(linking.radius.is_finite()
+                && linking.radius > 0.0
+                && linking.max_links > 0
+                && linking.stiffness.is_finite()
+                && linking.stiffness >= 0.0
+                && linking.break_length.is_finite()
+                && linking.break_length >= linking.radius)
+            {
+                return Err(ConfigError::Invalid(format!(
+                    "linking needs a positive radius and max_links, a non-negative stiffness and a break_length of at least the radius, got {:?}",
+                    linking
+                ))
+                .into());
+            }
+        }
+        world.linking = self.linking;
+        if let Some(regions) = self.regions.as_ref() {
+            let valid = |region: &Region| match *region {
+                Region::Rect { min, max } => min[0] < max[0] && min[1] < max[1],
+                Region::Circle { radius, .. } => radius.is_finite() && radius > 0.0,
+            };
+            if regions.regions.is_empty() || !regions.regions.iter().all(valid) {
+                return Err(ConfigError::Invalid(format!(
+                    "regions need at least one region, each of positive size, got {:?}",
+                    regions.regions
+                ))
+                .into());
+            }
+        }
+        world.regions = self.regions.clone();
+        world.seed = seed;
+        world.deterministic_order = self.deterministic_order;
+        world.sleep = self.sleep;
+        world.collision_response = self.collisions;
+        world.spawn_overlap = self.spawn_overlap;
+        world.restitution = self.restitution;
+        world.integrator = self.integrator;
+        world.max_substeps = match self.max_substeps {
+            Some(0) => {
+                return Err(
+                    ConfigError::Invalid("max_substeps must be at least 1".to_string()).into(),
+                )
+            }
+            Some(max) => max,
+            None => 1,
+        };
+        // Positions are generated top-left, convert them once the world exists.
+        world.set_coordinates(self.coordinates);
+
+        for source in self.field.iter() {
+            if !(source.strength.is_finite() && source.radius.is_finite() && source.radius > 0.0) {
+                return Err(ConfigError::Invalid(format!(
+                    "field sources need a finite strength and a positive radius, got {:?}",
+                    source
+                ))
+                .into());
+            }
+        }
+
+        if let Some(wall) = self.walls.iter().find(|wall| {
+            !wall
+                .from
+                .iter()
+                .chain(wall.to.iter())
+                .all(|v| v.is_finite())
+        }) {
+            return Err(
+                ConfigError::Invalid(format!("wall ends must be finite, got {:?}", wall)).into(),
+            );
+        }
+        world.walls = self.walls.clone();
+
+        if let Some(attractor) = self.attractors.iter().find(|attractor| {
+            !(attractor.position.iter().all(|v| v.is_finite()) && attractor.strength.is_finite())
+        }) {
+            return Err(ConfigError::Invalid(format!(
+                "attractors need a finite position and strength, got {:?}",
+                attractor
+            ))
+            .into());
+        }
+        world.attractors = self.attractors.clone();
+
+        if let Some(point) = self
+            .migration_path
+            .iter()
+            .find(|point| !point.iter().all(|v| v.is_finite()))
+        {
+            return Err(ConfigError::Invalid(format!(
+                "migration_path points must be finite, got {:?}",
+                point
+            ))
+            .into());
+        }
+        world.migration_path = self.migration_path.clone();
+
+        if let Some(pair) = self.portals.iter().find(|pair| {
+            ![pair.a, pair.b].iter().all(|portal| {
+                portal.center.iter().all(|v| v.is_finite())
+                    && portal.angle.is_finite()
+                    && portal.radius.is_finite()
+                    && portal.radius > 0.0
+            })
+        }) {
+            return Err(ConfigError::Invalid(format!(
+                "portals need finite centers and angles and a positive radius, got {:?}",
+                pair
+            ))
+            .into());
+        }
+        world.portals = self.portals.clone();
+
+        if !self.field.is_empty() {
+            world.field = Some(ScalarField {
+                sources: self.field.clone(),
+            });
+        }
+
+        if let Some(lanes) = self.lanes.as_ref() {
+            world.lanes = Some(LaneField::new(
+                lanes.origin,
+                lanes.cell_size,
+                lanes.columns,
+                lanes.directions.clone(),
+            )?);
+        }
+
+        if let Some(grid) = self.pheromones.as_ref() {
+            world.pheromones = Some(PheromoneGrid::new(
+                grid.origin,
+                grid.cell_size,
+                grid.columns,
+                grid.rows,
+                grid.decay,
+            )?);
+        }
+
+        if let Some(flow) = self.flow_field.as_ref() {
+            world.flow_field = Some(FlowField::new(
+                flow.origin,
+                flow.cell_size,
+                flow.columns,
+                flow.rows,
+                flow.goal,
+                &flow.blocked,
+            )?);
+        }
+
+        if let Some(grid) = self.nav_grid.as_ref() {
+            world.nav_grid = Some(NavGrid::new(
+                grid.origin,
+                grid.cell_size,
+                grid.columns,
+                grid.rows,
+                &grid.blocked,
+            )?);
+        }
+
+        if let Some(config) = self.reaction.as_ref() {
+            if !(0.0..=1.0).contains(&config.threshold) {
+                return Err(ConfigError::Invalid(format!(
+                    "reaction threshold must be between 0 and 1, got {}",
+                    config.threshold
+                ))
+                .into());
+            }
+
+            let model = GrayScott {
+                feed: config.feed,
+                kill: config.kill,
+                diffusion_a: config.diffusion_a,
+                diffusion_b: config.diffusion_b,
+            };
+            let mut grid = ReactionGrid::new(
+                config.origin,
+                config.cell_size,
+                config.columns,
+                config.rows,
+                model,
+            )?;
+            for &seed in config.seeds.iter() {
+                grid.seed(seed, config.seed_radius);
+            }
+
+            let kind = world.add_kind(EntityKind {
+                renderer: factory.build_colored(ShapeKind::Square, config.colors[1]),
+                size: config.cell_size,
+            });
+            world.reaction = Some(Reaction {
+                grid,
+                kind,
+                threshold: config.threshold,
+                iterations: config.iterations,
+                colors: (config.colors[0], config.colors[1]),
+            });
+        }
+
+        if let Some(field) = self.vector_field.as_ref() {
+            world.vector_field = Some(match (field.image.as_ref(), field.columns) {
+                (Some(path), None) if field.vectors.is_empty() => {
+                    VectorField::from_image(path, field.origin, field.cell_size)?
+                }
+                (None, Some(columns)) => VectorField::new(
+                    field.origin,
+                    field.cell_size,
+                    columns,
+                    field.vectors.clone(),
+                )?,
+                _ => {
+                    return Err(ConfigError::Invalid(
+                        "vector_field needs either an image or columns of vectors".to_string(),
+                    )
+                    .into())
+                }
+            });
+        }
+
+        Ok(world)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seek_group_builds_the_configured_target() {
+        let config = Config::parse(
+            r#"
+            seed = 1
+
+            [[groups]]
+            count = 2
+            shape = "circle"
+            behavior = { type = "seek", target = [10.0, 20.0], speed = 3.0 }
+            "#,
+        )
+        .unwrap();
+        let world = config.build_world().unwrap();
+
+        assert_eq!(world.entities.len(), 2);
+        for entity in world.entities.iter() {
+            let behavior = entity.behavior.as_ref().unwrap();
+            assert_eq!(
+                behavior.to_tag(),
+                Some(BehaviorConfig::Seek {
+                    target: Some([10.0, 20.0]),
+                    leader_id: None,
+                    speed: 3.0,
+                })
+            );
+        }
+
+        let error = Config::parse(
+            r#"
+            [[groups]]
+            count = 1
+            shape = "circle"
+            behavior = { type = "seek", target = [10.0, 20.0], leader_id = 0, speed = 3.0 }
+            "#,
+        )
+        .unwrap()
+        .build_world();
+        assert!(error.is_err());
+    }
+
+    #[test]
+    fn spatial_index_is_set_from_the_config() {
+        let world = Config::parse(r#"spatial_index = { type = "quad_tree", capacity = 4 }"#)
+            .unwrap()
+            .build_world()
+            .unwrap();
+        assert_eq!(
+            world.spatial_index,
+            SpatialIndexKind::QuadTree { capacity: 4 }
+        );
+
+        let default = Config::parse("").unwrap().build_world().unwrap();
+        assert_eq!(
+            default.spatial_index,
+            SpatialIndexKind::Grid { cell_size: 50.0 }
+        );
+
+        let error = Config::parse(r#"spatial_index = { type = "grid", cell_size = 0.0 }"#)
+            .unwrap()
+            .build_world();
+        assert!(error.is_err());
+    }
+
+    #[test]
+    fn updates_per_second_are_parsed_and_clamped() {
+        let ups = |text: &str| Config::parse(text).unwrap().updates_per_second();
+
+        assert_eq!(ups(""), DEFAULT_UPS);
+        assert_eq!(ups("ups = 60"), 60);
+        assert_eq!(ups("ups = 0"), 1);
+        assert_eq!(ups("ups = -5"), 1);
+        assert_eq!(ups("ups = 5000"), MAX_UPS);
+        assert!(Config::parse("ups = \"fast\"").is_err());
+    }
+
+    #[test]
+    fn opengl_versions_are_parsed() {
+        assert!(matches!(parse_opengl("2.1"), Ok(OpenGL::V2_1)));
+        assert!(matches!(parse_opengl(" 3.2 "), Ok(OpenGL::V3_2)));
+        assert!(matches!(parse_opengl("9.9"), Err(ConfigError::Invalid(_))));
+    }
+
+    #[test]
+    fn window_settings_apply_the_options() {
+        let options = WindowOptions {
+            vsync: true,
+            fullscreen: false,
+        };
+        let settings = window_settings("nature", [640, 480], OpenGL::V3_2, options);
+
+        assert!(settings.get_vsync());
+        assert!(!settings.get_fullscreen());
+        assert_eq!(settings.get_title(), "nature");
+        assert_eq!(settings.get_size().width, 640.0);
+
+        let options = WindowOptions {
+            vsync: false,
+            fullscreen: true,
+        };
+        let settings = window_settings("nature", [640, 480], OpenGL::V3_2, options);
+
+        assert!(!settings.get_vsync());
+        assert!(settings.get_fullscreen());
+    }
+
+    #[test]
+    fn resolve_seed_draws_only_for_auto() {
+        let entropy = || 42;
+
+        assert_eq!(resolve_seed(Some(Seed::Fixed(7)), entropy), Some(7));
+        assert_eq!(
+            resolve_seed(Some(Seed::Keyword(SeedKeyword::Auto)), entropy),
+            Some(42)
+        );
+        assert_eq!(resolve_seed(None, || panic!("drew a seed")), None);
+    }
+
+    #[test]
+    fn windows_that_cant_open_are_descriptive_errors() {
+        let message = |result: Result<(), NatureError>| match result {
+            Err(NatureError::Window(message)) => message,
+            other => panic!("expected a window error, got {:?}", other),
+        };
+
+        assert!(check_window([640, 480], true).is_ok());
+        assert_eq!(
+            message(check_window([0, 480], true)),
+            "the window needs a positive size, got 0x480"
+        );
+        assert!(message(check_window([640, 0], false)).contains("positive size"));
+        assert!(message(check_window([640, 480], false)).contains("--headless"));
+
+        // Caught before the backend is ever asked for a window.
+        let window = build_window("nature", [0, 0], DEFAULT_OPENGL, WindowOptions::default());
+        assert!(matches!(window, Err(NatureError::Window(_))));
+    }
+}
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+// Where world coordinates put their origin and which way y points.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CoordinateSystem {
+    // Piston's convention, origin at the top-left corner and y growing downwards.
+    #[default]
+    TopLeft,
+    // Origin at the center of the world and y growing upwards.
+    Centered,
+}
+
+impl CoordinateSystem {
+    pub fn y_up(&self) -> bool {
+        *self == CoordinateSystem::Centered
+    }
+
+    // Corners of a world of the given size, minimum first.
+    pub fn bounds(&self, width: f64, height: f64) -> ([f64; 2], [f64; 2]) {
+        match self {
+            CoordinateSystem::TopLeft => ([0.0, 0.0], [width, height]),
+            CoordinateSystem::Centered => {
+                ([-width / 2.0, -height / 2.0], [width / 2.0, height / 2.0])
+            }
+        }
+    }
+
+    pub fn center(&self, width: f64, height: f64) -> [f64; 2] {
+        match self {
+            CoordinateSystem::TopLeft => [width / 2.0, height / 2.0],
+            CoordinateSystem::Centered => [0.0, 0.0],
+        }
+    }
+
+    // Converts a point of this system to the top-left system of the same world.
+    pub fn to_top_left(&self, point: [f64; 2], width: f64, height: f64) -> [f64; 2] {
+        match self {
+            CoordinateSystem::TopLeft => point,
+            CoordinateSystem::Centered => [point[0] + width / 2.0, height / 2.0 - point[1]],
+        }
+    }
+
+    pub fn from_top_left(&self, point: [f64; 2], width: f64, height: f64) -> [f64; 2] {
+        match self {
+            CoordinateSystem::TopLeft => point,
+            CoordinateSystem::Centered => [point[0] - width / 2.0, height / 2.0 - point[1]],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions_round_trip() {
+        let centered = CoordinateSystem::Centered;
+        assert_eq!(
+            centered.to_top_left([0.0, 0.0], 200.0, 100.0),
+            [100.0, 50.0]
+        );
+        assert_eq!(
+            centered.to_top_left([-100.0, 50.0], 200.0, 100.0),
+            [0.0, 0.0]
+        );
+
+        for system in [CoordinateSystem::TopLeft, CoordinateSystem::Centered] {
+            for point in [[0.0, 0.0], [37.5, -12.0], [-100.0, 50.0]] {
+                let top_left = system.to_top_left(point, 200.0, 100.0);
+                assert_eq!(system.from_top_left(top_left, 200.0, 100.0), point);
+            }
+        }
+    }
+}
@@ -0,0 +1,29 @@
+// Fast start and gentle landing, 0 at t = 0 and 1 at t = 1, t clamped to that range.
+pub fn ease_out_cubic(t: f64) -> f64 {
+    let t = t.clamp(0.0, 1.0);
+
+    1.0 - (1.0 - t).powi(3)
+}
+
+// Render size multiplier of an entity `age` seconds old that grows in over `duration`.
+pub fn spawn_scale(age: f64, duration: f64) -> f64 {
+    if duration <= 0.0 {
+        return 1.0;
+    }
+
+    ease_out_cubic(age / duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spawn_scale_eases_from_zero_to_one() {
+        assert_eq!(spawn_scale(0.0, 2.0), 0.0);
+        assert!(spawn_scale(1.0, 2.0) > 0.5);
+        assert!((spawn_scale(2.0, 2.0) - 1.0).abs() < 1e-12);
+        assert_eq!(spawn_scale(5.0, 2.0), 1.0);
+        assert_eq!(spawn_scale(0.0, 0.0), 1.0);
+    }
+}
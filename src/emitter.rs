@@ -0,0 +1,85 @@
+use rand::Rng;
+
+use crate::entity::KindId;
+
+// Fountain that spawns entities of a kind at a steady rate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Emitter {
+    pub position: [f64; 2],
+    pub kind: KindId,
+    // Entities per second.
+    pub rate: f64,
+    // Center of the launch cone in radians, 0 towards +x.
+    pub direction: f64,
+    // Full width of the cone in radians.
+    pub spread: f64,
+    // Units per second.
+    pub speed: f64,
+    // Fraction of an entity carried over between steps.
+    pub accumulator: f64,
+}
+
+impl Emitter {
+    pub fn new(position: [f64; 2], kind: KindId, rate: f64) -> Emitter {
+        Emitter {
+            position,
+            kind,
+            rate,
+            direction: 0.0,
+            spread: 0.0,
+            speed: 0.0,
+            accumulator: 0.0,
+        }
+    }
+
+    // Launch velocities of the entities due after `dt` more seconds.
+    pub fn emit<R: Rng>(&mut self, dt: f64, rng: &mut R) -> Vec<[f64; 2]> {
+        self.accumulator += self.rate.max(0.0) * dt;
+
+        let count = self.accumulator.floor();
+        self.accumulator -= count;
+
+        (0..count as usize)
+            .map(|_| {
+                let half = self.spread.abs() / 2.0;
+                let angle = if half > 0.0 {
+                    self.direction + rng.gen_range(-half..=half)
+                } else {
+                    self.direction
+                };
+
+                [self.speed * angle.cos(), self.speed * angle.sin()]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::*;
+
+    #[test]
+    fn emits_at_the_rate_within_the_cone() {
+        let mut emitter = Emitter {
+            direction: std::f64::consts::FRAC_PI_2,
+            spread: 1.0,
+            speed: 5.0,
+            ..Emitter::new([0.0, 0.0], KindId(0), 2.0)
+        };
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut velocities = Vec::new();
+
+        for _ in 0..40 {
+            velocities.extend(emitter.emit(0.25, &mut rng));
+        }
+
+        assert_eq!(velocities.len(), 20);
+        for [vx, vy] in velocities {
+            assert!((vx.hypot(vy) - 5.0).abs() < 1e-9);
+            assert!((vy.atan2(vx) - emitter.direction).abs() <= 0.5 + 1e-9);
+        }
+    }
+}
@@ -0,0 +1,382 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::f64::consts::{PI, TAU};
+
+use crate::behavior::AI;
+use crate::collision::{DEFAULT_COLLISION_LAYER, DEFAULT_COLLISION_MASK};
+use crate::color::{age_color, HueCycle};
+use crate::epidemic::Health;
+use crate::error::NatureError;
+use crate::lifecycle::Lifecycle;
+use crate::render::Glow;
+use crate::shapes::Drawable;
+use crate::split::Split;
+use crate::trail::Trail;
+use crate::world::BoundaryMode;
+
+// Arbitrary data attached to an entity, at most one value of each type. Values must be
+// shareable across threads as entities are.
+#[derive(Default)]
+pub struct Components(HashMap<TypeId, Box<dyn Any + Send + Sync>>);
+
+impl Components {
+    // Returns the value of the same type it replaces, if any.
+    pub fn insert<T: Any + Send + Sync>(&mut self, value: T) -> Option<T> {
+        self.0
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|old| old.downcast().ok())
+            .map(|old| *old)
+    }
+
+    pub fn get<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.0
+            .get(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_ref())
+    }
+
+    pub fn get_mut<T: Any + Send + Sync>(&mut self) -> Option<&mut T> {
+        self.0
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|value| value.downcast_mut())
+    }
+
+    pub fn remove<T: Any + Send + Sync>(&mut self) -> Option<T> {
+        self.0
+            .remove(&TypeId::of::<T>())
+            .and_then(|value| value.downcast().ok())
+            .map(|value| *value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+// Handle to an `EntityKind` stored on the world.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KindId(pub usize);
+
+// Data shared by every entity of a kind, so identical entities don't each own a renderer.
+pub struct EntityKind {
+    pub renderer: Box<dyn Drawable>,
+    pub size: f64,
+}
+
+pub enum Renderer {
+    Owned(Box<dyn Drawable>),
+    Kind(KindId),
+}
+
+pub struct Entity {
+    pub physics: Physics,
+    pub renderer: Renderer,
+    pub behavior: Option<Box<dyn AI>>,
+    // The behavior only runs on ticks that are a multiple of this, in between the entity
+    // coasts on its last velocity.
+    pub update_every: u32,
+    // Higher layers draw on top when rendering by layer.
+    pub layer: i32,
+    pub trail: Option<Trail>,
+    // Cycles the drawn color through the hues, see `HueCycle`.
+    pub hue_cycle: Option<HueCycle>,
+    // Drawn around the entity, beneath every shape.
+    pub glow: Option<Glow>,
+    // Replaces the world's boundary mode for this entity when set.
+    pub boundary_mode: Option<BoundaryMode>,
+    // Seconds since the entity was created.
+    pub age: f64,
+    pub lifetime: Option<f64>,
+    // Start and end colors blended over the lifetime, in place of the renderer's own.
+    pub life_colors: Option<([f32; 4], [f32; 4])>,
+    pub split: Option<Split>,
+    // Stages the entity goes through by age, see `World::run_lifecycles`.
+    pub lifecycle: Option<Lifecycle>,
+    // Free-form labels behaviors can react to, like "positive" and "negative".
+    pub tags: Vec<String>,
+    // Skipped by the update until a force or collision wakes it, see `settle`.
+    pub sleeping: bool,
+    // Pinned in place: skipped by behaviors and integration for good, but still drawn and
+    // still an obstacle to others.
+    pub frozen: bool,
+    // Index of the world region the entity is kept in, see `RegionMap`.
+    pub region: Option<usize>,
+    // Radians per second rotations asked for by behaviors are reached at, instantly
+    // when not set.
+    pub max_turn_rate: Option<f64>,
+    // Layers the entity is on and layers it collides with as bits, see `layers_collide`.
+    pub collision_layer: u32,
+    pub collision_mask: u32,
+    // Part of the world's epidemic when set, see `Epidemic`.
+    pub health: Option<Health>,
+    // Consecutive ticks spent below the sleep speed.
+    pub still_ticks: u32,
+    // Custom data from downstream code, like health or a faction. Not saved, and split
+    // children start without any.
+    pub components: Components,
+    // Position at the start of the last step, for drawing in between steps.
+    pub previous: Option<[f64; 2]>,
+    // Simulated time of the last flash, see `World::flash`.
+    pub flashed_at: Option<f64>,
+    // Acceleration from the world's forces at the end of the last step, for Verlet.
+    pub acceleration: Option<[f64; 2]>,
+    pub _id: u32,
+}
+
+impl Entity {
+    pub fn new(id: u32, physics: Physics, renderer: Renderer) -> Entity {
+        Entity {
+            physics,
+            renderer,
+            behavior: None,
+            update_every: 1,
+            layer: 0,
+            trail: None,
+            hue_cycle: None,
+            glow: None,
+            boundary_mode: None,
+            age: 0.0,
+            lifetime: None,
+            life_colors: None,
+            split: None,
+            lifecycle: None,
+            tags: Vec::new(),
+            sleeping: false,
+            frozen: false,
+            region: None,
+            max_turn_rate: None,
+            collision_layer: DEFAULT_COLLISION_LAYER,
+            collision_mask: DEFAULT_COLLISION_MASK,
+            health: None,
+            still_ticks: 0,
+            flashed_at: None,
+            previous: None,
+            components: Components::default(),
+            acceleration: None,
+            _id: id,
+        }
+    }
+
+    pub fn with_trail(mut self, trail: Trail) -> Entity {
+        self.trail = Some(trail);
+        self
+    }
+
+    pub fn with_life_colors(mut self, lifetime: f64, start: [f32; 4], end: [f32; 4]) -> Entity {
+        self.lifetime = Some(lifetime);
+        self.life_colors = Some((start, end));
+        self
+    }
+
+    // Color to draw with instead of the renderer's own, if any.
+    pub fn color_override(&self) -> Option<[f32; 4]> {
+        match (self.life_colors, self.lifetime) {
+            (Some((start, end)), Some(lifetime)) => Some(age_color(start, end, self.age, lifetime)),
+            _ => None,
+        }
+    }
+
+    pub fn with_split(mut self, split: Split) -> Entity {
+        self.split = Some(split);
+        self
+    }
+
+    pub fn with_tag(mut self, tag: &str) -> Entity {
+        self.tags.push(tag.to_string());
+        self
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|own| own == tag)
+    }
+
+    pub fn with_component<T: Any + Send + Sync>(mut self, value: T) -> Entity {
+        self.components.insert(value);
+        self
+    }
+
+    pub fn get_component<T: Any + Send + Sync>(&self) -> Option<&T> {
+        self.components.get()
+    }
+
+    pub fn get_component_mut<T: Any + Send + Sync>(&mut self) -> Option<&mut T> {
+        self.components.get_mut()
+    }
+
+    pub fn with_behavior(mut self, behavior: Box<dyn AI>) -> Entity {
+        behavior.on_spawn(&self.physics);
+        self.behavior = Some(behavior);
+        self
+    }
+
+    // Counts another tick below `speed`, falling asleep at rest after `ticks` of them.
+    pub fn settle(&mut self, speed: f64, ticks: u32) {
+        let [vx, vy] = [self.physics.vx, self.physics.vy];
+
+        if vx * vx + vy * vy >= speed * speed {
+            self.still_ticks = 0;
+            return;
+        }
+
+        self.still_ticks = self.still_ticks.saturating_add(1);
+        if self.still_ticks >= ticks {
+            self.sleeping = true;
+            self.physics.vx = 0.0;
+            self.physics.vy = 0.0;
+        }
+    }
+
+    pub fn wake(&mut self) {
+        self.sleeping = false;
+        self.still_ticks = 0;
+    }
+
+    // Whether the behavior runs on `tick`, 0 counting as every tick.
+    pub fn behavior_due(&self, tick: u64) -> bool {
+        tick.is_multiple_of(self.update_every.max(1) as u64)
+    }
+}
+
+#[derive(Clone, Debug, Copy, PartialEq)]
+pub struct Physics {
+    pub x: f64,
+    pub y: f64,
+    pub size: f64,
+    pub rotation: f64,
+    pub vx: f64,
+    pub vy: f64,
+    // Radians per second the shape spins by, whatever way it's heading.
+    pub angular_velocity: f64,
+}
+
+// The same angle within [-π, π).
+pub fn normalize_angle(angle: f64) -> f64 {
+    (angle + PI).rem_euclid(TAU) - PI
+}
+
+impl Physics {
+    // At rest and unrotated, rejecting sizes that would make the shape vanish and
+    // coordinates that aren't finite.
+    pub fn new(x: f64, y: f64, size: f64) -> Result<Physics, NatureError> {
+        let physics = Physics {
+            x,
+            y,
+            size,
+            rotation: 0.0,
+            vx: 0.0,
+            vy: 0.0,
+            angular_velocity: 0.0,
+        };
+
+        if !physics.is_valid() {
+            return Err(NatureError::InvalidParameter(format!(
+                "physics needs finite coordinates and a positive size, got ({}, {}) size {}",
+                x, y, size
+            )));
+        }
+
+        Ok(physics)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.x.is_finite()
+            && self.y.is_finite()
+            && self.size.is_finite()
+            && self.size > 0.0
+            && self.vx.is_finite()
+            && self.vy.is_finite()
+            && self.angular_velocity.is_finite()
+    }
+
+    // Turns by the angular velocity over `dt`, keeping the rotation normalized. Left
+    // alone when not spinning.
+    pub fn spin(&mut self, dt: f64) {
+        if self.angular_velocity != 0.0 {
+            self.rotation = normalize_angle(self.rotation + self.angular_velocity * dt);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapes::Circle;
+
+    fn entity() -> Entity {
+        Entity::new(
+            0,
+            Physics::new(0.0, 0.0, 1.0).unwrap(),
+            Renderer::Owned(Box::new(Circle::new([1.0; 4]))),
+        )
+    }
+
+    #[test]
+    fn physics_rejects_vanishing_sizes_and_nan_coordinates() {
+        assert!(Physics::new(1.0, 2.0, 3.0).is_ok());
+        assert!(Physics::new(1.0, 2.0, 0.0).is_err());
+        assert!(Physics::new(1.0, 2.0, -3.0).is_err());
+        assert!(Physics::new(f64::NAN, 2.0, 3.0).is_err());
+        assert!(Physics::new(1.0, f64::NAN, 3.0).is_err());
+        assert!(Physics::new(1.0, f64::INFINITY, 3.0).is_err());
+    }
+
+    #[test]
+    fn behavior_runs_every_update_every_ticks() {
+        let mut entity = entity();
+        entity.update_every = 3;
+
+        let due: Vec<u64> = (0..10).filter(|&tick| entity.behavior_due(tick)).collect();
+        assert_eq!(due, vec![0, 3, 6, 9]);
+
+        entity.update_every = 0;
+        assert!((0..4).all(|tick| entity.behavior_due(tick)));
+    }
+
+    #[test]
+    fn components_keep_one_value_per_type() {
+        #[derive(Debug, PartialEq)]
+        struct Health(u32);
+
+        let mut components = Components::default();
+        assert!(components.is_empty());
+        assert_eq!(components.insert(Health(3)), None);
+        assert_eq!(components.insert(7u8), None);
+        assert_eq!(components.insert(Health(5)), Some(Health(3)));
+        assert_eq!(components.len(), 2);
+        assert_eq!(components.get::<Health>(), Some(&Health(5)));
+        assert_eq!(components.get::<u8>(), Some(&7));
+        assert_eq!(components.get::<u16>(), None);
+
+        components.get_mut::<Health>().unwrap().0 -= 1;
+        assert_eq!(components.remove::<Health>(), Some(Health(4)));
+        assert_eq!(components.get::<Health>(), None);
+
+        let entity = entity().with_component(Health(1));
+        assert_eq!(entity.get_component::<Health>(), Some(&Health(1)));
+    }
+
+    #[test]
+    fn spin_accumulates_rotation_within_a_turn() {
+        let mut physics = Physics::new(0.0, 0.0, 1.0).unwrap();
+        physics.angular_velocity = 1.0;
+
+        for _ in 0..6 {
+            physics.spin(0.5);
+        }
+        assert!((physics.rotation - 3.0).abs() < 1e-12);
+
+        physics.spin(0.5);
+        assert!((physics.rotation - (3.5 - TAU)).abs() < 1e-12);
+
+        assert_eq!(normalize_angle(PI), -PI);
+        assert!((normalize_angle(-3.0 * TAU + 1.0) - 1.0).abs() < 1e-12);
+
+        physics.angular_velocity = 0.0;
+        physics.rotation = 5.0;
+        physics.spin(0.5);
+        assert_eq!(physics.rotation, 5.0);
+    }
+}
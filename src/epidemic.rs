@@ -0,0 +1,166 @@
+use rand::Rng;
+use serde::Deserialize;
+
+use crate::spatial::NeighborQuery;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Health {
+    Susceptible,
+    // Since this many seconds of simulated time.
+    Infected { since: f64 },
+    Recovered,
+}
+
+// Health an entity starts with, as written in a scene file. The infected start their
+// illness at time zero.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthState {
+    Susceptible,
+    Infected,
+    Recovered,
+}
+
+impl HealthState {
+    pub fn health(self) -> Health {
+        match self {
+            HealthState::Susceptible => Health::Susceptible,
+            HealthState::Infected => Health::Infected { since: 0.0 },
+            HealthState::Recovered => Health::Recovered,
+        }
+    }
+}
+
+// SIR spread between nearby entities: each step every infected entity infects each
+// susceptible one within `radius` with probability `chance`, and recovers for good
+// `recovery` seconds after catching it. Entities without a health take no part.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Epidemic {
+    pub radius: f64,
+    pub chance: f64,
+    pub recovery: f64,
+    // Drawn colors of the susceptible, infected and recovered.
+    #[serde(default = "default_health_colors")]
+    pub colors: [[f32; 4]; 3],
+}
+
+fn default_health_colors() -> [[f32; 4]; 3] {
+    [
+        [0.3, 0.6, 1.0, 1.0],
+        [1.0, 0.2, 0.2, 1.0],
+        [0.6, 0.6, 0.6, 1.0],
+    ]
+}
+
+impl Epidemic {
+    pub fn color(&self, health: Health) -> [f32; 4] {
+        match health {
+            Health::Susceptible => self.colors[0],
+            Health::Infected { .. } => self.colors[1],
+            Health::Recovered => self.colors[2],
+        }
+    }
+
+    // Health of everyone after the step ending at `now`. `index` is built over
+    // `positions`, which line up with `health`. Only those infected before the step
+    // pass it on, so it spreads one contact per step.
+    pub fn spread<R: Rng>(
+        &self,
+        health: &[Option<Health>],
+        positions: &[[f64; 2]],
+        index: &dyn NeighborQuery,
+        now: f64,
+        rng: &mut R,
+    ) -> Vec<Option<Health>> {
+        let mut next = health.to_vec();
+
+        for (i, state) in health.iter().enumerate() {
+            if !matches!(state, Some(Health::Infected { .. })) {
+                continue;
+            }
+
+            let mut neighbors = index.within_radius(positions[i], self.radius);
+            // Same rolls whatever order the index returns them in.
+            neighbors.sort_unstable();
+
+            for j in neighbors {
+                if j != i
+                    && health[j] == Some(Health::Susceptible)
+                    && next[j] == Some(Health::Susceptible)
+                    && rng.gen_bool(self.chance.clamp(0.0, 1.0))
+                {
+                    next[j] = Some(Health::Infected { since: now });
+                }
+            }
+        }
+
+        for state in next.iter_mut() {
+            if let Some(Health::Infected { since }) = *state {
+                if now - since >= self.recovery {
+                    *state = Some(Health::Recovered);
+                }
+            }
+        }
+
+        next
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{Entity, Physics, Renderer};
+    use crate::shapes::Circle;
+    use crate::world::World;
+
+    // Ten entities in a row 5 apart, the first infected, after `steps` steps.
+    fn infected_after(steps: usize, chance: f64, seed: u64) -> usize {
+        let entities = (0..10)
+            .map(|id| {
+                let mut entity = Entity::new(
+                    id,
+                    Physics::new(5.0 + id as f64 * 5.0, 50.0, 2.0).unwrap(),
+                    Renderer::Owned(Box::new(Circle::new([1.0; 4]))),
+                );
+                entity.health = Some(if id == 0 {
+                    Health::Infected { since: 0.0 }
+                } else {
+                    Health::Susceptible
+                });
+                entity
+            })
+            .collect();
+        let mut world = World::new(entities, 100.0, 100.0).unwrap();
+        world.seed = Some(seed);
+        world.epidemic = Some(Epidemic {
+            radius: 6.0,
+            chance,
+            recovery: 100.0,
+            colors: default_health_colors(),
+        });
+
+        for _ in 0..steps {
+            world.step(0.1);
+        }
+
+        world
+            .entities
+            .iter()
+            .filter(|entity| matches!(entity.health, Some(Health::Infected { .. })))
+            .count()
+    }
+
+    #[test]
+    fn seeded_epidemic_spreads_one_contact_per_step() {
+        // Certain infection walks down the row one entity a step.
+        assert_eq!(infected_after(1, 1.0, 3), 2);
+        assert_eq!(infected_after(4, 1.0, 3), 5);
+        assert_eq!(infected_after(20, 1.0, 3), 10);
+
+        // A coin flip per contact, the same flips for the same seed.
+        assert_eq!(infected_after(8, 0.5, 3), 8);
+        assert_eq!(infected_after(8, 0.5, 0), 4);
+        assert_eq!(infected_after(8, 0.0, 3), 1);
+    }
+}
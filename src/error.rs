@@ -0,0 +1,41 @@
+use std::fmt;
+
+use crate::config::ConfigError;
+
+#[derive(Debug)]
+pub enum NatureError {
+    InvalidParameter(String),
+    Config(ConfigError),
+    Window(String),
+    Font(String),
+    Texture(String),
+    Save(String),
+    // A save written by a newer build, or with a version that never existed.
+    UnsupportedVersion { found: u64, supported: u32 },
+}
+
+impl fmt::Display for NatureError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NatureError::InvalidParameter(message) => write!(f, "invalid parameter: {}", message),
+            NatureError::Config(error) => write!(f, "{}", error),
+            NatureError::Window(message) => write!(f, "could not open the window: {}", message),
+            NatureError::Font(message) => write!(f, "could not load the font: {}", message),
+            NatureError::Texture(message) => write!(f, "could not load the texture: {}", message),
+            NatureError::Save(message) => write!(f, "could not read the save: {}", message),
+            NatureError::UnsupportedVersion { found, supported } => write!(
+                f,
+                "save version {} is not supported, this build reads versions 1 to {}",
+                found, supported
+            ),
+        }
+    }
+}
+
+impl std::error::Error for NatureError {}
+
+impl From<ConfigError> for NatureError {
+    fn from(error: ConfigError) -> NatureError {
+        NatureError::Config(error)
+    }
+}
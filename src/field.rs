@@ -0,0 +1,48 @@
+use serde::Deserialize;
+
+// Step used for the central differences of `ScalarField::gradient`, in world units.
+const GRADIENT_STEP: f64 = 0.5;
+
+// Gaussian bump of a scalar quantity like nutrient or heat, negative for a sink.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Source {
+    pub position: [f64; 2],
+    // Value at the center.
+    pub strength: f64,
+    // Standard deviation of the bump.
+    pub radius: f64,
+}
+
+// Sum of sources sampled anywhere in the world.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ScalarField {
+    pub sources: Vec<Source>,
+}
+
+impl ScalarField {
+    pub fn sample(&self, point: [f64; 2]) -> f64 {
+        self.sources
+            .iter()
+            .filter(|source| source.radius > 0.0)
+            .map(|source| {
+                let dx = point[0] - source.position[0];
+                let dy = point[1] - source.position[1];
+                let spread = 2.0 * source.radius * source.radius;
+
+                source.strength * (-(dx * dx + dy * dy) / spread).exp()
+            })
+            .sum()
+    }
+
+    // Direction of steepest increase, scaled by how steep it is.
+    pub fn gradient(&self, point: [f64; 2]) -> [f64; 2] {
+        let h = GRADIENT_STEP;
+        let [x, y] = point;
+
+        [
+            (self.sample([x + h, y]) - self.sample([x - h, y])) / (2.0 * h),
+            (self.sample([x, y + h]) - self.sample([x, y - h])) / (2.0 * h),
+        ]
+    }
+}
@@ -0,0 +1,245 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::error::NatureError;
+
+// Neighbor offsets as `[column, row]`, the four sides then the diagonals.
+const NEIGHBORS: [[i64; 2]; 8] = [
+    [1, 0],
+    [-1, 0],
+    [0, 1],
+    [0, -1],
+    [1, 1],
+    [1, -1],
+    [-1, 1],
+    [-1, -1],
+];
+
+// Grid of directions leading every reachable cell to a shared goal around the blocked
+// cells, row by row from `origin`. The goal, blocked and unreachable cells have none.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FlowField {
+    // Corner of the first cell, the one with the smallest coordinates.
+    pub origin: [f64; 2],
+    pub cell_size: f64,
+    pub columns: usize,
+    pub rows: usize,
+    // Path length to the goal in cells, infinite where it can't be reached.
+    distances: Vec<f64>,
+    // Unit directions towards the next cell on the way, `columns` per row.
+    directions: Vec<[f64; 2]>,
+}
+
+// Heap entry, ordered so the shortest distance pops first.
+#[derive(PartialEq)]
+struct Visit {
+    distance: f64,
+    cell: usize,
+}
+
+impl Eq for Visit {}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Visit) -> Ordering {
+        other.distance.total_cmp(&self.distance)
+    }
+}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Visit) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FlowField {
+    // Runs Dijkstra out from the cell under `goal`, diagonal steps costing √2 and never
+    // cutting the corner of a blocked cell. `blocked` cells are `[column, row]`.
+    pub fn new(
+        origin: [f64; 2],
+        cell_size: f64,
+        columns: usize,
+        rows: usize,
+        goal: [f64; 2],
+        blocked: &[[usize; 2]],
+    ) -> Result<FlowField, NatureError> {
+        if !(cell_size.is_finite() && cell_size > 0.0) {
+            return Err(NatureError::InvalidParameter(format!(
+                "flow field cells need a positive size, got {}",
+                cell_size
+            )));
+        }
+        if columns == 0 || rows == 0 {
+            return Err(NatureError::InvalidParameter(format!(
+                "flow field needs at least one cell, got {}x{}",
+                columns, rows
+            )));
+        }
+
+        let mut field = FlowField {
+            origin,
+            cell_size,
+            columns,
+            rows,
+            distances: vec![f64::INFINITY; columns * rows],
+            directions: vec![[0.0, 0.0]; columns * rows],
+        };
+        let goal = field.cell_of(goal).ok_or_else(|| {
+            NatureError::InvalidParameter(format!("flow field goal {:?} is outside the grid", goal))
+        })?;
+
+        let mut open = vec![true; columns * rows];
+        for &[column, row] in blocked {
+            if column >= columns || row >= rows {
+                return Err(NatureError::InvalidParameter(format!(
+                    "blocked cell {:?} is outside the {}x{} flow field",
+                    [column, row],
+                    columns,
+                    rows
+                )));
+            }
+            open[row * columns + column] = false;
+        }
+        if !open[goal] {
+            return Err(NatureError::InvalidParameter(
+                "flow field goal is in a blocked cell".to_string(),
+            ));
+        }
+
+        field.distances[goal] = 0.0;
+        let mut heap = BinaryHeap::new();
+        heap.push(Visit {
+            distance: 0.0,
+            cell: goal,
+        });
+
+        while let Some(Visit { distance, cell }) = heap.pop() {
+            if distance > field.distances[cell] {
+                continue;
+            }
+
+            for next in field.steps(cell, &open) {
+                let step = if next.1 {
+                    std::f64::consts::SQRT_2
+                } else {
+                    1.0
+                };
+                if distance + step < field.distances[next.0] {
+                    field.distances[next.0] = distance + step;
+                    heap.push(Visit {
+                        distance: distance + step,
+                        cell: next.0,
+                    });
+                }
+            }
+        }
+
+        // Every reached cell points at its closest neighbor, ties going to the sides.
+        for cell in 0..columns * rows {
+            if cell == goal || !field.distances[cell].is_finite() {
+                continue;
+            }
+
+            let best = field
+                .steps(cell, &open)
+                .into_iter()
+                .map(|(next, diagonal)| {
+                    let step = if diagonal {
+                        std::f64::consts::SQRT_2
+                    } else {
+                        1.0
+                    };
+                    (next, field.distances[next] + step)
+                })
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+
+            if let Some((next, _)) = best {
+                let dx = (next % columns) as f64 - (cell % columns) as f64;
+                let dy = (next / columns) as f64 - (cell / columns) as f64;
+                let length = (dx * dx + dy * dy).sqrt();
+                field.directions[cell] = [dx / length, dy / length];
+            }
+        }
+
+        Ok(field)
+    }
+
+    // Open neighbors of `cell`, each with whether the step is diagonal.
+    fn steps(&self, cell: usize, open: &[bool]) -> Vec<(usize, bool)> {
+        let (column, row) = ((cell % self.columns) as i64, (cell / self.columns) as i64);
+        let index = |column: i64, row: i64| {
+            if column < 0 || row < 0 || column >= self.columns as i64 || row >= self.rows as i64 {
+                return None;
+            }
+            let index = row as usize * self.columns + column as usize;
+            Some(index).filter(|&index| open[index])
+        };
+
+        NEIGHBORS
+            .iter()
+            .filter_map(|&[dx, dy]| {
+                let next = index(column + dx, row + dy)?;
+                let diagonal = dx != 0 && dy != 0;
+                if diagonal
+                    && (index(column + dx, row).is_none() || index(column, row + dy).is_none())
+                {
+                    return None;
+                }
+                Some((next, diagonal))
+            })
+            .collect()
+    }
+
+    // Index of the cell under `point`, None outside the grid.
+    pub fn cell_of(&self, point: [f64; 2]) -> Option<usize> {
+        let column = ((point[0] - self.origin[0]) / self.cell_size).floor();
+        let row = ((point[1] - self.origin[1]) / self.cell_size).floor();
+
+        if column < 0.0 || row < 0.0 || column >= self.columns as f64 || row >= self.rows as f64 {
+            return None;
+        }
+
+        Some(row as usize * self.columns + column as usize)
+    }
+
+    // Path length from the cell under `point` to the goal in cells, None where no path.
+    pub fn distance_at(&self, point: [f64; 2]) -> Option<f64> {
+        self.cell_of(point)
+            .map(|cell| self.distances[cell])
+            .filter(|distance| distance.is_finite())
+    }
+
+    // Way to go from the cell under `point`, None outside the grid, on the goal or
+    // where there is no path.
+    pub fn direction_at(&self, point: [f64; 2]) -> Option<[f64; 2]> {
+        self.cell_of(point)
+            .map(|cell| self.directions[cell])
+            .filter(|&direction| direction != [0.0, 0.0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn open_field_points_every_cell_towards_the_goal() {
+        let field = FlowField::new([0.0, 0.0], 10.0, 10, 10, [55.0, 55.0], &[]).unwrap();
+
+        assert_eq!(field.direction_at([55.0, 55.0]), None);
+        assert_eq!(field.distance_at([55.0, 55.0]), Some(0.0));
+
+        for row in 0..10 {
+            for column in 0..10 {
+                let center = [column as f64 * 10.0 + 5.0, row as f64 * 10.0 + 5.0];
+                let [dx, dy] = match field.direction_at(center) {
+                    Some(direction) => direction,
+                    None => continue,
+                };
+                let next = [center[0] + dx * 10.0, center[1] + dy * 10.0];
+
+                assert!(dx * (55.0 - center[0]) + dy * (55.0 - center[1]) > 0.0);
+                assert!(field.distance_at(next) < field.distance_at(center));
+            }
+        }
+    }
+}
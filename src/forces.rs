@@ -0,0 +1,115 @@
+use std::f64::consts::PI;
+
+use serde::Deserialize;
+
+// Uniform acceleration applied to every entity each tick.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Wind {
+    // Heading in radians, 0 blowing towards +x.
+    pub direction: f64,
+    // Units per second squared.
+    pub strength: f64,
+    // When set, the wind swings back and forth with this period in seconds.
+    pub period: Option<f64>,
+}
+
+impl Wind {
+    pub fn acceleration(&self, sim_time: f64) -> [f64; 2] {
+        let scale = match self.period {
+            Some(period) if period > 0.0 => (2.0 * PI * sim_time / period).cos(),
+            _ => 1.0,
+        };
+        let strength = self.strength * scale;
+
+        [
+            strength * self.direction.cos(),
+            strength * self.direction.sin(),
+        ]
+    }
+}
+
+// Fraction of its velocity an entity keeps over `dt` under air drag, which grows with
+// its cross-section, the size in 2D. `coefficient` is the loss per second per unit of size.
+pub fn drag_keep(size: f64, coefficient: f64, dt: f64) -> f64 {
+    (1.0 - coefficient * size * dt).clamp(0.0, 1.0)
+}
+
+// Acceleration towards `to` from a body of `mass` there, by mass over squared distance.
+// The distance is softened as sqrt(d² + softening²), so the pull fades back to zero as
+// the bodies meet instead of blowing up.
+pub fn softened_pull(
+    from: [f64; 2],
+    to: [f64; 2],
+    mass: f64,
+    strength: f64,
+    softening: f64,
+) -> [f64; 2] {
+    let [dx, dy] = [to[0] - from[0], to[1] - from[1]];
+    let squared = dx * dx + dy * dy + softening * softening;
+
+    if squared < f64::EPSILON {
+        return [0.0, 0.0];
+    }
+
+    let scale = strength * mass / (squared * squared.sqrt());
+    [dx * scale, dy * scale]
+}
+
+// Keeps the pull finite when an entity passes right over an attractor.
+const SOFTENING: f64 = 10.0;
+
+// Point source pulling entities in, or pushing them away when `strength` is negative.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Attractor {
+    pub position: [f64; 2],
+    // Acceleration at unit distance, falling off with the square of the distance.
+    pub strength: f64,
+}
+
+impl Attractor {
+    pub fn acceleration(&self, point: [f64; 2]) -> [f64; 2] {
+        let dx = self.position[0] - point[0];
+        let dy = self.position[1] - point[1];
+        let distance_squared = dx * dx + dy * dy;
+        let distance = distance_squared.sqrt();
+
+        if distance < f64::EPSILON {
+            return [0.0, 0.0];
+        }
+
+        let pull = self.strength / (distance_squared + SOFTENING * SOFTENING);
+        [pull * dx / distance, pull * dy / distance]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oscillating_wind_changes_sign_every_half_period() {
+        let wind = Wind {
+            direction: 0.0,
+            strength: 10.0,
+            period: Some(4.0),
+        };
+
+        assert!((wind.acceleration(0.0)[0] - 10.0).abs() < 1e-9);
+        assert!(wind.acceleration(1.0)[0].abs() < 1e-9);
+        assert!((wind.acceleration(2.0)[0] + 10.0).abs() < 1e-9);
+        assert!((wind.acceleration(4.0)[0] - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn larger_entities_lose_more_velocity_to_drag() {
+        let small = drag_keep(2.0, 0.1, 0.5);
+        let large = drag_keep(8.0, 0.1, 0.5);
+
+        assert!((small - 0.9).abs() < 1e-12);
+        assert!((large - 0.6).abs() < 1e-12);
+        assert!(large < small);
+        assert_eq!(drag_keep(100.0, 0.1, 0.5), 0.0);
+        assert_eq!(drag_keep(8.0, 0.0, 0.5), 1.0);
+    }
+}
@@ -0,0 +1,289 @@
+use rand::Rng;
+use rand_distr::{Distribution, Normal};
+use serde::Deserialize;
+
+use crate::behavior::{step_rng, Walker};
+use crate::entity::{Entity, Physics, Renderer};
+use crate::error::NatureError;
+use crate::shapes::{Circle, DEFAULT_SIZE};
+use crate::world::World;
+
+// What to do with samples falling outside the world, the normal distribution being unbounded.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OutOfBounds {
+    #[default]
+    Keep,
+    Clamp,
+    Resample,
+}
+
+// Resampling gives up and clamps after this many misses for one position.
+const MAX_RESAMPLES: usize = 100;
+
+pub struct Relaxation {
+    pub iterations: usize,
+    pub min_spacing: f64,
+}
+
+pub fn color_generator(x: f32, y: f32, width: u32, height: u32) -> [f32; 4] {
+    let red = x / width as f32;
+    let green = y / height as f32;
+    // let blue = (x + y) / (height as f32 + width as f32);
+
+    [red, green, 0.0, 1.0]
+}
+
+// Push every pair of points closer than `min_spacing` apart, half the overlap each.
+pub fn relax_positions(positions: &mut [[f64; 2]], relaxation: &Relaxation) {
+    let min_spacing = relaxation.min_spacing;
+
+    for _ in 0..relaxation.iterations {
+        let mut moved = false;
+
+        for i in 0..positions.len() {
+            for j in (i + 1)..positions.len() {
+                let dx = positions[j][0] - positions[i][0];
+                let dy = positions[j][1] - positions[i][1];
+                let distance = (dx * dx + dy * dy).sqrt();
+
+                if distance >= min_spacing {
+                    continue;
+                }
+
+                // Coincident points have no direction, split them along x.
+                let (nx, ny) = if distance > 0.0 {
+                    (dx / distance, dy / distance)
+                } else {
+                    (1.0, 0.0)
+                };
+                let push = (min_spacing - distance) / 2.0;
+
+                positions[i][0] -= nx * push;
+                positions[i][1] -= ny * push;
+                positions[j][0] += nx * push;
+                positions[j][1] += ny * push;
+                moved = true;
+            }
+        }
+
+        if !moved {
+            break;
+        }
+    }
+}
+
+// Spawn distribution, normal and independent along x and y.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Gaussian {
+    pub mean: [f64; 2],
+    pub stddev: [f64; 2],
+}
+
+impl Gaussian {
+    // The one `gaussian_positions` samples, centered with three deviations to each edge.
+    pub fn spawn(width: f64, height: f64) -> Gaussian {
+        Gaussian {
+            mean: [width / 2.0, height / 2.0],
+            stddev: [width / 6.0, height / 6.0],
+        }
+    }
+
+    // Bounds `[x, y, width, height]` of the density contour `sigmas` deviations out.
+    pub fn contour(&self, sigmas: f64) -> [f64; 4] {
+        let [rx, ry] = [self.stddev[0] * sigmas, self.stddev[1] * sigmas];
+
+        [self.mean[0] - rx, self.mean[1] - ry, 2.0 * rx, 2.0 * ry]
+    }
+}
+
+// rand_distr only rejects a stddev that isn't finite, a negative one is caught here.
+pub fn normal(mean: f64, stddev: f64) -> Result<Normal<f64>, NatureError> {
+    if stddev < 0.0 {
+        return Err(NatureError::InvalidParameter(format!(
+            "normal N({}, {}): stddev is negative",
+            mean, stddev
+        )));
+    }
+
+    Normal::new(mean, stddev).map_err(|error| {
+        NatureError::InvalidParameter(format!("normal N({}, {}): {}", mean, stddev, error))
+    })
+}
+
+// Positions normally distributed around the center, three deviations to each edge.
+pub fn gaussian_positions<R: Rng>(
+    size: usize,
+    width: u32,
+    height: u32,
+    out_of_bounds: OutOfBounds,
+    rng: &mut R,
+) -> Result<Vec<[f64; 2]>, NatureError> {
+    let gaussian = Gaussian::spawn(width as f64, height as f64);
+    let x_normal = normal(gaussian.mean[0], gaussian.stddev[0])?;
+    let y_normal = normal(gaussian.mean[1], gaussian.stddev[1])?;
+    let (width, height) = (width as f64, height as f64);
+    let inside = |[x, y]: [f64; 2]| x >= 0.0 && x <= width && y >= 0.0 && y <= height;
+
+    Ok((0..size)
+        .map(|_| {
+            let mut position = [x_normal.sample(rng), y_normal.sample(rng)];
+
+            if out_of_bounds == OutOfBounds::Resample {
+                for _ in 0..MAX_RESAMPLES {
+                    if inside(position) {
+                        break;
+                    }
+                    position = [x_normal.sample(rng), y_normal.sample(rng)];
+                }
+            }
+
+            if out_of_bounds != OutOfBounds::Keep {
+                position = [
+                    position[0].clamp(0.0, width),
+                    position[1].clamp(0.0, height),
+                ];
+            }
+
+            position
+        })
+        .collect())
+}
+
+pub fn gaussian_dots_generator<R: Rng>(
+    size: usize,
+    width: u32,
+    height: u32,
+    relaxation: Option<Relaxation>,
+    out_of_bounds: OutOfBounds,
+    rng: &mut R,
+) -> Result<Vec<Entity>, NatureError> {
+    let mut entities: Vec<Entity> = Vec::with_capacity(size);
+    let mut positions = gaussian_positions(size, width, height, out_of_bounds, rng)?;
+
+    if let Some(relaxation) = relaxation {
+        if !(relaxation.min_spacing.is_finite() && relaxation.min_spacing >= 0.0) {
+            return Err(NatureError::InvalidParameter(format!(
+                "relaxation min_spacing must be non-negative, got {}",
+                relaxation.min_spacing
+            )));
+        }
+
+        relax_positions(&mut positions, &relaxation);
+
+        // Relaxation can push the outermost dots back out.
+        if out_of_bounds != OutOfBounds::Keep {
+            for position in positions.iter_mut() {
+                position[0] = position[0].clamp(0.0, width as f64);
+                position[1] = position[1].clamp(0.0, height as f64);
+            }
+        }
+    }
+
+    for (id, [x, y]) in positions.into_iter().enumerate() {
+        let physics = Physics::new(x, y, DEFAULT_SIZE)?;
+        let renderer = Renderer::Owned(Box::new(Circle::new(color_generator(
+            x as f32, y as f32, width, height,
+        ))));
+
+        entities.push(
+            Entity::new(id as u32, physics, renderer).with_behavior(Box::new(Walker::default())),
+        );
+    }
+
+    Ok(entities)
+}
+
+// Scene run without a config, relaxed gaussian dots walking about. With a seed both the
+// dots and their walk repeat from run to run.
+pub fn gaussian_dots_world(
+    size: usize,
+    width: u32,
+    height: u32,
+    seed: Option<u64>,
+) -> Result<World, NatureError> {
+    // Spread the initial dots so they don't start on top of each other.
+    let relaxation = Relaxation {
+        iterations: 10,
+        min_spacing: 10.0,
+    };
+    // Spawning draws from a stream of its own, before the first tick, as configured
+    // scenes do.
+    let mut rng = step_rng(seed, 0, u64::MAX - 1);
+    let entities = gaussian_dots_generator(
+        size,
+        width,
+        height,
+        Some(relaxation),
+        OutOfBounds::Resample,
+        &mut rng,
+    )?;
+
+    let mut world = World::new(entities, width as f64, height as f64)?;
+    world.seed = seed;
+
+    Ok(world)
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn relaxation_spreads_a_clump_to_the_minimum_spacing() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut positions: Vec<[f64; 2]> = (0..20)
+            .map(|_| [rng.gen_range(0.0..5.0), rng.gen_range(0.0..5.0)])
+            .collect();
+        let relaxation = Relaxation {
+            iterations: 500,
+            min_spacing: 10.0,
+        };
+
+        relax_positions(&mut positions, &relaxation);
+
+        for (i, a) in positions.iter().enumerate() {
+            for b in positions.iter().skip(i + 1) {
+                let distance = (b[0] - a[0]).hypot(b[1] - a[1]);
+                assert!(
+                    distance >= 10.0 - 1e-6,
+                    "{:?} and {:?} are {} apart",
+                    a,
+                    b,
+                    distance
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn resampled_positions_stay_in_bounds() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let positions = gaussian_positions(2000, 60, 40, OutOfBounds::Resample, &mut rng).unwrap();
+
+        assert_eq!(positions.len(), 2000);
+        for [x, y] in positions {
+            assert!((0.0..=60.0).contains(&x) && (0.0..=40.0).contains(&y));
+        }
+    }
+
+    #[test]
+    fn negative_stddev_is_an_error() {
+        assert!(matches!(
+            normal(0.0, -1.0),
+            Err(NatureError::InvalidParameter(_))
+        ));
+        assert!(normal(0.0, 1.0).is_ok());
+    }
+
+    #[test]
+    fn one_sigma_contour_spans_a_deviation_each_way() {
+        let gaussian = Gaussian::spawn(600.0, 300.0);
+
+        assert_eq!(gaussian.contour(1.0), [200.0, 100.0, 200.0, 100.0]);
+        assert_eq!(gaussian.contour(3.0), [0.0, 0.0, 600.0, 300.0]);
+    }
+}
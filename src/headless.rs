@@ -0,0 +1,86 @@
+use crate::save::SavedWorld;
+use crate::stats::Metrics;
+use crate::svg::Trajectories;
+use crate::world::World;
+
+// Progress is reported this many times over a run, whatever its length.
+const PROGRESS_REPORTS: u64 = 10;
+
+// Steps the world `steps` times of `dt` seconds without rendering anything, calling
+// `progress` with the steps done every tenth of the way, and returns the final state.
+// Every position, the starting ones included, is added to `trajectories` when given, and
+// the metrics of every step to `metrics`.
+pub fn run_headless<F: FnMut(u64)>(
+    world: &mut World,
+    steps: u64,
+    dt: f64,
+    mut trajectories: Option<&mut Trajectories>,
+    mut metrics: Option<&mut Vec<Metrics>>,
+    mut progress: F,
+) -> SavedWorld {
+    let every = (steps / PROGRESS_REPORTS).max(1);
+
+    if let Some(trajectories) = trajectories.as_mut() {
+        record(world, trajectories);
+    }
+
+    for step in 1..=steps {
+        world.step(dt);
+
+        if let Some(trajectories) = trajectories.as_mut() {
+            record(world, trajectories);
+        }
+        if let Some(metrics) = metrics.as_mut() {
+            metrics.push(measure(world));
+        }
+
+        if step.is_multiple_of(every) || step == steps {
+            progress(step);
+        }
+    }
+
+    SavedWorld::capture(world)
+}
+
+fn record(world: &World, trajectories: &mut Trajectories) {
+    for entity in world.entities.iter() {
+        trajectories
+            .entry(entity._id)
+            .or_default()
+            .push([entity.physics.x, entity.physics.y]);
+    }
+}
+
+// Metrics of the step just taken, the collisions counted since the one before.
+fn measure(world: &mut World) -> Metrics {
+    let collisions = world.take_stats().collisions;
+    let positions: Vec<[f64; 2]> = world
+        .entities
+        .iter()
+        .map(|entity| [entity.physics.x, entity.physics.y])
+        .collect();
+    let velocities: Vec<[f64; 2]> = world
+        .entities
+        .iter()
+        .map(|entity| [entity.physics.vx, entity.physics.vy])
+        .collect();
+
+    Metrics::measure(world.tick, &positions, &velocities, collisions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::generator::gaussian_dots_world;
+
+    #[test]
+    fn seeded_default_scenes_run_the_same() {
+        let run = |seed| {
+            let mut world = gaussian_dots_world(50, 200, 100, Some(seed)).unwrap();
+            run_headless(&mut world, 30, 1.0 / 60.0, None, None, |_| {}).to_json()
+        };
+
+        assert_eq!(run(7), run(7));
+        assert_ne!(run(7), run(8));
+    }
+}
@@ -0,0 +1,113 @@
+use serde::Deserialize;
+
+use crate::entity::Physics;
+
+// How positions and velocities advance under the world's forces each step.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Integrator {
+    // Semi-implicit Euler, the velocity updated first and the position moved with it.
+    #[default]
+    Euler,
+    // Velocity Verlet, averaging the acceleration at the start and end of the step.
+    // Keeps orbits and springs from gaining energy over time.
+    Verlet,
+}
+
+// Substeps needed to move `displacement` in pieces no longer than `radius`, between 1
+// and `max`.
+pub fn substep_count(displacement: f64, radius: f64, max: usize) -> usize {
+    if !(displacement.is_finite() && radius > 0.0) || displacement <= radius {
+        return 1;
+    }
+
+    ((displacement / radius).ceil() as usize).clamp(1, max.max(1))
+}
+
+impl Integrator {
+    // Moves `physics` through one step of `acceleration`, a field of the position, with
+    // `keep` the fraction of velocity left after damping. `previous` is the acceleration
+    // this returned last step, if any, which Verlet reuses instead of sampling again.
+    pub fn advance<F>(
+        &self,
+        physics: &mut Physics,
+        previous: Option<[f64; 2]>,
+        keep: f64,
+        dt: f64,
+        acceleration: F,
+    ) -> [f64; 2]
+    where
+        F: Fn([f64; 2]) -> [f64; 2],
+    {
+        match self {
+            Integrator::Euler => {
+                let [ax, ay] = acceleration([physics.x, physics.y]);
+
+                physics.vx = (physics.vx + ax * dt) * keep;
+                physics.vy = (physics.vy + ay * dt) * keep;
+                physics.x += physics.vx * dt;
+                physics.y += physics.vy * dt;
+                [ax, ay]
+            }
+            Integrator::Verlet => {
+                let [ax, ay] = previous.unwrap_or_else(|| acceleration([physics.x, physics.y]));
+
+                physics.vx *= keep;
+                physics.vy *= keep;
+                physics.x += physics.vx * dt + 0.5 * ax * dt * dt;
+                physics.y += physics.vy * dt + 0.5 * ay * dt * dt;
+
+                let [nx, ny] = acceleration([physics.x, physics.y]);
+
+                physics.vx += 0.5 * (ax + nx) * dt;
+                physics.vy += 0.5 * (ay + ny) * dt;
+                [nx, ny]
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Largest energy error over a few circular orbits around a unit mass at the origin.
+    fn orbit_energy_drift(integrator: Integrator) -> f64 {
+        let gravity = |[x, y]: [f64; 2]| {
+            let r = x.hypot(y);
+            [-x / (r * r * r), -y / (r * r * r)]
+        };
+        let energy = |p: &Physics| 0.5 * (p.vx * p.vx + p.vy * p.vy) - 1.0 / p.x.hypot(p.y);
+        let mut physics = Physics::new(1.0, 0.0, 1.0).unwrap();
+        physics.vy = 1.0;
+        let start = energy(&physics);
+        let mut previous = None;
+        let mut drift: f64 = 0.0;
+
+        for _ in 0..1000 {
+            previous = Some(integrator.advance(&mut physics, previous, 1.0, 0.05, gravity));
+            drift = drift.max((energy(&physics) - start).abs());
+        }
+
+        drift
+    }
+
+    #[test]
+    fn verlet_drifts_less_than_euler_in_orbit() {
+        let euler = orbit_energy_drift(Integrator::Euler);
+        let verlet = orbit_energy_drift(Integrator::Verlet);
+
+        assert!(verlet < euler / 10.0, "verlet {} euler {}", verlet, euler);
+    }
+
+    #[test]
+    fn substeps_keep_each_move_within_the_radius() {
+        assert_eq!(substep_count(0.5, 1.0, 8), 1);
+        assert_eq!(substep_count(1.0, 1.0, 8), 1);
+        assert_eq!(substep_count(1.5, 1.0, 8), 2);
+        assert_eq!(substep_count(5.0, 1.0, 8), 5);
+        assert_eq!(substep_count(50.0, 1.0, 8), 8);
+        assert_eq!(substep_count(50.0, 1.0, 0), 1);
+        assert_eq!(substep_count(f64::INFINITY, 1.0, 8), 1);
+    }
+}
@@ -0,0 +1,77 @@
+use crate::error::NatureError;
+
+// Grid of flow directions laid over the world, row by row from `origin`. Cells with a
+// zero direction are off-lane.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LaneField {
+    // Corner of the first cell, the one with the smallest coordinates.
+    pub origin: [f64; 2],
+    pub cell_size: f64,
+    pub columns: usize,
+    // Unit directions, `columns` per row.
+    directions: Vec<[f64; 2]>,
+}
+
+impl LaneField {
+    // Directions are normalized, a partial last row is rejected.
+    pub fn new(
+        origin: [f64; 2],
+        cell_size: f64,
+        columns: usize,
+        directions: Vec<[f64; 2]>,
+    ) -> Result<LaneField, NatureError> {
+        if !(cell_size.is_finite() && cell_size > 0.0) {
+            return Err(NatureError::InvalidParameter(format!(
+                "lane cells need a positive size, got {}",
+                cell_size
+            )));
+        }
+        if columns == 0 || !directions.len().is_multiple_of(columns) {
+            return Err(NatureError::InvalidParameter(format!(
+                "{} lane directions don't fill rows of {}",
+                directions.len(),
+                columns
+            )));
+        }
+
+        let directions = directions
+            .into_iter()
+            .map(|[dx, dy]| {
+                let length = (dx * dx + dy * dy).sqrt();
+                if length.is_finite() && length > f64::EPSILON {
+                    [dx / length, dy / length]
+                } else {
+                    [0.0, 0.0]
+                }
+            })
+            .collect();
+
+        Ok(LaneField {
+            origin,
+            cell_size,
+            columns,
+            directions,
+        })
+    }
+
+    pub fn rows(&self) -> usize {
+        self.directions.len() / self.columns
+    }
+
+    // Flow direction of the cell under `point`, None outside the grid or off-lane.
+    pub fn direction_at(&self, point: [f64; 2]) -> Option<[f64; 2]> {
+        let column = ((point[0] - self.origin[0]) / self.cell_size).floor();
+        let row = ((point[1] - self.origin[1]) / self.cell_size).floor();
+
+        if column < 0.0 || row < 0.0 || column >= self.columns as f64 || row >= self.rows() as f64 {
+            return None;
+        }
+
+        let direction = self.directions[row as usize * self.columns + column as usize];
+        if direction == [0.0, 0.0] {
+            return None;
+        }
+
+        Some(direction)
+    }
+}
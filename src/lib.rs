@@ -0,0 +1,51 @@
+extern crate graphics;
+extern crate opengl_graphics;
+extern crate piston;
+extern crate rand;
+extern crate rand_distr;
+#[cfg(feature = "parallel")]
+extern crate rayon;
+extern crate serde;
+extern crate toml;
+
+pub mod app;
+pub mod behavior;
+pub mod budget;
+pub mod changelog;
+pub mod collision;
+pub mod color;
+pub mod config;
+pub mod coords;
+pub mod easing;
+pub mod emitter;
+pub mod entity;
+pub mod epidemic;
+pub mod error;
+pub mod field;
+pub mod flowfield;
+pub mod forces;
+pub mod generator;
+pub mod headless;
+pub mod integrator;
+pub mod lanes;
+pub mod life;
+pub mod lifecycle;
+pub mod links;
+pub mod mock;
+pub mod pathfinding;
+pub mod pheromone;
+pub mod portal;
+pub mod reaction;
+pub mod regions;
+pub mod render;
+pub mod save;
+pub mod shapes;
+pub mod spatial;
+pub mod split;
+pub mod sprite;
+pub mod stats;
+pub mod svg;
+pub mod trail;
+pub mod tuning;
+pub mod vectorfield;
+pub mod world;
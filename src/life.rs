@@ -0,0 +1,121 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::entity::KindId;
+use crate::error::NatureError;
+
+// Column and row of a grid cell.
+pub type Cell = (i64, i64);
+
+// Neighbor counts giving birth to an empty cell and keeping a live one alive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Rule {
+    pub birth: Vec<u8>,
+    pub survive: Vec<u8>,
+}
+
+impl Rule {
+    // Conway's Game of Life, B3/S23.
+    pub fn conway() -> Rule {
+        Rule {
+            birth: vec![3],
+            survive: vec![2, 3],
+        }
+    }
+
+    // Rule in the usual "B3/S23" notation.
+    pub fn parse(text: &str) -> Result<Rule, NatureError> {
+        let invalid = || NatureError::InvalidParameter(format!("life rule {:?}", text));
+        let (birth, survive) = text.split_once('/').ok_or_else(invalid)?;
+        let counts = |part: &str, prefix: char| -> Result<Vec<u8>, NatureError> {
+            let digits = part
+                .trim()
+                .strip_prefix(|c: char| c.eq_ignore_ascii_case(&prefix))
+                .ok_or_else(invalid)?;
+
+            digits
+                .chars()
+                .map(|c| match c.to_digit(10) {
+                    Some(count) if count <= 8 => Ok(count as u8),
+                    _ => Err(invalid()),
+                })
+                .collect()
+        };
+
+        Ok(Rule {
+            birth: counts(birth, 'B')?,
+            survive: counts(survive, 'S')?,
+        })
+    }
+}
+
+// Cells alive after one generation, counting the eight surrounding cells.
+pub fn next_generation(alive: &HashSet<Cell>, rule: &Rule) -> HashSet<Cell> {
+    let mut counts: HashMap<Cell, u8> = HashMap::new();
+
+    for &(column, row) in alive {
+        for dc in -1..=1 {
+            for dr in -1..=1 {
+                if dc != 0 || dr != 0 {
+                    *counts.entry((column + dc, row + dr)).or_insert(0) += 1;
+                }
+            }
+        }
+    }
+
+    counts
+        .into_iter()
+        .filter(|(cell, count)| {
+            if alive.contains(cell) {
+                rule.survive.contains(count)
+            } else {
+                rule.birth.contains(count)
+            }
+        })
+        .map(|(cell, _)| cell)
+        .collect()
+}
+
+// Runs a cellular automaton over the entities of one kind, each occupying the grid cell
+// under its center.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Life {
+    pub kind: KindId,
+    pub cell_size: f64,
+    pub rule: Rule,
+    // Ticks between generations.
+    pub every: u64,
+}
+
+impl Life {
+    pub fn cell_of(&self, point: [f64; 2]) -> Cell {
+        (
+            (point[0] / self.cell_size).floor() as i64,
+            (point[1] / self.cell_size).floor() as i64,
+        )
+    }
+
+    pub fn center_of(&self, (column, row): Cell) -> [f64; 2] {
+        [
+            (column as f64 + 0.5) * self.cell_size,
+            (row as f64 + 0.5) * self.cell_size,
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blinker_flips_between_its_two_phases() {
+        let rule = Rule::parse("B3/S23").unwrap();
+        assert_eq!(rule, Rule::conway());
+
+        let horizontal: HashSet<Cell> = [(0, 1), (1, 1), (2, 1)].into();
+        let vertical: HashSet<Cell> = [(1, 0), (1, 1), (1, 2)].into();
+
+        assert_eq!(next_generation(&horizontal, &rule), vertical);
+        assert_eq!(next_generation(&vertical, &rule), horizontal);
+        assert!(Rule::parse("B9/S23").is_err());
+    }
+}
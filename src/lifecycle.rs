@@ -0,0 +1,60 @@
+use serde::Deserialize;
+
+use crate::config::BehaviorConfig;
+
+// Part of an entity's life starting at `age` seconds, like "juvenile" or "adult".
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Stage {
+    pub age: f64,
+    // Tag the entity carries during the stage.
+    pub name: Option<String>,
+    pub size: Option<f64>,
+    // Replaces the entity's behavior on entering the stage.
+    pub behavior: Option<BehaviorConfig>,
+}
+
+// Stages an entity goes through as it ages, sorted by starting age.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lifecycle {
+    stages: Vec<Stage>,
+    current: Option<usize>,
+    // Index of the entity in its group, stage behaviors being built for it as at spawn.
+    slot: usize,
+}
+
+impl Lifecycle {
+    pub fn new(mut stages: Vec<Stage>) -> Lifecycle {
+        stages.sort_by(|a, b| a.age.total_cmp(&b.age));
+
+        Lifecycle {
+            stages,
+            current: None,
+            slot: 0,
+        }
+    }
+
+    pub fn with_slot(self, slot: usize) -> Lifecycle {
+        Lifecycle { slot, ..self }
+    }
+
+    pub fn slot(&self) -> usize {
+        self.slot
+    }
+
+    pub fn current(&self) -> Option<&Stage> {
+        self.current.map(|index| &self.stages[index])
+    }
+
+    // Moves on to the stage reached at `age` and returns it when it's a new one. Stages
+    // skipped over within one step are never entered.
+    pub fn advance(&mut self, age: f64) -> Option<&Stage> {
+        let reached = self.stages.iter().rposition(|stage| stage.age <= age);
+        if reached.is_none() || reached == self.current {
+            return None;
+        }
+
+        self.current = reached;
+        self.current()
+    }
+}
@@ -0,0 +1,115 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::Deserialize;
+
+use crate::spatial::NeighborQuery;
+
+// Spring between two entities by id, pulling them back to `rest_length` apart.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Link {
+    pub a: u32,
+    pub b: u32,
+    pub rest_length: f64,
+}
+
+// Entities linking up with those that come near into a deformable network: pairs closer
+// than `radius` link at the distance they are at, up to `max_links` each, and a link
+// stretched past `break_length` snaps.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Linking {
+    pub radius: f64,
+    pub max_links: usize,
+    // Acceleration per unit of stretch.
+    pub stiffness: f64,
+    pub break_length: f64,
+}
+
+impl Linking {
+    // The links left once those to a missing entity or stretched too far are dropped.
+    pub fn break_links<F>(&self, links: &[Link], position: F) -> Vec<Link>
+    where
+        F: Fn(u32) -> Option<[f64; 2]>,
+    {
+        links
+            .iter()
+            .filter(|link| match (position(link.a), position(link.b)) {
+                (Some(a), Some(b)) => (b[0] - a[0]).hypot(b[1] - a[1]) <= self.break_length,
+                _ => false,
+            })
+            .copied()
+            .collect()
+    }
+
+    // New links between pairs within the radius that aren't linked yet and both have
+    // room for one more, nearest pairs first. `index` is built over `positions`, which
+    // line up with `ids`.
+    pub fn form_links(
+        &self,
+        links: &[Link],
+        ids: &[u32],
+        positions: &[[f64; 2]],
+        index: &dyn NeighborQuery,
+    ) -> Vec<Link> {
+        let mut counts: HashMap<u32, usize> = HashMap::new();
+        let mut linked: HashSet<(u32, u32)> = HashSet::new();
+        for link in links {
+            *counts.entry(link.a).or_default() += 1;
+            *counts.entry(link.b).or_default() += 1;
+            linked.insert((link.a.min(link.b), link.a.max(link.b)));
+        }
+
+        let distance = |i: usize, j: usize| {
+            (positions[j][0] - positions[i][0]).hypot(positions[j][1] - positions[i][1])
+        };
+        let mut pairs: Vec<(usize, usize)> = Vec::new();
+        for (i, &point) in positions.iter().enumerate() {
+            for j in index.within_radius(point, self.radius) {
+                if i < j && distance(i, j) > 0.0 {
+                    pairs.push((i, j));
+                }
+            }
+        }
+        pairs.sort_by(|&(a, b), &(c, d)| {
+            distance(a, b)
+                .total_cmp(&distance(c, d))
+                .then((ids[a], ids[b]).cmp(&(ids[c], ids[d])))
+        });
+
+        let mut formed = Vec::new();
+        for (i, j) in pairs {
+            let (a, b) = (ids[i], ids[j]);
+            let key = (a.min(b), a.max(b));
+            let full = |id: u32, counts: &HashMap<u32, usize>| {
+                counts.get(&id).copied().unwrap_or(0) >= self.max_links
+            };
+
+            if linked.contains(&key) || full(a, &counts) || full(b, &counts) {
+                continue;
+            }
+
+            *counts.entry(a).or_default() += 1;
+            *counts.entry(b).or_default() += 1;
+            linked.insert(key);
+            formed.push(Link {
+                a,
+                b,
+                rest_length: distance(i, j),
+            });
+        }
+
+        formed
+    }
+
+    // Acceleration of the `a` end towards `b`, the `b` end getting the opposite.
+    pub fn acceleration(&self, link: &Link, a: [f64; 2], b: [f64; 2]) -> [f64; 2] {
+        let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+        let length = dx.hypot(dy);
+        if length <= f64::EPSILON {
+            return [0.0, 0.0];
+        }
+
+        let pull = self.stiffness * (length - link.rest_length) / length;
+        [dx * pull, dy * pull]
+    }
+}
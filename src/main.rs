@@ -1,239 +1,259 @@
+extern crate base;
 extern crate glutin_window;
-extern crate graphics;
 extern crate opengl_graphics;
 extern crate piston;
-extern crate rand;
-
-use glutin_window::GlutinWindow as Window;
-use graphics::{clear, ellipse, polygon, rectangle, Context, Transformed};
-use opengl_graphics::{GlGraphics, OpenGL};
-use piston::event_loop::{EventSettings, Events};
-use piston::input::{RenderArgs, RenderEvent, UpdateArgs, UpdateEvent};
-use piston::window::WindowSettings;
-use std::f64::consts::PI;
-
-pub struct App {
-    gl: GlGraphics, // OpenGL drawing backend.
-    entities: Vec<Entity>,
-    background_color: [f32; 4],
-}
-
-impl App {
-    fn render(&mut self, args: &RenderArgs) {
-        let background_color: [f32; 4] = self.background_color.clone();
 
-        self.gl.draw(args.viewport(), |_c, gl| {
-            // Clear the screen.
-            clear(background_color, gl);
-        });
+use std::fs;
+use std::process;
+
+use base::app::App;
+use base::config::{
+    build_window, entropy_seed, parse_opengl, window_warning, Config, ConfigError, Seed,
+    WindowOptions, DEFAULT_OPENGL, DEFAULT_UPS,
+};
+use base::error::NatureError;
+use base::generator::{gaussian_dots_world, Gaussian};
+use base::headless::run_headless;
+use base::render::overlay_order;
+use base::stats::METRICS_CSV_HEADER;
+use base::svg::{trajectories_svg, Trajectories};
+use base::world::World;
+use opengl_graphics::GlGraphics;
+use piston::event_loop::{EventLoop, EventSettings, Events};
+use piston::input::{
+    Button, FocusEvent, MouseButton, MouseCursorEvent, PressEvent, ReleaseEvent, RenderEvent,
+    UpdateEvent,
+};
 
-        for entity in self.entities.iter() {
-            self.gl.draw(args.viewport(), |c, gl| {
-                // Clear the screen.
-                entity.renderer.draw(gl, c, entity.physics.clone());
-            });
-        }
+fn main() {
+    if let Err(error) = run() {
+        eprintln!("error: {}", error);
+        process::exit(1);
     }
+}
 
-    fn update(&mut self, _args: &UpdateArgs) {
-        for entity in self.entities.iter_mut() {
-            match &entity.behavior {
-                Some(_behavior) => {
-                    let ai = entity.behavior.take().unwrap();
+// Command line arguments,
+// `[--gl VERSION] [--vsync] [--fullscreen] [--seed N]
+// [[--headless] --steps N --out PATH [--svg PATH] [--csv PATH]] [CONFIG]`.
+struct Args {
+    gl: Option<String>,
+    vsync: bool,
+    fullscreen: bool,
+    config: Option<String>,
+    // Seed of the scene, over the one of the config.
+    seed: Option<u64>,
+    // Simulate without a window and write the final entities to `out`. Without it, the
+    // steps and output still make a headless run when no window can be opened.
+    headless: bool,
+    steps: Option<u64>,
+    out: Option<String>,
+    // Where a headless run also draws the path of every entity.
+    svg: Option<String>,
+    // Where a headless run also writes a row of metrics per step.
+    csv: Option<String>,
+}
 
-                    ai.apply_behavior(entity);
-                    entity.behavior = Some(Box::new(Walker {}));
+fn parse_args() -> Result<Args, NatureError> {
+    let mut args = Args {
+        gl: None,
+        vsync: false,
+        fullscreen: false,
+        config: None,
+        seed: None,
+        headless: false,
+        steps: None,
+        out: None,
+        svg: None,
+        csv: None,
+    };
+    let mut words = std::env::args().skip(1);
+
+    while let Some(word) = words.next() {
+        match word.as_str() {
+            "--gl" => match words.next() {
+                Some(version) => args.gl = Some(version),
+                None => {
+                    return Err(NatureError::InvalidParameter(
+                        "--gl needs a version such as 3.2".to_string(),
+                    ))
+                }
+            },
+            "--vsync" => args.vsync = true,
+            "--fullscreen" => args.fullscreen = true,
+            "--seed" => match words.next().and_then(|seed| seed.parse().ok()) {
+                Some(seed) => args.seed = Some(seed),
+                None => {
+                    return Err(NatureError::InvalidParameter(
+                        "--seed needs a number".to_string(),
+                    ))
+                }
+            },
+            "--headless" => args.headless = true,
+            "--steps" => match words.next().and_then(|steps| steps.parse().ok()) {
+                Some(steps) => args.steps = Some(steps),
+                None => {
+                    return Err(NatureError::InvalidParameter(
+                        "--steps needs a number of steps".to_string(),
+                    ))
                 }
-                None => {}
-            }
+            },
+            "--out" => match words.next() {
+                Some(path) => args.out = Some(path),
+                None => {
+                    return Err(NatureError::InvalidParameter(
+                        "--out needs a file path".to_string(),
+                    ))
+                }
+            },
+            "--svg" => match words.next() {
+                Some(path) => args.svg = Some(path),
+                None => {
+                    return Err(NatureError::InvalidParameter(
+                        "--svg needs a file path".to_string(),
+                    ))
+                }
+            },
+            "--csv" => match words.next() {
+                Some(path) => args.csv = Some(path),
+                None => {
+                    return Err(NatureError::InvalidParameter(
+                        "--csv needs a file path".to_string(),
+                    ))
+                }
+            },
+            _ => args.config = Some(word),
         }
     }
-}
 
-struct Entity {
-    physics: Physics,
-    renderer: Box<dyn Drawable>,
-    behavior: Option<Box<dyn AI>>,
-    _id: u32,
+    Ok(args)
 }
 
-#[derive(Clone, Debug, Copy, PartialEq)]
-struct Physics {
-    x: f64,
-    y: f64,
-    size: f64,
-    rotation: f64,
-}
-
-trait Drawable {
-    fn draw(&self, gl: &mut GlGraphics, c: Context, physics: Physics);
-}
-
-struct Square {
-    color: [f32; 4],
-}
-
-impl Drawable for Square {
-    fn draw(&self, gl: &mut GlGraphics, c: Context, physics: Physics) {
-        let square = rectangle::square(0.0, 0.0, physics.size);
-        let x = physics.x - physics.size / 2.0;
-        let y = physics.y - physics.size / 2.0;
-        let transform = c.transform.trans(x, y).rot_rad(physics.rotation);
-
-        rectangle(self.color.clone(), square, transform, gl);
+fn run() -> Result<(), NatureError> {
+    let args = parse_args()?;
+
+    // An optional config file describes the scene, otherwise use the gaussian dots.
+    let mut config = args
+        .config
+        .as_ref()
+        .map(|path| Config::load(path))
+        .transpose()?;
+    if let (Some(config), Some(seed)) = (config.as_mut(), args.seed) {
+        config.seed = Some(Seed::Fixed(seed));
+    }
+    // The default scene is always seeded, from the system unless given one, so any run of
+    // it can be replayed.
+    let seed = match config.as_mut() {
+        Some(config) => config.fix_seed(),
+        None => Some(args.seed.unwrap_or_else(entropy_seed)),
+    };
+    if let Some(seed) = seed {
+        eprintln!("seed: {}", seed);
     }
-}
 
-struct Arrow {
-    color: [f32; 4],
-}
+    let gl = args
+        .gl
+        .clone()
+        .or_else(|| config.as_ref().and_then(|config| config.gl.clone()));
+    let opengl = match gl {
+        Some(version) => parse_opengl(&version)?,
+        None => DEFAULT_OPENGL,
+    };
 
-impl Drawable for Arrow {
-    fn draw(&self, gl: &mut GlGraphics, c: Context, physics: Physics) {
-        let x = physics.x;
-        let y = physics.y - physics.size / 2.0;
-        let transform = c.transform.trans(x, y).rot_rad(physics.rotation);
-
-        polygon(
-            self.color.clone(),
-            &[
-                [0.0, -physics.size / 2.0],
-                [-physics.size / 3.0, physics.size / 2.0],
-                [physics.size / 3.0, physics.size / 2.0],
-            ],
-            transform,
-            gl,
-        );
-    }
-}
+    let (width, height, background_color, ups) = match &config {
+        Some(config) => (
+            config.width,
+            config.height,
+            config.background_color,
+            config.updates_per_second(),
+        ),
+        None => (800, 500, [0.0, 1.0, 0.0, 1.0], DEFAULT_UPS),
+    };
 
-struct Circle {
-    color: [f32; 4],
-}
+    let world = match &config {
+        Some(config) => config.build_world()?,
+        None => gaussian_dots_world(300, width, height, seed)?,
+    };
 
-impl Drawable for Circle {
-    fn draw(&self, gl: &mut GlGraphics, c: Context, physics: Physics) {
-        let square = rectangle::square(0.0, 0.0, physics.size);
-        let x = physics.x - physics.size / 2.0;
-        let y = physics.y - physics.size / 2.0;
-        let transform = c.transform.trans(x, y);
+    if args.headless {
+        return headless(world, args, ups);
+    }
 
-        ellipse(self.color.clone(), square, transform, gl);
+    let options = WindowOptions {
+        vsync: args.vsync || config.as_ref().is_some_and(|config| config.vsync),
+        fullscreen: args.fullscreen || config.as_ref().is_some_and(|config| config.fullscreen),
+    };
+    let configured_size = config.as_ref().map(|config| [config.width, config.height]);
+    if let Some(warning) = window_warning(options, configured_size) {
+        eprintln!("warning: {}", warning);
     }
-}
 
-trait AI {
-    fn apply_behavior(&self, entity: &mut Entity);
-}
+    // Create an Glutin window, or fall back to a headless run when one was described.
+    let mut window = match build_window("Window", [width, height], opengl, options) {
+        Ok(window) => window,
+        Err(error) if args.steps.is_some() && args.out.is_some() => {
+            eprintln!("warning: {}, running headless instead", error);
+            return headless(world, args, ups);
+        }
+        Err(error) => return Err(error),
+    };
 
-struct Walker {}
-
-impl AI for Walker {
-    fn apply_behavior(&self, entity: &mut Entity) {
-        use rand::Rng;
-        use rand_distr::{Distribution, Normal};
-        let normal = Normal::new(2.0, 1.0).unwrap();
-
-        let mut rng = rand::thread_rng();
-        let random_x_direction: i16 = rng.gen_range(-1..2);
-        let random_y_direction: i16 = rng.gen_range(-1..2);
-        let speed: f64 = normal.sample(&mut rand::thread_rng());
-        entity.physics.x += random_x_direction as f64 * speed;
-        entity.physics.y += random_y_direction as f64 * speed;
-
-        match random_x_direction {
-            -1 => match random_y_direction {
-                -1 => {
-                    entity.physics.rotation = -PI / 4.0;
-                }
-                1 => {
-                    entity.physics.rotation = -3.0 * PI / 4.0;
-                }
-                _ => {
-                    entity.physics.rotation = -PI / 2.0;
-                }
-            },
-            1 => match random_y_direction {
-                -1 => {
-                    entity.physics.rotation = PI / 4.0;
-                }
-                1 => {
-                    entity.physics.rotation = 3.0 * PI / 4.0;
-                }
-                _ => {
-                    entity.physics.rotation = PI / 2.0;
-                }
-            },
-            _ => match random_y_direction {
-                -1 => {
-                    entity.physics.rotation = 0.0;
-                }
-                1 => {
-                    entity.physics.rotation = PI;
-                }
-                _ => {}
-            },
+    // Create a new game and run it.
+    let mut app = App::new(GlGraphics::new(opengl), world, background_color);
+    // Every scene is spawned around the center, which moves with the coordinate system.
+    let (world_width, world_height) = (app.world.width, app.world.height);
+    app.distribution = Some(Gaussian {
+        mean: app.world.coordinates.center(world_width, world_height),
+        ..Gaussian::spawn(world_width, world_height)
+    });
+    app.pause_on_focus_loss = config
+        .as_ref()
+        .is_some_and(|config| config.pause_on_focus_loss);
+
+    if let Some(config) = config.as_ref() {
+        app.highlight = config.highlight;
+        app.batch_threshold = config.batch_threshold;
+        app.downsample = config.downsample;
+        app.shadow = config.shadow;
+        if let Some(connections) = config.connections {
+            app.connections = connections;
+            app.show_connections = true;
         }
+        app.interpolate = config.interpolate;
     }
-}
 
-fn color_generator(x: f32, y: f32, width: u32, height: u32) -> [f32; 4] {
-    let red = x / width as f32;
-    let green = y / height as f32;
-    // let blue = (x + y) / (height as f32 + width as f32);
+    if let Some(overlays) = config.as_ref().and_then(|config| config.overlays.as_ref()) {
+        app.overlays = overlay_order(overlays);
+    }
 
-    [red, green, 0.0, 1.0]
-}
+    if let Some(spacing) = config.as_ref().and_then(|config| config.paint_spacing) {
+        if !(spacing.is_finite() && spacing > 0.0) {
+            return Err(ConfigError::Invalid(format!(
+                "paint_spacing must be positive, got {}",
+                spacing
+            ))
+            .into());
+        }
 
-fn gaussian_dots_generator(size: usize, width: u32, height: u32) -> Vec<Entity> {
-    use rand_distr::{Distribution, Normal};
-    let mut entities: Vec<Entity> = Vec::with_capacity(size);
-    let y_normal = Normal::new(height as f64 / 2.0, height as f64 / 6.0).unwrap();
-    let x_normal = Normal::new(width as f64 / 2.0, width as f64 / 6.0).unwrap();
-
-    for id in 0..300 {
-        let x = x_normal.sample(&mut rand::thread_rng());
-        let y = y_normal.sample(&mut rand::thread_rng());
-
-        entities.push(Entity {
-            physics: Physics {
-                x,
-                y,
-                size: 10.0,
-                rotation: 0.0,
-            },
-            renderer: Box::new(Circle {
-                color: color_generator(x as f32, y as f32, width, height),
-            }),
-            behavior: Some(Box::new(Walker {})),
-            _id: id,
-        });
+        app.paint_spacing = spacing;
     }
 
-    entities
-}
+    if let Some(spacing) = config.as_ref().and_then(|config| config.axis_spacing) {
+        if !(spacing.is_finite() && spacing > 0.0) {
+            return Err(ConfigError::Invalid(format!(
+                "axis_spacing must be positive, got {}",
+                spacing
+            ))
+            .into());
+        }
 
-fn main() {
-    // Change this to OpenGL::V2_1 if not working.
-    let opengl = OpenGL::V3_2;
-    let height = 500;
-    let width = 800;
-
-    // Create an Glutin window.
-    let mut window: Window = WindowSettings::new("Window", [width, height])
-        .graphics_api(opengl)
-        .exit_on_esc(true)
-        .build()
-        .unwrap();
+        app.axis_spacing = spacing;
+    }
 
-    // Create a new game and run it.
-    let mut app = App {
-        gl: GlGraphics::new(opengl),
-        entities: gaussian_dots_generator(300, width, height),
-        background_color: [0.0, 1.0, 0.0, 1.0],
-    };
+    if let Some(font) = config.as_ref().and_then(|config| config.font.as_ref()) {
+        app.load_font(font)?;
+    }
 
-    let mut events = Events::new(EventSettings::new());
+    let mut events = Events::new(EventSettings::new().ups(ups));
     while let Some(e) = events.next(&mut window) {
         if let Some(args) = e.render_args() {
             app.render(&args);
@@ -242,5 +262,81 @@ fn main() {
         if let Some(args) = e.update_args() {
             app.update(&args);
         }
+
+        if let Some(focused) = e.focus_args() {
+            app.focus_changed(focused);
+        }
+
+        if let Some(position) = e.mouse_cursor_args() {
+            app.cursor_moved(position);
+        }
+
+        match e.press_args() {
+            Some(Button::Mouse(MouseButton::Left)) => app.mouse_pressed(),
+            Some(Button::Mouse(MouseButton::Right)) => app.set_goal(),
+            Some(Button::Mouse(MouseButton::Middle)) => app.paint_pressed(),
+            Some(Button::Keyboard(key)) => app.key_pressed(key),
+            _ => {}
+        }
+
+        match e.release_args() {
+            Some(Button::Mouse(MouseButton::Left)) => app.mouse_released(),
+            Some(Button::Mouse(MouseButton::Middle)) => app.paint_released(),
+            _ => {}
+        }
     }
+
+    Ok(())
+}
+
+// Runs the simulation at the fixed physics rate and writes the final state as JSON, and
+// the trajectories as SVG and per-step metrics as CSV when asked to.
+fn headless(mut world: World, args: Args, ups: u64) -> Result<(), NatureError> {
+    let (steps, out) = match (args.steps, args.out) {
+        (Some(steps), Some(out)) => (steps, out),
+        _ => {
+            return Err(NatureError::InvalidParameter(
+                "--headless needs --steps N and --out PATH".to_string(),
+            ))
+        }
+    };
+
+    let mut trajectories = Trajectories::new();
+    let recording = args.svg.as_ref().map(|_| &mut trajectories);
+    let mut metrics = Vec::new();
+    let measuring = args.csv.as_ref().map(|_| &mut metrics);
+
+    let saved = run_headless(
+        &mut world,
+        steps,
+        1.0 / ups as f64,
+        recording,
+        measuring,
+        |step| eprintln!("step {}/{}", step, steps),
+    );
+
+    fs::write(&out, saved.to_json())
+        .map_err(|error| NatureError::Save(format!("{}: {}", out, error)))?;
+    eprintln!("wrote {} entities to {}", saved.entities.len(), out);
+
+    if let Some(path) = args.svg {
+        let svg = trajectories_svg(&trajectories, world.width, world.height, world.coordinates);
+
+        fs::write(&path, svg).map_err(|error| NatureError::Save(format!("{}: {}", path, error)))?;
+        eprintln!("wrote {} trajectories to {}", trajectories.len(), path);
+    }
+
+    if let Some(path) = args.csv {
+        let mut csv = String::from(METRICS_CSV_HEADER);
+        for row in metrics.iter() {
+            csv.push('\n');
+            csv.push_str(&row.csv_row());
+        }
+        csv.push('\n');
+
+        fs::write(&path, csv).map_err(|error| NatureError::Save(format!("{}: {}", path, error)))?;
+        eprintln!("wrote {} rows of metrics to {}", metrics.len(), path);
+    }
+
+    Ok(())
 }
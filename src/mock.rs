@@ -0,0 +1,134 @@
+use graphics::math::Matrix2d;
+use graphics::types::{Color, Polygon, Rectangle};
+use graphics::{DrawState, Ellipse, Graphics, ImageSize};
+
+// Records the primitives a drawable emits, for checking draw logic without a GL context.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCall {
+    Rectangle {
+        color: Color,
+        rectangle: Rectangle,
+        transform: Matrix2d,
+    },
+    Polygon {
+        color: Color,
+        vertices: Vec<[f64; 2]>,
+        transform: Matrix2d,
+    },
+    Ellipse {
+        color: Color,
+        rectangle: Rectangle,
+        transform: Matrix2d,
+    },
+    // Per-vertex colored triangles, already transformed.
+    Triangles {
+        vertices: Vec<[f32; 2]>,
+        colors: Vec<[f32; 4]>,
+    },
+}
+
+pub struct NoTexture;
+
+impl ImageSize for NoTexture {
+    fn get_size(&self) -> (u32, u32) {
+        (0, 0)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct MockGraphics {
+    pub calls: Vec<DrawCall>,
+}
+
+impl MockGraphics {
+    pub fn new() -> MockGraphics {
+        MockGraphics { calls: Vec::new() }
+    }
+}
+
+impl Graphics for MockGraphics {
+    type Texture = NoTexture;
+
+    fn clear_color(&mut self, _color: Color) {}
+
+    fn clear_stencil(&mut self, _value: u8) {}
+
+    fn tri_list<F>(&mut self, _draw_state: &DrawState, _color: &[f32; 4], _f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]])),
+    {
+    }
+
+    fn tri_list_c<F>(&mut self, _draw_state: &DrawState, mut f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 4]])),
+    {
+        let mut vertices = Vec::new();
+        let mut colors = Vec::new();
+
+        f(&mut |v, c| {
+            vertices.extend_from_slice(v);
+            colors.extend_from_slice(c);
+        });
+        self.calls.push(DrawCall::Triangles { vertices, colors });
+    }
+
+    fn tri_list_uv<F>(
+        &mut self,
+        _draw_state: &DrawState,
+        _color: &[f32; 4],
+        _texture: &NoTexture,
+        _f: F,
+    ) where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]])),
+    {
+    }
+
+    fn tri_list_uv_c<F>(&mut self, _draw_state: &DrawState, _texture: &NoTexture, _f: F)
+    where
+        F: FnMut(&mut dyn FnMut(&[[f32; 2]], &[[f32; 2]], &[[f32; 4]])),
+    {
+    }
+
+    fn rectangle<R: Into<Rectangle>>(
+        &mut self,
+        r: &graphics::Rectangle,
+        rectangle: R,
+        _draw_state: &DrawState,
+        transform: Matrix2d,
+    ) {
+        self.calls.push(DrawCall::Rectangle {
+            color: r.color,
+            rectangle: rectangle.into(),
+            transform,
+        });
+    }
+
+    fn polygon(
+        &mut self,
+        p: &graphics::Polygon,
+        polygon: Polygon,
+        _draw_state: &DrawState,
+        transform: Matrix2d,
+    ) {
+        self.calls.push(DrawCall::Polygon {
+            color: p.color,
+            vertices: polygon.to_vec(),
+            transform,
+        });
+    }
+
+    fn ellipse<R: Into<Rectangle>>(
+        &mut self,
+        e: &Ellipse,
+        rectangle: R,
+        _draw_state: &DrawState,
+        transform: Matrix2d,
+    ) {
+        self.calls.push(DrawCall::Ellipse {
+            color: e.color,
+            rectangle: rectangle.into(),
+            transform,
+        });
+    }
+}
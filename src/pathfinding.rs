@@ -0,0 +1,218 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use crate::error::NatureError;
+
+// Column and row of a grid cell.
+pub type GridCell = (usize, usize);
+
+// Grid of open and blocked cells paths are searched over, row by row from `origin`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NavGrid {
+    // Corner of the first cell, the one with the smallest coordinates.
+    pub origin: [f64; 2],
+    pub cell_size: f64,
+    pub columns: usize,
+    pub rows: usize,
+    blocked: Vec<bool>,
+}
+
+impl NavGrid {
+    // `blocked` cells are `[column, row]`.
+    pub fn new(
+        origin: [f64; 2],
+        cell_size: f64,
+        columns: usize,
+        rows: usize,
+        blocked: &[[usize; 2]],
+    ) -> Result<NavGrid, NatureError> {
+        if !(cell_size.is_finite() && cell_size > 0.0) {
+            return Err(NatureError::InvalidParameter(format!(
+                "navigation cells need a positive size, got {}",
+                cell_size
+            )));
+        }
+        if columns == 0 || rows == 0 {
+            return Err(NatureError::InvalidParameter(format!(
+                "navigation grid needs at least one cell, got {}x{}",
+                columns, rows
+            )));
+        }
+
+        let mut grid = NavGrid {
+            origin,
+            cell_size,
+            columns,
+            rows,
+            blocked: vec![false; columns * rows],
+        };
+        for &[column, row] in blocked {
+            if column >= columns || row >= rows {
+                return Err(NatureError::InvalidParameter(format!(
+                    "blocked cell {:?} is outside the {}x{} navigation grid",
+                    [column, row],
+                    columns,
+                    rows
+                )));
+            }
+            grid.blocked[row * columns + column] = true;
+        }
+
+        Ok(grid)
+    }
+
+    // Column and row of the cell under `point`, None outside the grid.
+    pub fn cell_of(&self, point: [f64; 2]) -> Option<GridCell> {
+        let column = ((point[0] - self.origin[0]) / self.cell_size).floor();
+        let row = ((point[1] - self.origin[1]) / self.cell_size).floor();
+
+        if column < 0.0 || row < 0.0 || column >= self.columns as f64 || row >= self.rows as f64 {
+            return None;
+        }
+
+        Some((column as usize, row as usize))
+    }
+
+    pub fn center_of(&self, (column, row): GridCell) -> [f64; 2] {
+        [
+            self.origin[0] + (column as f64 + 0.5) * self.cell_size,
+            self.origin[1] + (row as f64 + 0.5) * self.cell_size,
+        ]
+    }
+
+    pub fn is_open(&self, (column, row): GridCell) -> bool {
+        column < self.columns && row < self.rows && !self.blocked[row * self.columns + column]
+    }
+
+    // Open neighbors of `cell` with the cost of stepping there. Diagonals cost √2 and
+    // never cut the corner of a blocked cell.
+    fn steps(&self, (column, row): GridCell) -> Vec<(GridCell, f64)> {
+        let open = |dc: i64, dr: i64| {
+            let (c, r) = (column as i64 + dc, row as i64 + dr);
+            if c < 0 || r < 0 {
+                return None;
+            }
+            Some((c as usize, r as usize)).filter(|&cell| self.is_open(cell))
+        };
+        let mut steps = Vec::new();
+
+        for dc in -1..=1 {
+            for dr in -1..=1 {
+                if dc == 0 && dr == 0 {
+                    continue;
+                }
+                let next = match open(dc, dr) {
+                    Some(next) => next,
+                    None => continue,
+                };
+                if dc != 0 && dr != 0 {
+                    if open(dc, 0).is_none() || open(0, dr).is_none() {
+                        continue;
+                    }
+                    steps.push((next, std::f64::consts::SQRT_2));
+                } else {
+                    steps.push((next, 1.0));
+                }
+            }
+        }
+
+        steps
+    }
+}
+
+// Heap entry, ordered so the lowest estimate pops first.
+#[derive(PartialEq)]
+struct Visit {
+    estimate: f64,
+    cell: GridCell,
+}
+
+impl Eq for Visit {}
+
+impl Ord for Visit {
+    fn cmp(&self, other: &Visit) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+impl PartialOrd for Visit {
+    fn partial_cmp(&self, other: &Visit) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Octile distance, the exact path length on an empty grid.
+fn heuristic(a: GridCell, b: GridCell) -> f64 {
+    let dx = a.0.abs_diff(b.0) as f64;
+    let dy = a.1.abs_diff(b.1) as f64;
+
+    dx.max(dy) + (std::f64::consts::SQRT_2 - 1.0) * dx.min(dy)
+}
+
+// Shortest path of cells from `start` to `goal`, both included, by A*. None when either
+// is blocked or the goal can't be reached.
+pub fn astar(grid: &NavGrid, start: GridCell, goal: GridCell) -> Option<Vec<GridCell>> {
+    if !grid.is_open(start) || !grid.is_open(goal) {
+        return None;
+    }
+
+    let index = |(column, row): GridCell| row * grid.columns + column;
+    let mut distances = vec![f64::INFINITY; grid.columns * grid.rows];
+    let mut came_from: Vec<Option<GridCell>> = vec![None; grid.columns * grid.rows];
+    let mut open = BinaryHeap::new();
+
+    distances[index(start)] = 0.0;
+    open.push(Visit {
+        estimate: heuristic(start, goal),
+        cell: start,
+    });
+
+    while let Some(Visit { estimate, cell }) = open.pop() {
+        if cell == goal {
+            let mut path = vec![goal];
+            while let Some(previous) = came_from[index(*path.last()?)] {
+                path.push(previous);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if estimate > distances[index(cell)] + heuristic(cell, goal) {
+            continue;
+        }
+
+        for (next, cost) in grid.steps(cell) {
+            let distance = distances[index(cell)] + cost;
+            if distance < distances[index(next)] {
+                distances[index(next)] = distance;
+                came_from[index(next)] = Some(cell);
+                open.push(Visit {
+                    estimate: distance + heuristic(next, goal),
+                    cell: next,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn astar_goes_around_a_wall_without_cutting_its_corners() {
+        let open = NavGrid::new([0.0, 0.0], 10.0, 3, 2, &[]).unwrap();
+        let walled = NavGrid::new([0.0, 0.0], 10.0, 3, 2, &[[1, 0]]).unwrap();
+
+        assert_eq!(
+            astar(&open, (0, 0), (2, 0)),
+            Some(vec![(0, 0), (1, 0), (2, 0)])
+        );
+        assert_eq!(
+            astar(&walled, (0, 0), (2, 0)),
+            Some(vec![(0, 0), (0, 1), (1, 1), (2, 1), (2, 0)])
+        );
+        assert_eq!(astar(&walled, (0, 0), (1, 0)), None);
+    }
+}
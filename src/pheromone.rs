@@ -0,0 +1,143 @@
+use crate::error::NatureError;
+
+// Grid of pheromone laid by entities, row by row from `origin`. Every cell fades at the
+// same rate, so trails only last while they keep being walked.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PheromoneGrid {
+    // Corner of the first cell, the one with the smallest coordinates.
+    pub origin: [f64; 2],
+    pub cell_size: f64,
+    pub columns: usize,
+    pub rows: usize,
+    // Exponential fade per second, a cell keeping e^-decay of its value after one.
+    pub decay: f64,
+    values: Vec<f64>,
+}
+
+impl PheromoneGrid {
+    pub fn new(
+        origin: [f64; 2],
+        cell_size: f64,
+        columns: usize,
+        rows: usize,
+        decay: f64,
+    ) -> Result<PheromoneGrid, NatureError> {
+        if !(cell_size.is_finite() && cell_size > 0.0) {
+            return Err(NatureError::InvalidParameter(format!(
+                "pheromone cells need a positive size, got {}",
+                cell_size
+            )));
+        }
+        if columns == 0 || rows == 0 {
+            return Err(NatureError::InvalidParameter(format!(
+                "pheromone grid needs at least one cell, got {}x{}",
+                columns, rows
+            )));
+        }
+        if !(decay.is_finite() && decay >= 0.0) {
+            return Err(NatureError::InvalidParameter(format!(
+                "pheromone decay must be non-negative, got {}",
+                decay
+            )));
+        }
+
+        Ok(PheromoneGrid {
+            origin,
+            cell_size,
+            columns,
+            rows,
+            decay,
+            values: vec![0.0; columns * rows],
+        })
+    }
+
+    // Column and row of the cell under `point`, None outside the grid.
+    pub fn cell_of(&self, point: [f64; 2]) -> Option<(usize, usize)> {
+        let column = ((point[0] - self.origin[0]) / self.cell_size).floor();
+        let row = ((point[1] - self.origin[1]) / self.cell_size).floor();
+
+        if column < 0.0 || row < 0.0 || column >= self.columns as f64 || row >= self.rows as f64 {
+            return None;
+        }
+
+        Some((column as usize, row as usize))
+    }
+
+    fn value(&self, column: usize, row: usize) -> f64 {
+        self.values[row * self.columns + column]
+    }
+
+    // Adds `amount` to the cell under `point`, nothing outside the grid.
+    pub fn deposit(&mut self, point: [f64; 2], amount: f64) {
+        if let Some((column, row)) = self.cell_of(point) {
+            self.values[row * self.columns + column] += amount;
+        }
+    }
+
+    pub fn fade(&mut self, dt: f64) {
+        let keep = (-self.decay * dt).exp();
+
+        for value in self.values.iter_mut() {
+            *value *= keep;
+        }
+    }
+
+    // Pheromone in the cell under `point`, 0 outside the grid.
+    pub fn sample(&self, point: [f64; 2]) -> f64 {
+        self.cell_of(point)
+            .map_or(0.0, |(column, row)| self.value(column, row))
+    }
+
+    // Direction of increase per world unit from the neighboring cells, one-sided along
+    // the edges. Zero outside the grid.
+    pub fn gradient(&self, point: [f64; 2]) -> [f64; 2] {
+        let (column, row) = match self.cell_of(point) {
+            Some(cell) => cell,
+            None => return [0.0, 0.0],
+        };
+        let (left, right) = (column.saturating_sub(1), (column + 1).min(self.columns - 1));
+        let (up, down) = (row.saturating_sub(1), (row + 1).min(self.rows - 1));
+        let slope = |low: f64, high: f64, cells: usize| {
+            if cells == 0 {
+                0.0
+            } else {
+                (high - low) / (cells as f64 * self.cell_size)
+            }
+        };
+
+        [
+            slope(self.value(left, row), self.value(right, row), right - left),
+            slope(self.value(column, up), self.value(column, down), down - up),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{Entity, Physics, Renderer};
+    use crate::shapes::Circle;
+    use crate::world::World;
+
+    #[test]
+    fn deposits_fade_as_the_world_steps() {
+        let mut grid = PheromoneGrid::new([0.0, 0.0], 10.0, 10, 10, 2.0).unwrap();
+        grid.deposit([25.0, 25.0], 3.0);
+        grid.deposit([25.0, 25.0], 1.0);
+        grid.deposit([-5.0, 25.0], 1.0);
+        assert_eq!(grid.sample([21.0, 29.0]), 4.0);
+        assert_eq!(grid.sample([35.0, 25.0]), 0.0);
+
+        let bystander = Entity::new(
+            0,
+            Physics::new(80.0, 80.0, 2.0).unwrap(),
+            Renderer::Owned(Box::new(Circle::new([1.0; 4]))),
+        );
+        let mut world = World::new(vec![bystander], 100.0, 100.0).unwrap();
+        world.pheromones = Some(grid);
+        world.step(0.5);
+
+        let left = world.pheromones.as_ref().unwrap().sample([25.0, 25.0]);
+        assert!((left - 4.0 * (-1.0f64).exp()).abs() < 1e-12);
+    }
+}
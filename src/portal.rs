@@ -0,0 +1,116 @@
+use serde::Deserialize;
+
+use crate::entity::Physics;
+
+// Circular mouth of a portal, facing `angle` radians.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Portal {
+    pub center: [f64; 2],
+    pub radius: f64,
+    #[serde(default)]
+    pub angle: f64,
+}
+
+impl Portal {
+    pub fn contains(&self, point: [f64; 2]) -> bool {
+        let dx = point[0] - self.center[0];
+        let dy = point[1] - self.center[1];
+
+        dx * dx + dy * dy <= self.radius * self.radius
+    }
+}
+
+// Two linked portals, entering either takes an entity out of the other.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PortalPair {
+    pub a: Portal,
+    pub b: Portal,
+    // Turns the velocity by the difference of the portals' angles, otherwise it's kept.
+    #[serde(default)]
+    pub rotate: bool,
+}
+
+impl PortalPair {
+    // Sends an entity that moved into one portal since `previous` out of the other, just
+    // clear of its edge along the new velocity so it doesn't go straight back. Returns
+    // whether it went through.
+    pub fn teleport(&self, physics: &mut Physics, previous: [f64; 2]) -> bool {
+        let point = [physics.x, physics.y];
+        let (entry, exit) = if self.a.contains(point) && !self.a.contains(previous) {
+            (self.a, self.b)
+        } else if self.b.contains(point) && !self.b.contains(previous) {
+            (self.b, self.a)
+        } else {
+            return false;
+        };
+
+        if self.rotate {
+            let (sin, cos) = (exit.angle - entry.angle).sin_cos();
+            let (vx, vy) = (physics.vx, physics.vy);
+
+            physics.vx = vx * cos - vy * sin;
+            physics.vy = vx * sin + vy * cos;
+        }
+
+        let speed = (physics.vx * physics.vx + physics.vy * physics.vy).sqrt();
+        let direction = if speed > f64::EPSILON {
+            [physics.vx / speed, physics.vy / speed]
+        } else {
+            [exit.angle.cos(), exit.angle.sin()]
+        };
+        let distance = exit.radius + physics.size / 2.0;
+
+        physics.x = exit.center[0] + direction[0] * distance;
+        physics.y = exit.center[1] + direction[1] * distance;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use super::*;
+
+    #[test]
+    fn teleport_moves_out_of_the_other_portal_and_turns_the_velocity() {
+        let mut pair = PortalPair {
+            a: Portal {
+                center: [10.0, 10.0],
+                radius: 2.0,
+                angle: 0.0,
+            },
+            b: Portal {
+                center: [80.0, 50.0],
+                radius: 2.0,
+                angle: FRAC_PI_2,
+            },
+            rotate: true,
+        };
+        let entering = || {
+            let mut physics = Physics::new(9.0, 10.0, 2.0).unwrap();
+            physics.vx = 3.0;
+            physics
+        };
+
+        let mut physics = entering();
+        assert!(pair.teleport(&mut physics, [7.0, 10.0]));
+        assert!(physics.vx.abs() < 1e-12 && (physics.vy - 3.0).abs() < 1e-12);
+        assert!((physics.x - 80.0).abs() < 1e-12 && (physics.y - 53.0).abs() < 1e-12);
+
+        pair.rotate = false;
+        let mut physics = entering();
+        assert!(pair.teleport(&mut physics, [7.0, 10.0]));
+        assert_eq!(
+            [physics.x, physics.y, physics.vx, physics.vy],
+            [83.0, 50.0, 3.0, 0.0]
+        );
+
+        // Already inside last step, it isn't sent back.
+        let mut physics = entering();
+        assert!(!pair.teleport(&mut physics, [10.0, 10.0]));
+        assert_eq!([physics.x, physics.y], [9.0, 10.0]);
+    }
+}
@@ -0,0 +1,89 @@
+use serde::Deserialize;
+
+use crate::entity::Physics;
+use crate::world::{enforce_circle, enforce_rect, BoundaryMode};
+
+// Area entities can be confined to, in world coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Region {
+    Rect { min: [f64; 2], max: [f64; 2] },
+    Circle { center: [f64; 2], radius: f64 },
+}
+
+impl Region {
+    pub fn contains(&self, point: [f64; 2]) -> bool {
+        match *self {
+            Region::Rect { min, max } => {
+                point[0] >= min[0] && point[0] <= max[0] && point[1] >= min[1] && point[1] <= max[1]
+            }
+            Region::Circle { center, radius } => {
+                (point[0] - center[0]).hypot(point[1] - center[1]) <= radius
+            }
+        }
+    }
+
+    // Bounces the entity back inside.
+    pub fn confine(&self, physics: &mut Physics) {
+        match *self {
+            Region::Rect { min, max } => enforce_rect(physics, BoundaryMode::Bounce, (min, max)),
+            Region::Circle { center, radius } => {
+                enforce_circle(physics, BoundaryMode::Bounce, center, radius)
+            }
+        }
+    }
+}
+
+// Circle where region walls are open, so entities cross between the regions it spans.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Opening {
+    pub center: [f64; 2],
+    pub radius: f64,
+}
+
+impl Opening {
+    pub fn contains(&self, point: [f64; 2]) -> bool {
+        (point[0] - self.center[0]).hypot(point[1] - self.center[1]) <= self.radius
+    }
+}
+
+// Arena made of several regions, each entity kept in the one it's in and only moving to
+// another through an opening.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RegionMap {
+    pub regions: Vec<Region>,
+    #[serde(default)]
+    pub openings: Vec<Opening>,
+}
+
+impl RegionMap {
+    // First region containing `point`.
+    pub fn region_of(&self, point: [f64; 2]) -> Option<usize> {
+        self.regions
+            .iter()
+            .position(|region| region.contains(point))
+    }
+
+    // Keeps the entity in its `home` region, the one it's in when none yet, and returns
+    // the region it belongs to afterwards. Within an opening it moves freely and belongs
+    // to whichever region it has crossed into. Entities outside every region with no
+    // home are left alone.
+    pub fn confine(&self, physics: &mut Physics, home: Option<usize>) -> Option<usize> {
+        let point = [physics.x, physics.y];
+        let home = home
+            .filter(|&home| home < self.regions.len())
+            .or_else(|| self.region_of(point))?;
+
+        if self.openings.iter().any(|opening| opening.contains(point)) {
+            if self.regions[home].contains(point) {
+                return Some(home);
+            }
+            return Some(self.region_of(point).unwrap_or(home));
+        }
+
+        self.regions[home].confine(physics);
+        Some(home)
+    }
+}
@@ -0,0 +1,1120 @@
+use std::f64::consts::PI;
+
+use graphics::draw_state::Blend;
+use graphics::ellipse::Ellipse;
+use graphics::line::Line;
+use graphics::math::Matrix2d;
+use graphics::rectangle::Rectangle;
+use graphics::{rectangle, Context, Graphics, Transformed, Viewport, BACK_END_MAX_VERTEX_COUNT};
+use opengl_graphics::GlGraphics;
+use serde::Deserialize;
+
+use crate::collision::Wall;
+use crate::coords::CoordinateSystem;
+use crate::easing::spawn_scale;
+use crate::entity::Entity;
+use crate::forces::Attractor;
+use crate::generator::Gaussian;
+use crate::shapes::{replay_textured, triangulate, DrawCommand};
+use crate::spatial::NeighborQuery;
+use crate::sprite::Atlas;
+use crate::world::{Boundary, World};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RenderOrder {
+    // Lowest layer first, so higher layers end up on top. Ties go oldest first.
+    Layer,
+    // Oldest entity first.
+    Creation,
+    // Farthest from the camera center first, so the focus ends up on top. Ties go oldest
+    // first.
+    CameraDistance,
+}
+
+// What gets drawn over the background each frame, in the order of `App::overlays`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Overlay {
+    Wells,
+    Density,
+    Arena,
+    Axes,
+    Walls,
+    Connections,
+    Entities,
+    Selection,
+    Labels,
+    Minimap,
+    Hud,
+}
+
+pub const DEFAULT_OVERLAYS: [Overlay; 11] = [
+    Overlay::Wells,
+    Overlay::Density,
+    Overlay::Arena,
+    Overlay::Axes,
+    Overlay::Walls,
+    Overlay::Connections,
+    Overlay::Entities,
+    Overlay::Selection,
+    Overlay::Labels,
+    Overlay::Minimap,
+    Overlay::Hud,
+];
+
+// The configured order with repeats dropped, so each overlay draws at most once. Those
+// left out aren't drawn at all.
+pub fn overlay_order(configured: &[Overlay]) -> Vec<Overlay> {
+    let mut order: Vec<Overlay> = Vec::with_capacity(configured.len());
+
+    for &overlay in configured {
+        if !order.contains(&overlay) {
+            order.push(overlay);
+        }
+    }
+
+    order
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Camera {
+    // World point shown at the center of the view.
+    pub x: f64,
+    pub y: f64,
+    pub zoom: f64,
+    // World y grows upwards on screen, for the centered coordinate system.
+    pub y_up: bool,
+}
+
+impl Camera {
+    // Shows the whole world, centered, in a view of the given size.
+    pub fn fit(
+        world_width: f64,
+        world_height: f64,
+        coordinates: CoordinateSystem,
+        view_size: [f64; 2],
+    ) -> Camera {
+        let [x, y] = coordinates.center(world_width, world_height);
+
+        Camera {
+            x,
+            y,
+            zoom: (view_size[0] / world_width).min(view_size[1] / world_height),
+            y_up: coordinates.y_up(),
+        }
+    }
+
+    fn y_sign(&self) -> f64 {
+        if self.y_up {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+
+    pub fn world_to_view(&self, point: [f64; 2], view_size: [f64; 2]) -> [f64; 2] {
+        [
+            (point[0] - self.x) * self.zoom + view_size[0] / 2.0,
+            self.y_sign() * (point[1] - self.y) * self.zoom + view_size[1] / 2.0,
+        ]
+    }
+
+    pub fn view_to_world(&self, point: [f64; 2], view_size: [f64; 2]) -> [f64; 2] {
+        [
+            (point[0] - view_size[0] / 2.0) / self.zoom + self.x,
+            self.y_sign() * (point[1] - view_size[1] / 2.0) / self.zoom + self.y,
+        ]
+    }
+
+    // Draw context taking world coordinates, for a view context of the given size.
+    pub fn apply(&self, c: Context, view_size: [f64; 2]) -> Context {
+        let c = c
+            .trans(view_size[0] / 2.0, view_size[1] / 2.0)
+            .zoom(self.zoom);
+        let c = if self.y_up { c.flip_v() } else { c };
+
+        c.trans(-self.x, -self.y)
+    }
+}
+
+// Point `alpha` of the way from `previous` to `current`, alpha clamped to [0, 1].
+pub fn interpolate(previous: [f64; 2], current: [f64; 2], alpha: f64) -> [f64; 2] {
+    let alpha = alpha.clamp(0.0, 1.0);
+
+    [
+        previous[0] + (current[0] - previous[0]) * alpha,
+        previous[1] + (current[1] - previous[1]) * alpha,
+    ]
+}
+
+// Where to draw an entity `alpha` of the way through the step after its last one. A
+// jump of over half the world is a wrap, drawn where it landed rather than across.
+pub fn drawn_position(entity: &Entity, alpha: f64, world_size: [f64; 2]) -> [f64; 2] {
+    let current = [entity.physics.x, entity.physics.y];
+
+    match entity.previous {
+        Some(previous)
+            if (current[0] - previous[0]).abs() <= world_size[0] / 2.0
+                && (current[1] - previous[1]).abs() <= world_size[1] / 2.0 =>
+        {
+            interpolate(previous, current, alpha)
+        }
+        _ => current,
+    }
+}
+
+// Id and window position of every entity seen through the camera, in storage order.
+pub fn screen_positions(
+    entities: &[Entity],
+    camera: &Camera,
+    view_size: [f64; 2],
+) -> Vec<(u32, [f64; 2])> {
+    entities
+        .iter()
+        .map(|entity| {
+            let point = [entity.physics.x, entity.physics.y];
+            (entity._id, camera.world_to_view(point, view_size))
+        })
+        .collect()
+}
+
+// Indices into `entities` in the order they should be drawn. Ties are broken by id,
+// so the order doesn't depend on storage order and nothing flickers between frames.
+pub fn render_order(entities: &[Entity], order: RenderOrder, camera: &Camera) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..entities.len()).collect();
+
+    match order {
+        RenderOrder::Layer => indices.sort_by_key(|&i| (entities[i].layer, entities[i]._id)),
+        RenderOrder::Creation => indices.sort_by_key(|&i| entities[i]._id),
+        RenderOrder::CameraDistance => {
+            let distance = |i: usize| {
+                let dx = entities[i].physics.x - camera.x;
+                let dy = entities[i].physics.y - camera.y;
+                dx * dx + dy * dy
+            };
+
+            indices.sort_by(|&a, &b| {
+                distance(b)
+                    .total_cmp(&distance(a))
+                    .then(entities[a]._id.cmp(&entities[b]._id))
+            });
+        }
+    }
+
+    indices
+}
+
+// Color of the quad drawn over the previous frame instead of clearing it,
+// lower alpha leaving longer streaks.
+pub fn fade_quad_color(background: [f32; 4], fade_alpha: f32) -> [f32; 4] {
+    [
+        background[0],
+        background[1],
+        background[2],
+        fade_alpha.clamp(0.0, 1.0),
+    ]
+}
+
+// Gap between an entity's edge and its label, in window points.
+pub const LABEL_OFFSET: f64 = 2.0;
+
+// Text baseline origin of a label placed up and right of an entity, given its center
+// and size on screen.
+pub fn label_position(center: [f64; 2], size: f64) -> [f64; 2] {
+    [
+        center[0] + size / 2.0 + LABEL_OFFSET,
+        center[1] - size / 2.0 - LABEL_OFFSET,
+    ]
+}
+
+// Multiples of `spacing` from `min` to `max`, both included. Empty for a spacing that
+// isn't positive.
+pub fn axis_ticks(min: f64, max: f64, spacing: f64) -> Vec<f64> {
+    if !(spacing.is_finite() && spacing > 0.0) || min > max {
+        return Vec::new();
+    }
+
+    let first = (min / spacing).ceil() as i64;
+    let last = (max / spacing).floor() as i64;
+
+    (first..=last).map(|tick| tick as f64 * spacing).collect()
+}
+
+pub fn on_screen(point: [f64; 2], view_size: [f64; 2]) -> bool {
+    point[0] >= 0.0 && point[0] <= view_size[0] && point[1] >= 0.0 && point[1] <= view_size[1]
+}
+
+// Bounds on the drawn size of a gravity well, in world units.
+pub const MIN_WELL_RADIUS: f64 = 10.0;
+pub const MAX_WELL_RADIUS: f64 = 200.0;
+// Number of nested discs that make up the gradient of a well.
+const WELL_RINGS: usize = 4;
+
+// Outer radius and color of the well drawn for an attractor, the radius grows with the
+// square root of the strength. Attractors are blue and repulsors red, both faint.
+pub fn well_visual(strength: f64) -> (f64, [f32; 4]) {
+    let radius = strength
+        .abs()
+        .sqrt()
+        .clamp(MIN_WELL_RADIUS, MAX_WELL_RADIUS);
+    let color = if strength >= 0.0 {
+        [0.2, 0.4, 1.0, 0.08]
+    } else {
+        [1.0, 0.3, 0.2, 0.08]
+    };
+
+    (radius, color)
+}
+
+// Draws every attractor as a radial gradient of stacked translucent discs.
+pub fn render_wells(
+    gl: &mut GlGraphics,
+    attractors: &[Attractor],
+    viewport: Viewport,
+    camera: &Camera,
+) {
+    let size = view_size(&viewport);
+
+    gl.draw(viewport, |c, gl| {
+        let c = camera.apply(c, size);
+
+        for attractor in attractors {
+            let (radius, color) = well_visual(attractor.strength);
+            let [x, y] = attractor.position;
+
+            for ring in 0..WELL_RINGS {
+                let r = radius * (WELL_RINGS - ring) as f64 / WELL_RINGS as f64;
+                Ellipse::new(color).draw(
+                    [x - r, y - r, 2.0 * r, 2.0 * r],
+                    &c.draw_state,
+                    c.transform,
+                    gl,
+                );
+            }
+        }
+    });
+}
+
+// Segments approximating a circular arena outline.
+const ARENA_SEGMENTS: usize = 64;
+const ARENA_COLOR: [f32; 4] = [0.5, 0.5, 0.5, 1.0];
+
+// Corners of the closed outline of the boundary, the last joining back to the first.
+pub fn arena_outline(boundary: &Boundary, bounds: ([f64; 2], [f64; 2])) -> Vec<[f64; 2]> {
+    match *boundary {
+        Boundary::Rect => {
+            let (min, max) = bounds;
+            vec![min, [max[0], min[1]], max, [min[0], max[1]]]
+        }
+        Boundary::Circle { center, radius } => (0..ARENA_SEGMENTS)
+            .map(|i| {
+                let angle = 2.0 * PI * i as f64 / ARENA_SEGMENTS as f64;
+                [
+                    center[0] + radius * angle.cos(),
+                    center[1] + radius * angle.sin(),
+                ]
+            })
+            .collect(),
+    }
+}
+
+// Outlines the world boundary, one window point thick whatever the zoom.
+pub fn render_arena(gl: &mut GlGraphics, world: &World, viewport: Viewport, camera: &Camera) {
+    let size = view_size(&viewport);
+    let outline = arena_outline(&world.boundary, world.bounds());
+    let line = Line::new(ARENA_COLOR, 0.5 / camera.zoom);
+
+    gl.draw(viewport, |c, gl| {
+        let c = camera.apply(c, size);
+
+        for (i, &[x1, y1]) in outline.iter().enumerate() {
+            let [x2, y2] = outline[(i + 1) % outline.len()];
+            line.draw([x1, y1, x2, y2], &c.draw_state, c.transform, gl);
+        }
+    });
+}
+
+const WALL_COLOR: [f32; 4] = [0.9, 0.9, 0.9, 1.0];
+
+// Draws the walls of the world and `pending`, the one being drawn, two window points
+// thick whatever the zoom.
+pub fn render_walls(
+    gl: &mut GlGraphics,
+    walls: &[Wall],
+    pending: Option<Wall>,
+    viewport: Viewport,
+    camera: &Camera,
+) {
+    let size = view_size(&viewport);
+    let line = Line::new(WALL_COLOR, 1.0 / camera.zoom);
+
+    gl.draw(viewport, |c, gl| {
+        let c = camera.apply(c, size);
+
+        for wall in walls.iter().chain(pending.iter()) {
+            let [x1, y1] = wall.from;
+            let [x2, y2] = wall.to;
+            line.draw([x1, y1, x2, y2], &c.draw_state, c.transform, gl);
+        }
+    });
+}
+
+// Lines from each entity to its nearest neighbors, showing the scene's structure.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Connections {
+    pub neighbors: usize,
+    // Neighbors farther than this aren't connected to.
+    pub radius: f64,
+}
+
+impl Default for Connections {
+    fn default() -> Connections {
+        Connections {
+            neighbors: 3,
+            radius: 50.0,
+        }
+    }
+}
+
+const CONNECTION_COLOR: [f32; 4] = [0.4, 0.4, 0.4, 0.6];
+
+// Edges from every point to its `neighbors` nearest within `radius`, as index pairs
+// smallest first. Each edge is listed once even when both ends pick the other, in
+// order. `index` is built over `positions`.
+pub fn nearest_edges(
+    positions: &[[f64; 2]],
+    index: &dyn NeighborQuery,
+    neighbors: usize,
+    radius: f64,
+) -> Vec<(usize, usize)> {
+    let mut edges = Vec::new();
+
+    for (i, &point) in positions.iter().enumerate() {
+        let distance = |j: usize| {
+            let [x, y] = positions[j];
+            (x - point[0]).hypot(y - point[1])
+        };
+        let mut nearby: Vec<usize> = index
+            .within_radius(point, radius)
+            .into_iter()
+            .filter(|&j| j != i)
+            .collect();
+
+        nearby.sort_by(|&a, &b| distance(a).total_cmp(&distance(b)).then(a.cmp(&b)));
+        edges.extend(
+            nearby
+                .into_iter()
+                .take(neighbors)
+                .map(|j| (i.min(j), i.max(j))),
+        );
+    }
+
+    edges.sort_unstable();
+    edges.dedup();
+    edges
+}
+
+// Thin lines along the nearest neighbor edges of the entities, a window point thick
+// whatever the zoom.
+pub fn render_connections(
+    gl: &mut GlGraphics,
+    world: &World,
+    connections: Connections,
+    viewport: Viewport,
+    camera: &Camera,
+) {
+    let size = view_size(&viewport);
+    let positions = world.positions();
+    let edges = nearest_edges(
+        &positions,
+        world.neighbor_index().as_ref(),
+        connections.neighbors,
+        connections.radius,
+    );
+    let line = Line::new(CONNECTION_COLOR, 0.5 / camera.zoom);
+
+    gl.draw(viewport, |c, gl| {
+        let c = camera.apply(c, size);
+
+        for (i, j) in edges {
+            let ([x1, y1], [x2, y2]) = (positions[i], positions[j]);
+            line.draw([x1, y1, x2, y2], &c.draw_state, c.transform, gl);
+        }
+    });
+}
+
+// How selected entities stand out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Highlight {
+    // A thin ring around each of them.
+    #[default]
+    Ring,
+    // Drawn again over themselves, `amount` of the way to white.
+    Brighten {
+        amount: f32,
+    },
+    // A glow whose opacity swings between `min` and `max` every `period` seconds of
+    // simulated time.
+    Pulse {
+        min: f64,
+        max: f64,
+        period: f64,
+    },
+}
+
+// Goes from `min` at time 0 up to `max` half a period later and back, smoothly.
+pub fn pulse(time: f64, min: f64, max: f64, period: f64) -> f64 {
+    if period <= 0.0 {
+        return max;
+    }
+
+    let phase = 0.5 - 0.5 * (2.0 * PI * time / period).cos();
+    min + (max - min) * phase
+}
+
+// Deviations out of each contour drawn for the spawn distribution.
+const DENSITY_SIGMAS: [f64; 2] = [1.0, 2.0];
+const DENSITY_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.6];
+
+// Contour ellipses of the spawn distribution, in world coordinates.
+pub fn render_density(
+    gl: &mut GlGraphics,
+    gaussian: &Gaussian,
+    viewport: Viewport,
+    camera: &Camera,
+) {
+    let size = view_size(&viewport);
+    let ring = Ellipse::new_border(DENSITY_COLOR, 0.5 / camera.zoom);
+
+    gl.draw(viewport, |c, gl| {
+        let c = camera.apply(c, size);
+
+        for &sigmas in DENSITY_SIGMAS.iter() {
+            ring.draw(gaussian.contour(sigmas), &c.draw_state, c.transform, gl);
+        }
+    });
+}
+
+// Width of the minimap and its gap to the window corner, in window points. The height
+// follows the world's aspect ratio.
+const MINIMAP_WIDTH: f64 = 160.0;
+const MINIMAP_MARGIN: f64 = 10.0;
+const MINIMAP_BACKGROUND: [f32; 4] = [0.0, 0.0, 0.0, 0.5];
+const MINIMAP_DOT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.8];
+const MINIMAP_DOT_SIZE: f64 = 2.0;
+const MINIMAP_VIEW_COLOR: [f32; 4] = [1.0, 1.0, 0.0, 1.0];
+
+// Where a world point lands in the minimap rectangle `[x, y, width, height]`, given the
+// world bounds. With y up the largest y goes to the top, as on screen.
+pub fn minimap_point(
+    point: [f64; 2],
+    bounds: ([f64; 2], [f64; 2]),
+    y_up: bool,
+    rect: [f64; 4],
+) -> [f64; 2] {
+    let (min, max) = bounds;
+    let u = (point[0] - min[0]) / (max[0] - min[0]);
+    let v = (point[1] - min[1]) / (max[1] - min[1]);
+    let v = if y_up { 1.0 - v } else { v };
+
+    [rect[0] + u * rect[2], rect[1] + v * rect[3]]
+}
+
+// Bottom-right rectangle of the window holding the minimap.
+pub fn minimap_rect(world_size: [f64; 2], window_size: [f64; 2]) -> [f64; 4] {
+    let height = MINIMAP_WIDTH * world_size[1] / world_size[0];
+
+    [
+        window_size[0] - MINIMAP_WIDTH - MINIMAP_MARGIN,
+        window_size[1] - height - MINIMAP_MARGIN,
+        MINIMAP_WIDTH,
+        height,
+    ]
+}
+
+// Every entity as a dot in a corner of the window, with the part the camera shows
+// outlined.
+pub fn render_minimap(gl: &mut GlGraphics, world: &World, viewport: Viewport, camera: &Camera) {
+    let size = view_size(&viewport);
+    let rect = minimap_rect([world.width, world.height], size);
+    let bounds = world.bounds();
+    let y_up = world.coordinates.y_up();
+    let to_minimap = |point: [f64; 2]| minimap_point(point, bounds, y_up, rect);
+
+    // The window corners in world coordinates, clipped to the world.
+    let clip = |[x, y]: [f64; 2]| {
+        to_minimap([
+            x.clamp(bounds.0[0], bounds.1[0]),
+            y.clamp(bounds.0[1], bounds.1[1]),
+        ])
+    };
+    let a = clip(camera.view_to_world([0.0, 0.0], size));
+    let b = clip(camera.view_to_world(size, size));
+    let view = [
+        a[0].min(b[0]),
+        a[1].min(b[1]),
+        (a[0] - b[0]).abs(),
+        (a[1] - b[1]).abs(),
+    ];
+
+    gl.draw(viewport, |c, gl| {
+        rectangle(MINIMAP_BACKGROUND, rect, c.transform, gl);
+
+        for entity in world.entities.iter() {
+            let [x, y] = to_minimap([entity.physics.x, entity.physics.y]);
+            let half = MINIMAP_DOT_SIZE / 2.0;
+
+            rectangle(
+                MINIMAP_DOT_COLOR,
+                [x - half, y - half, MINIMAP_DOT_SIZE, MINIMAP_DOT_SIZE],
+                c.transform,
+                gl,
+            );
+        }
+
+        Rectangle::new_border(MINIMAP_VIEW_COLOR, 0.5).draw(view, &c.draw_state, c.transform, gl);
+    });
+}
+
+// Size of the viewport rectangle in window points, the unit used by the draw context.
+pub fn view_size(viewport: &Viewport) -> [f64; 2] {
+    [
+        viewport.rect[2] as f64 * viewport.window_size[0] / viewport.draw_size[0] as f64,
+        viewport.rect[3] as f64 * viewport.window_size[1] / viewport.draw_size[1] as f64,
+    ]
+}
+
+// Whether there are enough entities to draw them as one batch of triangles, none
+// meaning never.
+pub fn should_batch(count: usize, threshold: Option<usize>) -> bool {
+    threshold.is_some_and(|threshold| count > threshold)
+}
+
+// Draws only a sample of the entities once there are more than `threshold`, so very dense
+// scenes stay smooth while looking much the same.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Downsample {
+    pub threshold: usize,
+    // Draw every this many entities. When not set, about `threshold` of them are drawn,
+    // spread evenly through the rest.
+    pub stride: Option<usize>,
+}
+
+impl Downsample {
+    // Entities skipped per one drawn out of `count`, 1 drawing them all.
+    pub fn stride(&self, count: usize) -> usize {
+        if count <= self.threshold {
+            return 1;
+        }
+
+        match self.stride {
+            Some(stride) => stride.max(1),
+            None => count.div_ceil(self.threshold.max(1)),
+        }
+    }
+}
+
+// Indices of the `count` entities that get drawn, every `stride`-th from the first.
+pub fn subsample(count: usize, stride: usize) -> Vec<usize> {
+    (0..count).step_by(stride.max(1)).collect()
+}
+
+const LINK_COLOR: [f32; 4] = [0.6, 0.6, 0.6, 0.8];
+
+// Soft light around an entity, its opacity falling off from the center to `radius`.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Glow {
+    pub radius: f64,
+    pub color: [f32; 4],
+}
+
+// Number of nested discs that make up a glow.
+const GLOW_RINGS: usize = 8;
+
+// Glow color `t` of the way from the center to the edge, the alpha falling off
+// quadratically to nothing at the edge.
+pub fn glow_color(color: [f32; 4], t: f64) -> [f32; 4] {
+    let falloff = (1.0 - t.clamp(0.0, 1.0)).powi(2) as f32;
+
+    [color[0], color[1], color[2], color[3] * falloff]
+}
+
+// Additive discs whose alphas add up to `glow_color` at each ring, outermost first.
+fn draw_glow<G: Graphics>(glow: &Glow, center: [f64; 2], c: Context, gl: &mut G) {
+    let draw_state = c.draw_state.blend(Blend::Add);
+
+    for ring in (1..=GLOW_RINGS).rev() {
+        let t = ring as f64 / GLOW_RINGS as f64;
+        let inner = glow_color(glow.color, (ring - 1) as f64 / GLOW_RINGS as f64);
+        let outer = glow_color(glow.color, t);
+        let color = [
+            glow.color[0],
+            glow.color[1],
+            glow.color[2],
+            inner[3] - outer[3],
+        ];
+        let r = glow.radius * t;
+
+        Ellipse::new(color).draw(
+            [center[0] - r, center[1] - r, 2.0 * r, 2.0 * r],
+            &draw_state,
+            c.transform,
+            gl,
+        );
+    }
+}
+
+// Dark copy of every entity drawn beneath them all, `offset` world units away, giving a
+// sense of depth.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Shadow {
+    pub offset: [f64; 2],
+    #[serde(default = "default_shadow_color")]
+    pub color: [f32; 4],
+}
+
+fn default_shadow_color() -> [f32; 4] {
+    [0.0, 0.0, 0.0, 0.35]
+}
+
+// Transform a shadow is drawn with, the entity's own moved by `offset` in world units.
+pub fn shadow_transform(transform: Matrix2d, offset: [f64; 2]) -> Matrix2d {
+    transform.trans(offset[0], offset[1])
+}
+
+// Whole triangles per call, within what the backend takes at once.
+fn draw_triangles<G: Graphics>(
+    positions: &[[f32; 2]],
+    colors: &[[f32; 4]],
+    c: Context,
+    gl: &mut G,
+) {
+    let chunk = BACK_END_MAX_VERTEX_COUNT - BACK_END_MAX_VERTEX_COUNT % 3;
+    for (positions, colors) in positions.chunks(chunk).zip(colors.chunks(chunk)) {
+        gl.tri_list_c(&c.draw_state, |f| f(positions, colors));
+    }
+}
+
+// Draws the world entities into the viewport as seen by the camera.
+// Several views can share one world by calling this once per viewport.
+// `alpha`, when set, draws entities that far between their last two steps.
+#[allow(clippy::too_many_arguments)]
+pub fn render_view(
+    gl: &mut GlGraphics,
+    world: &World,
+    viewport: Viewport,
+    camera: &Camera,
+    order: RenderOrder,
+    batch_threshold: Option<usize>,
+    downsample: Option<Downsample>,
+    shadow: Option<Shadow>,
+    atlases: &[Atlas],
+    alpha: Option<f64>,
+) {
+    let size = view_size(&viewport);
+    let mut order = render_order(&world.entities, order, camera);
+    let stride = downsample.map_or(1, |downsample| downsample.stride(world.entities.len()));
+    if stride > 1 {
+        let mut drawn = vec![false; world.entities.len()];
+        for index in subsample(world.entities.len(), stride) {
+            drawn[index] = true;
+        }
+        order.retain(|&index| drawn[index]);
+    }
+    let batch = should_batch(world.entities.len(), batch_threshold);
+
+    gl.draw(viewport, |c, gl| {
+        let c = camera.apply(c, size);
+
+        for trail in world.entities.iter().filter_map(|e| e.trail.as_ref()) {
+            trail.draw(gl, c);
+        }
+
+        let center = |entity: &Entity| match alpha {
+            Some(alpha) => drawn_position(entity, alpha, [world.width, world.height]),
+            None => [entity.physics.x, entity.physics.y],
+        };
+
+        let link_line = Line::new(LINK_COLOR, 0.5 / camera.zoom);
+        for link in world.links.iter() {
+            if let (Some(a), Some(b)) = (world.get_by_id(link.a), world.get_by_id(link.b)) {
+                let ([x1, y1], [x2, y2]) = (center(a), center(b));
+                link_line.draw([x1, y1, x2, y2], &c.draw_state, c.transform, gl);
+            }
+        }
+
+        // Glows go beneath every shape.
+        for entity in order.iter().map(|&i| &world.entities[i]) {
+            if let Some(glow) = entity.glow.as_ref() {
+                draw_glow(glow, center(entity), c, gl);
+            }
+        }
+
+        let drawn_physics = |entity: &Entity| {
+            let mut physics = entity.physics;
+
+            physics.size *= spawn_scale(entity.age, world.spawn_ease);
+            [physics.x, physics.y] = center(entity);
+            physics
+        };
+
+        // Shadows go beneath every shape too, batched as they share one color. Sprites cast none.
+        if let Some(shadow) = shadow {
+            let transform = shadow_transform(c.transform, shadow.offset);
+            let mut positions = Vec::new();
+            let mut colors = Vec::new();
+
+            for entity in order.iter().map(|&i| &world.entities[i]) {
+                let commands = world
+                    .renderer_of(entity)
+                    .geometry(drawn_physics(entity), Some(shadow.color));
+                triangulate(&commands, transform, &mut positions, &mut colors);
+            }
+            draw_triangles(&positions, &colors, c, gl);
+        }
+
+        let mut positions = Vec::new();
+        let mut colors = Vec::new();
+        let mut textured = Vec::new();
+
+        for entity in order.into_iter().map(|i| &world.entities[i]) {
+            let renderer = world.renderer_of(entity);
+            let commands = renderer.geometry(drawn_physics(entity), world.draw_color(entity));
+
+            if batch {
+                triangulate(&commands, c.transform, &mut positions, &mut colors);
+                textured.extend(
+                    commands
+                        .into_iter()
+                        .filter(|command| matches!(command, DrawCommand::Textured { .. })),
+                );
+            } else {
+                replay_textured(&commands, c, gl, atlases);
+            }
+        }
+
+        draw_triangles(&positions, &colors, c, gl);
+
+        // Sprites aren't part of the batch, they go on top.
+        replay_textured(&textured, c, gl, atlases);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::{Physics, Renderer};
+    use crate::shapes::Circle;
+    use crate::spatial::SpatialIndexKind;
+
+    fn dot(id: u32, x: f64, y: f64) -> Entity {
+        Entity::new(
+            id,
+            Physics::new(x, y, 2.0).unwrap(),
+            Renderer::Owned(Box::new(Circle::new([1.0; 4]))),
+        )
+    }
+
+    #[test]
+    fn camera_maps_the_viewport_onto_the_world() {
+        // A hidpi viewport covering the right half of an 800x300 point window.
+        let viewport = Viewport {
+            rect: [800, 0, 800, 600],
+            draw_size: [1600, 600],
+            window_size: [800.0, 300.0],
+        };
+        let size = view_size(&viewport);
+        assert_eq!(size, [400.0, 300.0]);
+
+        let camera = Camera::fit(800.0, 600.0, CoordinateSystem::TopLeft, size);
+        assert_eq!(camera.zoom, 0.5);
+        assert_eq!(camera.view_to_world([0.0, 0.0], size), [0.0, 0.0]);
+        assert_eq!(camera.view_to_world([400.0, 300.0], size), [800.0, 600.0]);
+        assert_eq!(camera.world_to_view([400.0, 300.0], size), [200.0, 150.0]);
+
+        let camera = Camera::fit(800.0, 600.0, CoordinateSystem::Centered, size);
+        assert_eq!(camera.view_to_world([0.0, 0.0], size), [-400.0, 300.0]);
+        assert_eq!(camera.world_to_view([400.0, -300.0], size), [400.0, 300.0]);
+        assert_eq!(
+            camera.view_to_world(camera.world_to_view([120.0, -45.0], size), size),
+            [120.0, -45.0]
+        );
+    }
+
+    #[test]
+    fn render_order_sorts_by_each_mode() {
+        let mut entities = vec![dot(3, 10.0, 0.0), dot(1, 30.0, 0.0), dot(2, 20.0, 0.0)];
+        entities[0].layer = 1;
+        entities[2].layer = -1;
+        let camera = Camera {
+            x: 0.0,
+            y: 0.0,
+            zoom: 1.0,
+            y_up: false,
+        };
+
+        assert_eq!(
+            render_order(&entities, RenderOrder::Layer, &camera),
+            vec![2, 1, 0]
+        );
+        assert_eq!(
+            render_order(&entities, RenderOrder::Creation, &camera),
+            vec![1, 2, 0]
+        );
+        assert_eq!(
+            render_order(&entities, RenderOrder::CameraDistance, &camera),
+            vec![1, 2, 0]
+        );
+
+        // Ties go to the lowest id.
+        entities[2].layer = 0;
+        entities[0].layer = 0;
+        assert_eq!(
+            render_order(&entities, RenderOrder::Layer, &camera),
+            vec![1, 2, 0]
+        );
+    }
+
+    #[test]
+    fn fade_quad_takes_the_background_color_and_the_fade_alpha() {
+        let background = [0.1, 0.2, 0.3, 1.0];
+
+        assert_eq!(fade_quad_color(background, 0.25), [0.1, 0.2, 0.3, 0.25]);
+        assert_eq!(fade_quad_color(background, 2.0), [0.1, 0.2, 0.3, 1.0]);
+        assert_eq!(fade_quad_color(background, -1.0), [0.1, 0.2, 0.3, 0.0]);
+    }
+
+    #[test]
+    fn labels_clear_the_entity_whatever_its_size() {
+        assert_eq!(
+            label_position([100.0, 100.0], 10.0),
+            [105.0 + LABEL_OFFSET, 95.0 - LABEL_OFFSET]
+        );
+        assert_eq!(
+            label_position([100.0, 100.0], 40.0),
+            [120.0 + LABEL_OFFSET, 80.0 - LABEL_OFFSET]
+        );
+    }
+
+    #[test]
+    fn wells_grow_with_strength_within_bounds() {
+        let (radius, color) = well_visual(400.0);
+        assert_eq!(radius, 20.0);
+        assert!(color[2] > color[0]);
+
+        let (radius, color) = well_visual(-400.0);
+        assert_eq!(radius, 20.0);
+        assert!(color[0] > color[2]);
+
+        assert_eq!(well_visual(1.0).0, MIN_WELL_RADIUS);
+        assert_eq!(well_visual(1e9).0, MAX_WELL_RADIUS);
+    }
+
+    #[test]
+    fn overlays_draw_once_each_in_the_configured_order() {
+        assert_eq!(
+            overlay_order(&[
+                Overlay::Hud,
+                Overlay::Entities,
+                Overlay::Hud,
+                Overlay::Wells
+            ]),
+            vec![Overlay::Hud, Overlay::Entities, Overlay::Wells]
+        );
+        assert_eq!(overlay_order(&DEFAULT_OVERLAYS), DEFAULT_OVERLAYS.to_vec());
+        assert!(overlay_order(&[]).is_empty());
+    }
+
+    #[test]
+    fn arena_outline_follows_the_boundary() {
+        let bounds = ([0.0, 0.0], [100.0, 50.0]);
+        assert_eq!(
+            arena_outline(&Boundary::Rect, bounds),
+            vec![[0.0, 0.0], [100.0, 0.0], [100.0, 50.0], [0.0, 50.0]]
+        );
+
+        let circle = Boundary::Circle {
+            center: [50.0, 25.0],
+            radius: 20.0,
+        };
+        let outline = arena_outline(&circle, bounds);
+        assert_eq!(outline.len(), ARENA_SEGMENTS);
+        assert_eq!(outline[0], [70.0, 25.0]);
+        for [x, y] in outline {
+            assert!(((x - 50.0).hypot(y - 25.0) - 20.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn minimap_maps_corners_and_center() {
+        let rect = minimap_rect([200.0, 100.0], [800.0, 600.0]);
+        assert_eq!(rect, [630.0, 510.0, 160.0, 80.0]);
+
+        let bounds = ([0.0, 0.0], [200.0, 100.0]);
+        let point = |point: [f64; 2], y_up: bool| minimap_point(point, bounds, y_up, rect);
+        assert_eq!(point([0.0, 0.0], false), [630.0, 510.0]);
+        assert_eq!(point([200.0, 100.0], false), [790.0, 590.0]);
+        assert_eq!(point([100.0, 50.0], false), [710.0, 550.0]);
+
+        // With y up the top of the world is the top of the minimap.
+        assert_eq!(point([0.0, 100.0], true), [630.0, 510.0]);
+        assert_eq!(point([100.0, 50.0], true), [710.0, 550.0]);
+    }
+
+    #[test]
+    fn pulse_swings_within_min_and_max() {
+        assert_eq!(pulse(0.0, 0.2, 0.8, 2.0), 0.2);
+        assert!((pulse(1.0, 0.2, 0.8, 2.0) - 0.8).abs() < 1e-12);
+
+        for step in 0..100 {
+            let brightness = pulse(step as f64 * 0.037, 0.2, 0.8, 2.0);
+            assert!((0.2 - 1e-12..=0.8 + 1e-12).contains(&brightness));
+        }
+    }
+
+    #[test]
+    fn batching_starts_above_the_threshold() {
+        assert!(!should_batch(99, Some(100)));
+        assert!(!should_batch(100, Some(100)));
+        assert!(should_batch(101, Some(100)));
+        assert!(!should_batch(10_000, None));
+    }
+
+    #[test]
+    fn axis_ticks_fall_on_multiples_of_the_spacing() {
+        assert_eq!(axis_ticks(0.0, 100.0, 25.0), [0.0, 25.0, 50.0, 75.0, 100.0]);
+        assert_eq!(axis_ticks(-30.0, 45.0, 20.0), [-20.0, 0.0, 20.0, 40.0]);
+        assert_eq!(axis_ticks(1.0, 9.0, 10.0), Vec::<f64>::new());
+        assert!(axis_ticks(0.0, 100.0, 0.0).is_empty());
+        assert!(axis_ticks(100.0, 0.0, 10.0).is_empty());
+    }
+
+    #[test]
+    fn screen_positions_go_through_the_camera() {
+        let entities = vec![dot(4, 60.0, 40.0), dot(2, 50.0, 50.0)];
+        let camera = Camera {
+            x: 50.0,
+            y: 50.0,
+            zoom: 2.0,
+            y_up: false,
+        };
+
+        // (60 - 50) * 2 + 400 / 2, (40 - 50) * 2 + 300 / 2.
+        assert_eq!(
+            screen_positions(&entities, &camera, [400.0, 300.0]),
+            vec![(4, [220.0, 130.0]), (2, [200.0, 150.0])]
+        );
+
+        let camera = Camera {
+            y_up: true,
+            ..camera
+        };
+        assert_eq!(
+            screen_positions(&entities, &camera, [400.0, 300.0])[0],
+            (4, [220.0, 170.0])
+        );
+    }
+
+    #[test]
+    fn interpolation_runs_from_the_previous_to_the_current_position() {
+        let (previous, current) = ([10.0, 20.0], [20.0, 0.0]);
+
+        assert_eq!(interpolate(previous, current, 0.0), previous);
+        assert_eq!(interpolate(previous, current, 0.5), [15.0, 10.0]);
+        assert_eq!(interpolate(previous, current, 1.0), current);
+        assert_eq!(interpolate(previous, current, 1.5), current);
+
+        let mut entity = dot(0, 20.0, 0.0);
+        entity.previous = Some(previous);
+        assert_eq!(drawn_position(&entity, 0.5, [100.0, 100.0]), [15.0, 10.0]);
+        // Wrapped across the world, drawn where it landed.
+        entity.previous = Some([95.0, 0.0]);
+        assert_eq!(drawn_position(&entity, 0.5, [100.0, 100.0]), [20.0, 0.0]);
+    }
+
+    #[test]
+    fn equal_layers_draw_in_id_order_whatever_the_storage_order() {
+        let camera = Camera {
+            x: 0.0,
+            y: 0.0,
+            zoom: 1.0,
+            y_up: false,
+        };
+        let drawn = |ids: [u32; 4]| {
+            let entities: Vec<Entity> = ids.iter().map(|&id| dot(id, 0.0, 0.0)).collect();
+            render_order(&entities, RenderOrder::Layer, &camera)
+                .into_iter()
+                .map(|index| entities[index]._id)
+                .collect::<Vec<u32>>()
+        };
+
+        assert_eq!(drawn([0, 1, 2, 3]), [0, 1, 2, 3]);
+        assert_eq!(drawn([3, 1, 0, 2]), [0, 1, 2, 3]);
+        assert_eq!(drawn([2, 3, 1, 0]), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn downsampling_draws_every_stride_th_entity() {
+        assert_eq!(subsample(10, 3), [0, 3, 6, 9]);
+        assert_eq!(subsample(4, 1), [0, 1, 2, 3]);
+        assert_eq!(subsample(3, 0), [0, 1, 2]);
+
+        let downsample = Downsample {
+            threshold: 100,
+            stride: None,
+        };
+        assert_eq!(downsample.stride(100), 1);
+        assert_eq!(downsample.stride(250), 3);
+        assert_eq!(subsample(250, downsample.stride(250)).len(), 84);
+
+        let fixed = Downsample {
+            threshold: 100,
+            stride: Some(5),
+        };
+        assert_eq!(fixed.stride(50), 1);
+        assert_eq!(fixed.stride(101), 5);
+    }
+
+    #[test]
+    fn glow_fades_quadratically_from_center_to_edge() {
+        let color = [0.2, 0.4, 0.6, 0.8];
+
+        assert_eq!(glow_color(color, 0.0), color);
+        assert_eq!(glow_color(color, 0.5), [0.2, 0.4, 0.6, 0.2]);
+        assert_eq!(glow_color(color, 1.0), [0.2, 0.4, 0.6, 0.0]);
+        assert_eq!(glow_color(color, 2.0), [0.2, 0.4, 0.6, 0.0]);
+    }
+
+    #[test]
+    fn nearest_edges_link_each_point_to_its_nearest_once() {
+        let positions = [[0.0, 0.0], [1.0, 0.0], [3.0, 0.0], [20.0, 0.0]];
+        let index = SpatialIndexKind::Grid { cell_size: 5.0 }.build(&positions);
+
+        assert_eq!(
+            nearest_edges(&positions, index.as_ref(), 1, 5.0),
+            vec![(0, 1), (1, 2)]
+        );
+        assert_eq!(
+            nearest_edges(&positions, index.as_ref(), 2, 5.0),
+            vec![(0, 1), (0, 2), (1, 2)]
+        );
+        assert!(nearest_edges(&positions, index.as_ref(), 2, 0.5).is_empty());
+    }
+
+    #[test]
+    fn shadows_are_offset_in_world_units() {
+        // Twice as large on screen, its origin at (10, 20).
+        let transform = [[2.0, 0.0, 10.0], [0.0, 2.0, 20.0]];
+
+        assert_eq!(
+            shadow_transform(transform, [3.0, -4.0]),
+            [[2.0, 0.0, 16.0], [0.0, 2.0, 12.0]]
+        );
+        assert_eq!(shadow_transform(transform, [0.0, 0.0]), transform);
+    }
+}
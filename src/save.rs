@@ -0,0 +1,447 @@
+use serde::{Deserialize, Serialize};
+use toml::Value;
+
+use crate::color::color_from_id;
+use crate::config::BehaviorConfig;
+use crate::coords::CoordinateSystem;
+use crate::entity::{Entity, Physics, Renderer};
+use crate::error::NatureError;
+use crate::shapes::Circle;
+use crate::world::World;
+
+// Bumped whenever the saved layout changes, older saves go through `migrate`.
+pub const SAVE_VERSION: u32 = 4;
+
+// What a save keeps of an entity. Renderers aren't data, loaded entities are drawn as
+// circles colored by id. Only built-in behaviors are kept, see `AI::to_tag`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedEntity {
+    pub id: u32,
+    pub x: f64,
+    pub y: f64,
+    pub size: f64,
+    pub rotation: f64,
+    pub vx: f64,
+    pub vy: f64,
+    #[serde(default)]
+    pub angular_velocity: f64,
+    pub layer: i32,
+    pub age: f64,
+    pub lifetime: Option<f64>,
+    pub tags: Vec<String>,
+    // Last, as TOML puts tables after plain values.
+    #[serde(default)]
+    pub behavior: Option<BehaviorConfig>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedWorld {
+    pub version: u32,
+    pub width: f64,
+    pub height: f64,
+    pub coordinates: CoordinateSystem,
+    pub sim_time: f64,
+    pub tick: u64,
+    pub entities: Vec<SavedEntity>,
+}
+
+impl SavedWorld {
+    pub fn capture(world: &World) -> SavedWorld {
+        let entities = world
+            .entities
+            .iter()
+            .map(|entity| SavedEntity {
+                id: entity._id,
+                x: entity.physics.x,
+                y: entity.physics.y,
+                size: entity.physics.size,
+                rotation: entity.physics.rotation,
+                vx: entity.physics.vx,
+                vy: entity.physics.vy,
+                angular_velocity: entity.physics.angular_velocity,
+                layer: entity.layer,
+                age: entity.age,
+                lifetime: entity.lifetime,
+                tags: entity.tags.clone(),
+                behavior: entity
+                    .behavior
+                    .as_ref()
+                    .and_then(|behavior| behavior.to_tag()),
+            })
+            .collect();
+
+        SavedWorld {
+            version: SAVE_VERSION,
+            width: world.width,
+            height: world.height,
+            coordinates: world.coordinates,
+            sim_time: world.sim_time,
+            tick: world.tick,
+            entities,
+        }
+    }
+
+    pub fn to_toml(&self) -> Result<String, NatureError> {
+        toml::to_string(self).map_err(|error| NatureError::Save(error.to_string()))
+    }
+
+    // Same fields as the TOML save, one entity per line. Written by hand as the save is
+    // flat and the crate has no JSON dependency.
+    pub fn to_json(&self) -> String {
+        let coordinates = match self.coordinates {
+            CoordinateSystem::TopLeft => "top_left",
+            CoordinateSystem::Centered => "centered",
+        };
+        let entities: Vec<String> = self
+            .entities
+            .iter()
+            .map(|entity| {
+                let lifetime = match entity.lifetime {
+                    Some(lifetime) => format!("{:?}", lifetime),
+                    None => "null".to_string(),
+                };
+                let tags: Vec<String> = entity.tags.iter().map(|tag| json_string(tag)).collect();
+                let behavior = entity
+                    .behavior
+                    .as_ref()
+                    .and_then(|behavior| Value::try_from(behavior).ok())
+                    .map(|behavior| json_value(&behavior))
+                    .unwrap_or_else(|| "null".to_string());
+
+                format!(
+                    "    {{\"id\": {}, \"x\": {:?}, \"y\": {:?}, \"size\": {:?}, \"rotation\": {:?}, \
+                     \"vx\": {:?}, \"vy\": {:?}, \"angular_velocity\": {:?}, \"layer\": {}, \"age\": {:?}, \"lifetime\": {}, \
+                     \"tags\": [{}], \"behavior\": {}}}",
+                    entity.id,
+                    entity.x,
+                    entity.y,
+                    entity.size,
+                    entity.rotation,
+                    entity.vx,
+                    entity.vy,
+                    entity.angular_velocity,
+                    entity.layer,
+                    entity.age,
+                    lifetime,
+                    tags.join(", "),
+                    behavior
+                )
+            })
+            .collect();
+
+        format!(
+            "{{\n  \"version\": {},\n  \"width\": {:?},\n  \"height\": {:?},\n  \"coordinates\": \"{}\",\n  \
+             \"sim_time\": {:?},\n  \"tick\": {},\n  \"entities\": [\n{}\n  ]\n}}\n",
+            self.version,
+            self.width,
+            self.height,
+            coordinates,
+            self.sim_time,
+            self.tick,
+            entities.join(",\n")
+        )
+    }
+
+    // Reads a save of this version or an older one, rejecting saves from newer builds.
+    pub fn from_toml(text: &str) -> Result<SavedWorld, NatureError> {
+        let value: Value = text
+            .parse()
+            .map_err(|error: toml::de::Error| NatureError::Save(error.to_string()))?;
+        let version = match value.get("version").and_then(Value::as_integer) {
+            Some(version) if version >= 0 => version as u64,
+            _ => return Err(NatureError::Save("missing version".to_string())),
+        };
+
+        if version == 0 || version > SAVE_VERSION as u64 {
+            return Err(NatureError::UnsupportedVersion {
+                found: version,
+                supported: SAVE_VERSION,
+            });
+        }
+
+        migrate(value, version as u32)?
+            .try_into()
+            .map_err(|error: toml::de::Error| NatureError::Save(error.to_string()))
+    }
+
+    pub fn restore(&self) -> Result<World, NatureError> {
+        let mut entities = Vec::with_capacity(self.entities.len());
+
+        for saved in self.entities.iter() {
+            let mut physics = Physics::new(saved.x, saved.y, saved.size)?;
+            physics.rotation = saved.rotation;
+            physics.vx = saved.vx;
+            physics.vy = saved.vy;
+            physics.angular_velocity = saved.angular_velocity;
+
+            let renderer = Renderer::Owned(Box::new(Circle::new(color_from_id(saved.id))));
+            let mut entity = Entity::new(saved.id, physics, renderer);
+            entity.layer = saved.layer;
+            entity.age = saved.age;
+            entity.lifetime = saved.lifetime;
+            entity.tags = saved.tags.clone();
+            if let Some(behavior) = saved.behavior.as_ref() {
+                entity = entity.with_behavior(behavior.build()?);
+            }
+            entities.push(entity);
+        }
+
+        let mut world = World::new(entities, self.width, self.height)?;
+        world.coordinates = self.coordinates;
+        world.sim_time = self.sim_time;
+        world.tick = self.tick;
+        Ok(world)
+    }
+}
+
+// Quoted JSON string, escaping quotes, backslashes and control characters.
+pub fn json_string(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if (c as u32) < 0x20 => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+
+    quoted
+}
+
+// TOML value written as JSON, dates as strings.
+pub fn json_value(value: &Value) -> String {
+    match value {
+        Value::String(text) => json_string(text),
+        Value::Integer(integer) => integer.to_string(),
+        Value::Float(float) if float.is_finite() => format!("{:?}", float),
+        Value::Float(_) => "null".to_string(),
+        Value::Boolean(boolean) => boolean.to_string(),
+        Value::Datetime(datetime) => json_string(&datetime.to_string()),
+        Value::Array(values) => {
+            let values: Vec<String> = values.iter().map(json_value).collect();
+            format!("[{}]", values.join(", "))
+        }
+        Value::Table(table) => {
+            let fields: Vec<String> = table
+                .iter()
+                .map(|(key, value)| format!("{}: {}", json_string(key), json_value(value)))
+                .collect();
+            format!("{{{}}}", fields.join(", "))
+        }
+    }
+}
+
+// Brings an older save up to the current layout one version at a time.
+pub fn migrate(mut value: Value, version: u32) -> Result<Value, NatureError> {
+    let mut version = version;
+
+    while version < SAVE_VERSION {
+        value = match version {
+            1 => migrate_v1(value)?,
+            // Version 2 had no behaviors, which default to none.
+            2 => value,
+            // Version 3 had no spin, which defaults to none.
+            3 => value,
+            _ => {
+                return Err(NatureError::UnsupportedVersion {
+                    found: version as u64,
+                    supported: SAVE_VERSION,
+                })
+            }
+        };
+        version += 1;
+    }
+
+    if let Some(table) = value.as_table_mut() {
+        table.insert("version".to_string(), Value::Integer(SAVE_VERSION as i64));
+    }
+
+    Ok(value)
+}
+
+// Version 1 kept `position` and `velocity` pairs and had no coordinates, layers, ages,
+// lifetimes or tags.
+fn migrate_v1(mut value: Value) -> Result<Value, NatureError> {
+    let invalid = |what: &str| NatureError::Save(format!("version 1 save: {}", what));
+    let table = value.as_table_mut().ok_or_else(|| invalid("not a table"))?;
+
+    table
+        .entry("coordinates".to_string())
+        .or_insert_with(|| Value::String("top_left".to_string()));
+
+    let entities = match table.get_mut("entities") {
+        Some(Value::Array(entities)) => entities,
+        Some(_) => return Err(invalid("entities is not a list")),
+        None => return Ok(value),
+    };
+
+    for entity in entities.iter_mut() {
+        let entity = entity
+            .as_table_mut()
+            .ok_or_else(|| invalid("entity is not a table"))?;
+
+        for (pair, [first, second]) in [("position", ["x", "y"]), ("velocity", ["vx", "vy"])] {
+            let values = match entity.remove(pair) {
+                Some(Value::Array(values)) if values.len() == 2 => values,
+                _ => return Err(invalid(&format!("entity without a {} pair", pair))),
+            };
+
+            entity.insert(first.to_string(), values[0].clone());
+            entity.insert(second.to_string(), values[1].clone());
+        }
+
+        entity
+            .entry("layer".to_string())
+            .or_insert(Value::Integer(0));
+        entity.entry("age".to_string()).or_insert(Value::Float(0.0));
+        entity
+            .entry("tags".to_string())
+            .or_insert_with(|| Value::Array(Vec::new()));
+    }
+
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world() -> World {
+        let mut physics = Physics::new(10.0, 20.0, 3.0).unwrap();
+        physics.vx = 1.5;
+        let mut entity = Entity::new(4, physics, Renderer::Owned(Box::new(Circle::new([1.0; 4]))));
+        entity.tags = vec!["positive".to_string()];
+
+        World::new(vec![entity], 100.0, 80.0).unwrap()
+    }
+
+    #[test]
+    fn saves_from_newer_builds_are_rejected() {
+        let mut saved = SavedWorld::capture(&world());
+        saved.version = SAVE_VERSION + 1;
+
+        assert!(matches!(
+            SavedWorld::from_toml(&saved.to_toml().unwrap()),
+            Err(NatureError::UnsupportedVersion { found, supported: SAVE_VERSION })
+                if found == SAVE_VERSION as u64 + 1
+        ));
+    }
+
+    #[test]
+    fn current_saves_round_trip() {
+        let saved = SavedWorld::capture(&world());
+        let loaded = SavedWorld::from_toml(&saved.to_toml().unwrap()).unwrap();
+
+        assert_eq!(loaded, saved);
+        assert_eq!(SavedWorld::capture(&loaded.restore().unwrap()), saved);
+    }
+
+    #[test]
+    fn every_builtin_behavior_survives_a_save() {
+        #[derive(Deserialize)]
+        struct Behaviors {
+            behaviors: Vec<BehaviorConfig>,
+        }
+
+        let Behaviors { behaviors } = toml::from_str(
+            r#"
+            behaviors = [
+                { type = "walker" },
+                { type = "seek", target = [10.0, 20.0], speed = 3.0 },
+                { type = "containment", margin = 5.0, strength = 2.0 },
+                { type = "corral", leader_id = 0, radius = 30.0, margin = 5.0, strength = 2.0 },
+                { type = "follow", leader_id = 0, distance = 5.0, speed = 3.0 },
+                { type = "formation", leader_id = 0, shape = "v", slot = 2, spacing = 4.0, speed = 3.0 },
+                { type = "scatter_gather", period = 4.0, speed = 3.0 },
+                { type = "grow", rate = 0.5, max_size = 6.0 },
+                { type = "pen", every = 3, size = 1.0 },
+                { type = "diffuse", drift = [1.0, 0.0], noise = 2.0 },
+                { type = "path_follow", target = [150.0, 150.0], speed = 3.0 },
+                { type = "patrol", waypoints = [[110.0, 110.0], [20.0, 20.0]], speed = 3.0 },
+                { type = "field_lines", dipoles = [{ position = [50.0, 50.0], moment = [0.0, 1.0] }], speed = 3.0, strength = 1.0 },
+                { type = "magnetism", perception_radius = 20.0, strength = 1.0, positive = "plus", negative = "minus" },
+                { type = "territory", owners = ["red", "blue"], radius = 20.0, attraction = 1.0, repulsion = 2.0 },
+                { type = "crystallize", perception_radius = 20.0, spacing = 5.0, strength = 1.0, damping = 0.5 },
+                { type = "flock", perception_radius = 20.0, separation = 1.0, alignment = 1.0, cohesion = 1.0, max_speed = 5.0, leader_id = 0, leader_pull = 0.5 },
+                { type = "disperse", probe_distance = 5.0, radius = 10.0, speed = 3.0 },
+                { type = "evacuate", exits = [[0.0, 100.0]], speed = 3.0, perception_radius = 10.0 },
+                { type = "school", perception_radius = 20.0, matching = 1.0, max_speed = 5.0 },
+                { type = "rendezvous", perception_radius = 20.0, gain = 1.0, max_speed = 5.0 },
+                { type = "lane_flow", speed = 3.0, strength = 1.0 },
+                { type = "flow_field_follow", speed = 3.0, strength = 1.0 },
+                { type = "vector_field_follow", speed = 3.0, strength = 1.0 },
+                { type = "levy_flight", speed = 3.0, min_step = 1.0, alpha = 1.5, max_step = 50.0 },
+                { type = "orbit", strength = 1.0 },
+                { type = "lissajous", a = 3.0, b = 2.0, delta = 0.5, scale = 20.0, center = [100.0, 100.0] },
+                { type = "nbody_gravity", strength = 1.0, theta = 0.5 },
+                { type = "sph", smoothing_radius = 10.0, particle_mass = 1.0, rest_density = 1.0, stiffness = 1.0, viscosity = 0.1 },
+                { type = "ant", speed = 3.0, strength = 1.0, deposit = 1.0 },
+                { type = "chemotaxis", speed = 3.0, descend = true },
+                { type = "convection", buoyancy = 1.0, sizes = [4.0, 2.0] },
+                { type = "color_cluster", perception_radius = 20.0, threshold = 0.5, attraction = 1.0, repulsion = 1.0 },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        let entities = behaviors
+            .iter()
+            .enumerate()
+            .map(|(id, behavior)| {
+                let position = 100.0 + id as f64;
+                let mut entity = Entity::new(
+                    id as u32,
+                    Physics::new(position, position, 2.0).unwrap(),
+                    Renderer::Owned(Box::new(Circle::new([1.0; 4]))),
+                )
+                .with_behavior(behavior.build().unwrap());
+                entity.tags = vec!["red".to_string()];
+                entity
+            })
+            .collect();
+        let mut world = World::new(entities, 200.0, 200.0).unwrap();
+        world.step(0.1);
+
+        let saved = SavedWorld::capture(&world);
+        let restored = SavedWorld::from_toml(&saved.to_toml().unwrap())
+            .unwrap()
+            .restore()
+            .unwrap();
+        let tags = |world: &World| -> Vec<Option<BehaviorConfig>> {
+            world
+                .entities
+                .iter()
+                .map(|entity| {
+                    entity
+                        .behavior
+                        .as_ref()
+                        .and_then(|behavior| behavior.to_tag())
+                })
+                .collect()
+        };
+
+        // The pen's dot comes after and has no behavior.
+        assert!(tags(&world)[..behaviors.len()].iter().all(Option::is_some));
+        assert_eq!(tags(&restored), tags(&world));
+
+        // Runtime state comes back with the rest.
+        assert!(tags(&restored).contains(&Some(BehaviorConfig::Patrol {
+            waypoints: vec![[110.0, 110.0], [20.0, 20.0]],
+            speed: 3.0,
+            threshold: 5.0,
+            current: Some(1),
+        })));
+        assert!(tags(&restored).contains(&Some(BehaviorConfig::Territory {
+            owners: vec!["red".to_string(), "blue".to_string()],
+            radius: 20.0,
+            attraction: 1.0,
+            repulsion: 2.0,
+            home: Some([114.0, 114.0]),
+        })));
+    }
+}
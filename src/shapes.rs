@@ -0,0 +1,1009 @@
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use graphics::math::{identity, multiply, Matrix2d};
+use graphics::triangulation::{tx, ty};
+use graphics::{ellipse, polygon, rectangle, Context, Graphics, Transformed};
+use opengl_graphics::GlGraphics;
+
+use serde::Deserialize;
+
+use crate::color::rotation_color;
+use crate::entity::Physics;
+use crate::sprite::{Atlas, AtlasId};
+
+pub const DEFAULT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+pub const DEFAULT_SIZE: f64 = 10.0;
+// Height over width of an ellipse built without an explicit aspect.
+pub const DEFAULT_ASPECT: f64 = 0.5;
+
+// One primitive of a drawable, its transform relative to the draw context.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCommand {
+    Rectangle {
+        color: [f32; 4],
+        rectangle: [f64; 4],
+        transform: Matrix2d,
+    },
+    Polygon {
+        color: [f32; 4],
+        vertices: Vec<[f64; 2]>,
+        transform: Matrix2d,
+    },
+    Ellipse {
+        color: [f32; 4],
+        rectangle: [f64; 4],
+        transform: Matrix2d,
+    },
+    // Triangle list with a color per vertex.
+    Triangles {
+        positions: Vec<[f64; 2]>,
+        colors: Vec<[f32; 4]>,
+        transform: Matrix2d,
+    },
+    // Triangle list sampling an atlas texture. Only `replay_textured` can draw these, as
+    // the textures are held by the app.
+    Textured {
+        atlas: AtlasId,
+        positions: Vec<[f64; 2]>,
+        uvs: Vec<[f32; 2]>,
+        transform: Matrix2d,
+    },
+}
+
+pub type DrawCommands = Vec<DrawCommand>;
+
+// What a shape draws for an entity, kept apart from the GL calls so it can be checked
+// without a window.
+pub trait Geometry {
+    // `color` replaces the shape's own color, where it has one.
+    fn geometry(&self, physics: Physics, color: Option<[f32; 4]>) -> DrawCommands;
+
+    // The shape's own color, for behaviors that react to how entities look.
+    fn color(&self) -> Option<[f32; 4]> {
+        None
+    }
+}
+
+// Generic over the backend so draw logic can run against a mock in place of OpenGL.
+pub trait Drawable<G: Graphics = GlGraphics>: Geometry {
+    fn draw(&self, gl: &mut G, c: Context, physics: Physics) {
+        replay(&self.geometry(physics, None), c, gl);
+    }
+
+    // Draws in `color` instead of the drawable's own color, where it has one.
+    fn draw_colored(&self, gl: &mut G, c: Context, physics: Physics, color: [f32; 4]) {
+        replay(&self.geometry(physics, Some(color)), c, gl);
+    }
+}
+
+impl<G: Graphics, T: Geometry> Drawable<G> for T {}
+
+pub fn replay<G: Graphics>(commands: &[DrawCommand], c: Context, gl: &mut G) {
+    for command in commands {
+        match command {
+            DrawCommand::Rectangle {
+                color,
+                rectangle: bounds,
+                transform,
+            } => rectangle(*color, *bounds, multiply(c.transform, *transform), gl),
+            DrawCommand::Polygon {
+                color,
+                vertices,
+                transform,
+            } => polygon(*color, vertices, multiply(c.transform, *transform), gl),
+            DrawCommand::Ellipse {
+                color,
+                rectangle: bounds,
+                transform,
+            } => ellipse(*color, *bounds, multiply(c.transform, *transform), gl),
+            DrawCommand::Triangles {
+                positions,
+                colors,
+                transform,
+            } => {
+                let transform = multiply(c.transform, *transform);
+                let positions: Vec<[f32; 2]> = positions
+                    .iter()
+                    .map(|&[x, y]| [tx(transform, x, y), ty(transform, x, y)])
+                    .collect();
+
+                gl.tri_list_c(&c.draw_state, |f| f(&positions, colors));
+            }
+            DrawCommand::Textured { .. } => {}
+        }
+    }
+}
+
+// `replay` for the GL backend, drawing textured commands with the app's atlases.
+pub fn replay_textured(
+    commands: &[DrawCommand],
+    c: Context,
+    gl: &mut GlGraphics,
+    atlases: &[Atlas],
+) {
+    for command in commands {
+        match command {
+            DrawCommand::Textured {
+                atlas,
+                positions,
+                uvs,
+                transform,
+            } => {
+                let atlas = match atlases.get(atlas.0) {
+                    Some(atlas) => atlas,
+                    None => continue,
+                };
+                let transform = multiply(c.transform, *transform);
+                let positions: Vec<[f32; 2]> = positions
+                    .iter()
+                    .map(|&[x, y]| [tx(transform, x, y), ty(transform, x, y)])
+                    .collect();
+
+                gl.tri_list_uv(&c.draw_state, &[1.0; 4], &atlas.texture, |f| {
+                    f(&positions, uvs)
+                });
+            }
+            other => replay(std::slice::from_ref(other), c, gl),
+        }
+    }
+}
+
+// Segments approximating an ellipse when it is turned into triangles.
+const ELLIPSE_SEGMENTS: usize = 24;
+
+// Appends the commands as colored triangles with `transform` applied, ready for a single
+// `tri_list_c` call. Polygons are assumed convex, as every shape here draws them.
+pub fn triangulate(
+    commands: &[DrawCommand],
+    transform: Matrix2d,
+    positions: &mut Vec<[f32; 2]>,
+    colors: &mut Vec<[f32; 4]>,
+) {
+    for command in commands {
+        let (color, vertices, local) = match command {
+            DrawCommand::Rectangle {
+                color,
+                rectangle: [x, y, w, h],
+                transform,
+            } => (
+                *color,
+                vec![[*x, *y], [x + w, *y], [x + w, y + h], [*x, y + h]],
+                *transform,
+            ),
+            DrawCommand::Polygon {
+                color,
+                vertices,
+                transform,
+            } => (*color, vertices.clone(), *transform),
+            DrawCommand::Ellipse {
+                color,
+                rectangle: [x, y, w, h],
+                transform,
+            } => {
+                let (rx, ry) = (w / 2.0, h / 2.0);
+                let vertices = (0..ELLIPSE_SEGMENTS)
+                    .map(|i| {
+                        let angle = 2.0 * std::f64::consts::PI * i as f64 / ELLIPSE_SEGMENTS as f64;
+                        [x + rx + rx * angle.cos(), y + ry + ry * angle.sin()]
+                    })
+                    .collect();
+
+                (*color, vertices, *transform)
+            }
+            DrawCommand::Triangles {
+                positions: triangles,
+                colors: triangle_colors,
+                transform: local,
+            } => {
+                let transform = multiply(transform, *local);
+
+                positions.extend(
+                    triangles
+                        .iter()
+                        .map(|&[x, y]| [tx(transform, x, y), ty(transform, x, y)]),
+                );
+                colors.extend_from_slice(triangle_colors);
+                continue;
+            }
+            // Left to `replay_textured`.
+            DrawCommand::Textured { .. } => continue,
+        };
+
+        let transform = multiply(transform, local);
+        let point = |[x, y]: [f64; 2]| [tx(transform, x, y), ty(transform, x, y)];
+
+        for i in 1..vertices.len().saturating_sub(1) {
+            positions.extend([
+                point(vertices[0]),
+                point(vertices[i]),
+                point(vertices[i + 1]),
+            ]);
+            colors.extend([color; 3]);
+        }
+    }
+}
+
+// Triangle fan over a convex polygon, as vertex positions and matching colors for
+// `tri_list_c`. The color blends from `center_color` at `center` to `edge_color` at
+// the vertices.
+pub fn gradient_fan(
+    vertices: &[[f64; 2]],
+    center: [f64; 2],
+    center_color: [f32; 4],
+    edge_color: [f32; 4],
+) -> (Vec<[f64; 2]>, Vec<[f32; 4]>) {
+    let mut positions = Vec::with_capacity(vertices.len() * 3);
+    let mut colors = Vec::with_capacity(vertices.len() * 3);
+
+    if vertices.len() < 3 {
+        return (positions, colors);
+    }
+
+    for (i, &vertex) in vertices.iter().enumerate() {
+        let next = vertices[(i + 1) % vertices.len()];
+
+        positions.extend_from_slice(&[center, vertex, next]);
+        colors.extend_from_slice(&[center_color, edge_color, edge_color]);
+    }
+
+    (positions, colors)
+}
+
+pub struct Square {
+    pub color: [f32; 4],
+    // Center and edge colors, drawn in place of the flat `color` when set.
+    pub gradient: Option<([f32; 4], [f32; 4])>,
+}
+
+impl Square {
+    pub fn new(color: [f32; 4]) -> Square {
+        Square {
+            color,
+            gradient: None,
+        }
+    }
+
+    pub fn with_gradient(mut self, center: [f32; 4], edge: [f32; 4]) -> Square {
+        self.gradient = Some((center, edge));
+        self
+    }
+}
+
+impl Default for Square {
+    fn default() -> Square {
+        Square::new(DEFAULT_COLOR)
+    }
+}
+
+impl Geometry for Square {
+    fn color(&self) -> Option<[f32; 4]> {
+        Some(self.color)
+    }
+
+    fn geometry(&self, physics: Physics, color: Option<[f32; 4]>) -> DrawCommands {
+        let size = physics.size;
+        let x = physics.x - size / 2.0;
+        let y = physics.y - size / 2.0;
+        let transform = identity().trans(x, y).rot_rad(physics.rotation);
+
+        match (color, self.gradient) {
+            (None, Some((center, edge))) => {
+                let corners = [[0.0, 0.0], [size, 0.0], [size, size], [0.0, size]];
+                let (positions, colors) =
+                    gradient_fan(&corners, [size / 2.0, size / 2.0], center, edge);
+
+                vec![DrawCommand::Triangles {
+                    positions,
+                    colors,
+                    transform,
+                }]
+            }
+            _ => vec![DrawCommand::Rectangle {
+                color: color.unwrap_or(self.color),
+                rectangle: rectangle::square(0.0, 0.0, size),
+                transform,
+            }],
+        }
+    }
+}
+
+pub struct Arrow {
+    pub color: [f32; 4],
+    pub gradient: Option<([f32; 4], [f32; 4])>,
+    // Colored by heading instead, so a field of arrows reads as a direction map.
+    pub heading_colors: bool,
+}
+
+impl Arrow {
+    pub fn new(color: [f32; 4]) -> Arrow {
+        Arrow {
+            color,
+            gradient: None,
+            heading_colors: false,
+        }
+    }
+
+    pub fn with_heading_colors(mut self) -> Arrow {
+        self.heading_colors = true;
+        self
+    }
+
+    pub fn with_gradient(mut self, center: [f32; 4], edge: [f32; 4]) -> Arrow {
+        self.gradient = Some((center, edge));
+        self
+    }
+
+    fn vertices(size: f64) -> [[f64; 2]; 3] {
+        [
+            [0.0, -size / 2.0],
+            [-size / 3.0, size / 2.0],
+            [size / 3.0, size / 2.0],
+        ]
+    }
+}
+
+impl Default for Arrow {
+    fn default() -> Arrow {
+        Arrow::new(DEFAULT_COLOR)
+    }
+}
+
+impl Geometry for Arrow {
+    fn color(&self) -> Option<[f32; 4]> {
+        Some(self.color)
+    }
+
+    fn geometry(&self, physics: Physics, color: Option<[f32; 4]>) -> DrawCommands {
+        let x = physics.x;
+        let y = physics.y - physics.size / 2.0;
+        let transform = identity().trans(x, y).rot_rad(physics.rotation);
+        let vertices = Arrow::vertices(physics.size);
+        let color = color.or_else(|| {
+            self.heading_colors
+                .then(|| rotation_color(physics.rotation))
+        });
+
+        match (color, self.gradient) {
+            (None, Some((center, edge))) => {
+                // The centroid of the triangle.
+                let middle = [0.0, physics.size / 6.0];
+                let (positions, colors) = gradient_fan(&vertices, middle, center, edge);
+
+                vec![DrawCommand::Triangles {
+                    positions,
+                    colors,
+                    transform,
+                }]
+            }
+            _ => vec![DrawCommand::Polygon {
+                color: color.unwrap_or(self.color),
+                vertices: vertices.to_vec(),
+                transform,
+            }],
+        }
+    }
+}
+
+pub struct Circle {
+    pub color: [f32; 4],
+    // Drawn as a polygon with this many sides, for the same edge whatever the backend's
+    // ellipses look like. An ellipse when not set.
+    pub segments: Option<usize>,
+}
+
+impl Circle {
+    pub fn new(color: [f32; 4]) -> Circle {
+        Circle {
+            color,
+            segments: None,
+        }
+    }
+
+    // A circle of `rgba`, the builder the other shapes spell `new`.
+    pub fn colored(rgba: [f32; 4]) -> Circle {
+        Circle::new(rgba)
+    }
+
+    pub fn with_segments(mut self, segments: usize) -> Circle {
+        self.segments = Some(segments);
+        self
+    }
+}
+
+// `segments` points evenly spaced on the circle of `radius` around the origin, starting
+// on the positive x axis.
+pub fn circle_vertices(radius: f64, segments: usize) -> Vec<[f64; 2]> {
+    (0..segments)
+        .map(|i| {
+            let angle = i as f64 * std::f64::consts::TAU / segments as f64;
+            [radius * angle.cos(), radius * angle.sin()]
+        })
+        .collect()
+}
+
+impl Default for Circle {
+    fn default() -> Circle {
+        Circle::new(DEFAULT_COLOR)
+    }
+}
+
+impl Geometry for Circle {
+    fn color(&self) -> Option<[f32; 4]> {
+        Some(self.color)
+    }
+
+    fn geometry(&self, physics: Physics, color: Option<[f32; 4]>) -> DrawCommands {
+        if let Some(segments) = self.segments.filter(|&segments| segments >= 3) {
+            return vec![DrawCommand::Polygon {
+                color: color.unwrap_or(self.color),
+                vertices: circle_vertices(physics.size / 2.0, segments),
+                transform: identity().trans(physics.x, physics.y),
+            }];
+        }
+
+        let x = physics.x - physics.size / 2.0;
+        let y = physics.y - physics.size / 2.0;
+
+        vec![DrawCommand::Ellipse {
+            color: color.unwrap_or(self.color),
+            rectangle: rectangle::square(0.0, 0.0, physics.size),
+            transform: identity().trans(x, y),
+        }]
+    }
+}
+
+// Circle stretched along its heading, `physics.size` is the width and the height
+// follows from the aspect.
+pub struct Ellipse {
+    pub color: [f32; 4],
+    // Height over width.
+    pub aspect: f64,
+}
+
+impl Ellipse {
+    pub fn new(color: [f32; 4], aspect: f64) -> Ellipse {
+        Ellipse { color, aspect }
+    }
+
+    // Bounding rectangle around the origin, before rotation.
+    pub fn bounds(size: f64, aspect: f64) -> [f64; 4] {
+        let (width, height) = (size, size * aspect);
+
+        [-width / 2.0, -height / 2.0, width, height]
+    }
+}
+
+impl Default for Ellipse {
+    fn default() -> Ellipse {
+        Ellipse::new(DEFAULT_COLOR, DEFAULT_ASPECT)
+    }
+}
+
+impl Geometry for Ellipse {
+    fn color(&self) -> Option<[f32; 4]> {
+        Some(self.color)
+    }
+
+    fn geometry(&self, physics: Physics, color: Option<[f32; 4]>) -> DrawCommands {
+        vec![DrawCommand::Ellipse {
+            color: color.unwrap_or(self.color),
+            rectangle: Ellipse::bounds(physics.size, self.aspect),
+            transform: identity()
+                .trans(physics.x, physics.y)
+                .rot_rad(physics.rotation),
+        }]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShapeKind {
+    Square,
+    Arrow,
+    Circle,
+    Ellipse,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShapeDefaults {
+    pub size: f64,
+    pub color: [f32; 4],
+}
+
+impl Default for ShapeDefaults {
+    fn default() -> ShapeDefaults {
+        ShapeDefaults {
+            size: DEFAULT_SIZE,
+            color: DEFAULT_COLOR,
+        }
+    }
+}
+
+// Builds boxed drawables from a shape tag, with per-shape default size and color.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ShapeFactory {
+    pub square: ShapeDefaults,
+    pub arrow: ShapeDefaults,
+    pub circle: ShapeDefaults,
+    pub ellipse: ShapeDefaults,
+    // Sides of the polygon circles are drawn as, see `Circle::segments`.
+    pub circle_segments: Option<usize>,
+}
+
+impl ShapeFactory {
+    pub fn defaults(&self, kind: ShapeKind) -> ShapeDefaults {
+        match kind {
+            ShapeKind::Square => self.square,
+            ShapeKind::Arrow => self.arrow,
+            ShapeKind::Circle => self.circle,
+            ShapeKind::Ellipse => self.ellipse,
+        }
+    }
+
+    // At rest at (x, y), with the default size of the shape.
+    pub fn physics(&self, kind: ShapeKind, x: f64, y: f64) -> Physics {
+        Physics {
+            x,
+            y,
+            size: self.defaults(kind).size,
+            rotation: 0.0,
+            vx: 0.0,
+            vy: 0.0,
+            angular_velocity: 0.0,
+        }
+    }
+
+    pub fn build<G: Graphics>(&self, kind: ShapeKind) -> Box<dyn Drawable<G>> {
+        self.build_colored(kind, self.defaults(kind).color)
+    }
+
+    pub fn build_colored<G: Graphics>(
+        &self,
+        kind: ShapeKind,
+        color: [f32; 4],
+    ) -> Box<dyn Drawable<G>> {
+        match kind {
+            ShapeKind::Square => Box::new(Square::new(color)),
+            ShapeKind::Arrow => Box::new(Arrow::new(color)),
+            ShapeKind::Circle => Box::new(Circle {
+                segments: self.circle_segments,
+                ..Circle::colored(color)
+            }),
+            ShapeKind::Ellipse => Box::new(Ellipse::new(color, DEFAULT_ASPECT)),
+        }
+    }
+
+    // Drawn as `slow` until the entity reaches `threshold` units per second, then `fast`.
+    pub fn build_by_speed<G: Graphics + 'static>(
+        &self,
+        slow: ShapeKind,
+        fast: ShapeKind,
+        threshold: f64,
+        color: [f32; 4],
+    ) -> Box<dyn Drawable<G>> {
+        Box::new(SpeedShape::<G> {
+            slow,
+            fast,
+            threshold,
+            slow_drawable: self.build_colored(slow, color),
+            fast_drawable: self.build_colored(fast, color),
+        })
+    }
+}
+
+// Shape of an entity moving at `speed`, `fast` from `threshold` on.
+pub fn shape_for_speed(speed: f64, threshold: f64, slow: ShapeKind, fast: ShapeKind) -> ShapeKind {
+    if speed >= threshold {
+        fast
+    } else {
+        slow
+    }
+}
+
+// Switches shape with speed so motion shows in the form as well as the color, like a
+// circle at rest and an arrow on the move.
+pub struct SpeedShape<G: Graphics = GlGraphics> {
+    pub slow: ShapeKind,
+    pub fast: ShapeKind,
+    // Units per second.
+    pub threshold: f64,
+    slow_drawable: Box<dyn Drawable<G>>,
+    fast_drawable: Box<dyn Drawable<G>>,
+}
+
+impl<G: Graphics> Geometry for SpeedShape<G> {
+    fn color(&self) -> Option<[f32; 4]> {
+        self.slow_drawable.color()
+    }
+
+    fn geometry(&self, physics: Physics, color: Option<[f32; 4]>) -> DrawCommands {
+        let speed = (physics.vx * physics.vx + physics.vy * physics.vy).sqrt();
+        let drawable = if shape_for_speed(speed, self.threshold, self.slow, self.fast) == self.fast
+        {
+            &self.fast_drawable
+        } else {
+            &self.slow_drawable
+        };
+
+        drawable.geometry(physics, color)
+    }
+}
+
+// Tail quads going back from the head along `points`, oldest first and ending at the
+// head, narrowing to nothing at the oldest point. Across the head the tail is square to
+// `velocity`, further back to the path through the points.
+pub fn comet_tail(points: &[[f64; 2]], velocity: [f64; 2], width: f64) -> Vec<[[f64; 2]; 4]> {
+    let count = points.len();
+
+    if count < 2 {
+        return Vec::new();
+    }
+
+    let unit = |[dx, dy]: [f64; 2]| {
+        let length = (dx * dx + dy * dy).sqrt();
+        if length > 0.0 {
+            Some([dx / length, dy / length])
+        } else {
+            None
+        }
+    };
+    let head = points[count - 1];
+    let mut back = unit([-velocity[0], -velocity[1]])
+        .or_else(|| {
+            unit([
+                points[count - 2][0] - head[0],
+                points[count - 2][1] - head[1],
+            ])
+        })
+        .unwrap_or([-1.0, 0.0]);
+
+    // Edges of the tail at each point, from the head back.
+    let edges: Vec<[[f64; 2]; 2]> = (0..count)
+        .rev()
+        .map(|i| {
+            if i + 1 < count {
+                let older = points[i.saturating_sub(1)];
+                let newer = points[i + 1];
+                back = unit([older[0] - newer[0], older[1] - newer[1]]).unwrap_or(back);
+            }
+
+            let half = width / 2.0 * i as f64 / (count - 1) as f64;
+            let (px, py) = (-back[1] * half, back[0] * half);
+            let [x, y] = points[i];
+
+            [[x + px, y + py], [x - px, y - py]]
+        })
+        .collect();
+
+    edges
+        .windows(2)
+        .map(|pair| [pair[0][0], pair[1][0], pair[1][1], pair[0][1]])
+        .collect()
+}
+
+// Draws a fading tail behind the wrapped shape, along the positions it was drawn at.
+pub struct Comet<G: Graphics = GlGraphics> {
+    pub head: Box<dyn Drawable<G>>,
+    pub color: [f32; 4],
+    // Positions the tail goes back through, the head's included.
+    pub length: usize,
+    // Where the comet was drawn, oldest first. Drawing only gets `&self`.
+    history: Mutex<VecDeque<[f64; 2]>>,
+}
+
+impl<G: Graphics> Comet<G> {
+    pub fn new(head: Box<dyn Drawable<G>>, color: [f32; 4], length: usize) -> Comet<G> {
+        Comet {
+            head,
+            color,
+            length,
+            history: Mutex::new(VecDeque::with_capacity(length + 1)),
+        }
+    }
+
+    // The positions of the tail with `head` recorded as the newest. Redraws in place,
+    // while paused, leave the tail as it is.
+    fn record(&self, head: [f64; 2]) -> Vec<[f64; 2]> {
+        let mut history = self.history.lock().unwrap();
+
+        if history.back() != Some(&head) {
+            history.push_back(head);
+        }
+        while history.len() > self.length {
+            history.pop_front();
+        }
+
+        history.iter().copied().collect()
+    }
+
+    fn tail(&self, physics: Physics) -> DrawCommands {
+        let points = self.record([physics.x, physics.y]);
+        let quads = comet_tail(&points, [physics.vx, physics.vy], physics.size);
+        let count = quads.len();
+
+        quads
+            .into_iter()
+            .enumerate()
+            .map(|(i, quad)| {
+                let fade = 1.0 - i as f32 / count as f32;
+
+                DrawCommand::Polygon {
+                    color: [
+                        self.color[0],
+                        self.color[1],
+                        self.color[2],
+                        self.color[3] * fade,
+                    ],
+                    vertices: quad.to_vec(),
+                    transform: identity(),
+                }
+            })
+            .collect()
+    }
+}
+
+impl<G: Graphics> Geometry for Comet<G> {
+    fn color(&self) -> Option<[f32; 4]> {
+        self.head.color()
+    }
+
+    // The tail keeps its own color, only the head takes `color`.
+    fn geometry(&self, physics: Physics, color: Option<[f32; 4]>) -> DrawCommands {
+        let mut commands = self.tail(physics);
+
+        commands.extend(self.head.geometry(physics, color));
+        commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use graphics::math::identity;
+
+    use super::*;
+    use crate::mock::{DrawCall, MockGraphics};
+
+    #[test]
+    fn comet_tail_narrows_behind_the_head() {
+        let straight = comet_tail(&[[-10.0, 0.0], [-5.0, 0.0], [0.0, 0.0]], [10.0, 0.0], 4.0);
+
+        assert_eq!(
+            straight,
+            vec![
+                [[0.0, -2.0], [-5.0, -1.0], [-5.0, 1.0], [0.0, 2.0]],
+                [[-5.0, -1.0], [-10.0, 0.0], [-10.0, 0.0], [-5.0, 1.0]],
+            ]
+        );
+
+        // Square to the velocity at the head, then following the bend back to the
+        // oldest point.
+        let curved = comet_tail(&[[-5.0, -5.0], [-5.0, 0.0], [0.0, 0.0]], [10.0, 0.0], 4.0);
+        let s = std::f64::consts::FRAC_1_SQRT_2;
+        let expected = [
+            [[0.0, -2.0], [-5.0 + s, -s], [-5.0 - s, s], [0.0, 2.0]],
+            [[-5.0 + s, -s], [-5.0, -5.0], [-5.0, -5.0], [-5.0 - s, s]],
+        ];
+
+        assert_eq!(curved.len(), expected.len());
+        for (quad, expected) in curved.iter().zip(expected.iter()) {
+            for (vertex, expected) in quad.iter().zip(expected.iter()) {
+                assert!(
+                    (vertex[0] - expected[0]).abs() < 1e-12
+                        && (vertex[1] - expected[1]).abs() < 1e-12,
+                    "{:?} != {:?}",
+                    quad,
+                    expected
+                );
+            }
+        }
+        assert!(comet_tail(&[[0.0, 0.0]], [10.0, 0.0], 4.0).is_empty());
+    }
+
+    #[test]
+    fn factory_sizes_each_shape_kind_by_its_defaults() {
+        let factory = ShapeFactory {
+            square: ShapeDefaults {
+                size: 4.0,
+                color: DEFAULT_COLOR,
+            },
+            circle: ShapeDefaults {
+                size: 16.0,
+                color: [0.0, 1.0, 0.0, 1.0],
+            },
+            ..ShapeFactory::default()
+        };
+
+        for (kind, size) in [
+            (ShapeKind::Square, 4.0),
+            (ShapeKind::Arrow, DEFAULT_SIZE),
+            (ShapeKind::Circle, 16.0),
+            (ShapeKind::Ellipse, DEFAULT_SIZE),
+        ] {
+            let physics = factory.physics(kind, 20.0, 30.0);
+            assert_eq!([physics.x, physics.y, physics.size], [20.0, 30.0, size]);
+        }
+        assert_eq!(
+            Circle::colored([0.0, 1.0, 0.0, 1.0]).color,
+            [0.0, 1.0, 0.0, 1.0]
+        );
+    }
+
+    #[test]
+    fn mock_records_the_primitive_of_each_shape() {
+        let red = [1.0, 0.0, 0.0, 1.0];
+        let physics = Physics::new(20.0, 30.0, 12.0).unwrap();
+        let draw = |drawable: &dyn Drawable<MockGraphics>| {
+            let mut gl = MockGraphics::new();
+            drawable.draw(&mut gl, Context::new(), physics);
+            gl.calls
+        };
+
+        assert_eq!(
+            draw(&Square::new(red)),
+            vec![DrawCall::Rectangle {
+                color: red,
+                rectangle: [0.0, 0.0, 12.0, 12.0],
+                transform: identity().trans(14.0, 24.0),
+            }]
+        );
+        assert_eq!(
+            draw(&Arrow::new(red)),
+            vec![DrawCall::Polygon {
+                color: red,
+                vertices: vec![[0.0, -6.0], [-4.0, 6.0], [4.0, 6.0]],
+                transform: identity().trans(20.0, 24.0),
+            }]
+        );
+        assert_eq!(
+            draw(&Circle::new(red)),
+            vec![DrawCall::Ellipse {
+                color: red,
+                rectangle: [0.0, 0.0, 12.0, 12.0],
+                transform: identity().trans(14.0, 24.0),
+            }]
+        );
+    }
+
+    #[test]
+    fn factory_drawables_render_in_the_default_colors() {
+        let factory = ShapeFactory {
+            circle: ShapeDefaults {
+                size: DEFAULT_SIZE,
+                color: [0.0, 1.0, 0.0, 1.0],
+            },
+            ..ShapeFactory::default()
+        };
+
+        for kind in [
+            ShapeKind::Square,
+            ShapeKind::Arrow,
+            ShapeKind::Circle,
+            ShapeKind::Ellipse,
+        ] {
+            let mut gl = MockGraphics::new();
+            let drawable = factory.build::<MockGraphics>(kind);
+            drawable.draw(&mut gl, Context::new(), factory.physics(kind, 20.0, 30.0));
+
+            let color = factory.defaults(kind).color;
+            match (kind, gl.calls.as_slice()) {
+                (ShapeKind::Square, [DrawCall::Rectangle { color: c, .. }])
+                | (ShapeKind::Arrow, [DrawCall::Polygon { color: c, .. }])
+                | (ShapeKind::Circle | ShapeKind::Ellipse, [DrawCall::Ellipse { color: c, .. }]) => {
+                    assert_eq!(*c, color, "{:?}", kind)
+                }
+                (kind, calls) => panic!("{:?} drew {:?}", kind, calls),
+            }
+        }
+    }
+
+    #[test]
+    fn gradient_colors_each_vertex_of_the_fan() {
+        let (center, edge) = ([1.0, 1.0, 1.0, 1.0], [0.0, 0.0, 1.0, 0.5]);
+        let square = Square::new(DEFAULT_COLOR).with_gradient(center, edge);
+        let physics = Physics::new(1.0, 1.0, 2.0).unwrap();
+
+        let mut gl = MockGraphics::new();
+        square.draw(&mut gl, Context::new(), physics);
+        match gl.calls.as_slice() {
+            [DrawCall::Triangles { vertices, colors }] => {
+                assert_eq!(vertices.len(), 12);
+                assert_eq!(vertices[..3], [[1.0, 1.0], [0.0, 0.0], [2.0, 0.0]]);
+                assert_eq!(colors, &[center, edge, edge].repeat(4));
+            }
+            calls => panic!("{:?}", calls),
+        }
+
+        // An explicit color still draws flat.
+        let mut gl = MockGraphics::new();
+        square.draw_colored(&mut gl, Context::new(), physics, edge);
+        assert!(matches!(gl.calls.as_slice(), [DrawCall::Rectangle { .. }]));
+    }
+
+    #[test]
+    fn ellipse_is_centered_and_turned_with_its_heading() {
+        assert_eq!(Ellipse::bounds(10.0, 0.5), [-5.0, -2.5, 10.0, 5.0]);
+
+        let mut physics = Physics::new(20.0, 30.0, 10.0).unwrap();
+        physics.rotation = std::f64::consts::FRAC_PI_2;
+        let mut gl = MockGraphics::new();
+        Ellipse::new(DEFAULT_COLOR, 0.5).draw(&mut gl, Context::new(), physics);
+
+        assert_eq!(
+            gl.calls,
+            vec![DrawCall::Ellipse {
+                color: DEFAULT_COLOR,
+                rectangle: [-5.0, -2.5, 10.0, 5.0],
+                transform: identity()
+                    .trans(20.0, 30.0)
+                    .rot_rad(std::f64::consts::FRAC_PI_2),
+            }]
+        );
+    }
+
+    #[test]
+    fn shapes_emit_their_golden_command_lists() {
+        let red = [1.0, 0.0, 0.0, 1.0];
+        let blue = [0.0, 0.0, 1.0, 1.0];
+        let physics = Physics::new(10.0, 20.0, 4.0).unwrap();
+
+        assert_eq!(
+            Square::new(red).geometry(physics, Some(blue)),
+            vec![DrawCommand::Rectangle {
+                color: blue,
+                rectangle: [0.0, 0.0, 4.0, 4.0],
+                transform: identity().trans(8.0, 18.0),
+            }]
+        );
+        assert_eq!(
+            Arrow::new(red).geometry(physics, None),
+            vec![DrawCommand::Polygon {
+                color: red,
+                vertices: vec![[0.0, -2.0], [-4.0 / 3.0, 2.0], [4.0 / 3.0, 2.0]],
+                transform: identity().trans(10.0, 18.0),
+            }]
+        );
+        assert_eq!(
+            Circle::new(red).geometry(physics, None),
+            vec![DrawCommand::Ellipse {
+                color: red,
+                rectangle: [0.0, 0.0, 4.0, 4.0],
+                transform: identity().trans(8.0, 18.0),
+            }]
+        );
+        assert_eq!(
+            Ellipse::new(red, 0.5).geometry(physics, None),
+            vec![DrawCommand::Ellipse {
+                color: red,
+                rectangle: [-2.0, -1.0, 4.0, 2.0],
+                transform: identity().trans(10.0, 20.0).rot_rad(0.0),
+            }]
+        );
+    }
+
+    #[test]
+    fn circle_vertices_are_evenly_spaced_on_the_radius() {
+        let vertices = circle_vertices(3.0, 8);
+
+        assert_eq!(vertices.len(), 8);
+        assert_eq!(vertices[0], [3.0, 0.0]);
+        assert!(vertices[2][0].abs() < 1e-12 && (vertices[2][1] - 3.0).abs() < 1e-12);
+
+        let side = |a: [f64; 2], b: [f64; 2]| (a[0] - b[0]).hypot(a[1] - b[1]);
+        for (i, vertex) in vertices.iter().enumerate() {
+            let next = vertices[(i + 1) % vertices.len()];
+            assert!((vertex[0].hypot(vertex[1]) - 3.0).abs() < 1e-12);
+            assert!((side(*vertex, next) - side(vertices[0], vertices[1])).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn shape_switches_at_the_speed_threshold() {
+        let shape = |speed| shape_for_speed(speed, 5.0, ShapeKind::Circle, ShapeKind::Arrow);
+
+        assert_eq!(shape(0.0), ShapeKind::Circle);
+        assert_eq!(shape(4.99), ShapeKind::Circle);
+        assert_eq!(shape(5.0), ShapeKind::Arrow);
+        assert_eq!(shape(12.0), ShapeKind::Arrow);
+    }
+}
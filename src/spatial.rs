@@ -0,0 +1,439 @@
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+use crate::forces::softened_pull;
+
+pub trait NeighborQuery: Send + Sync {
+    // Indices of the points within `radius` of `point`, in no particular order.
+    fn within_radius(&self, point: [f64; 2], radius: f64) -> Vec<usize>;
+}
+
+fn distance_squared(a: [f64; 2], b: [f64; 2]) -> f64 {
+    let dx = a[0] - b[0];
+    let dy = a[1] - b[1];
+    dx * dx + dy * dy
+}
+
+// Which index neighbors are looked up in, written like
+// `{ type = "quad_tree", capacity = 8 }` in a config.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum SpatialIndexKind {
+    Grid { cell_size: f64 },
+    QuadTree { capacity: usize },
+}
+
+impl SpatialIndexKind {
+    pub fn build(&self, positions: &[[f64; 2]]) -> Box<dyn NeighborQuery> {
+        match *self {
+            SpatialIndexKind::Grid { cell_size } => {
+                Box::new(SpatialGrid::build(positions, cell_size))
+            }
+            SpatialIndexKind::QuadTree { capacity } => {
+                Box::new(QuadTree::build(positions, capacity))
+            }
+        }
+    }
+}
+
+// Uniform grid of square cells, good when entities are spread evenly.
+pub struct SpatialGrid {
+    cell_size: f64,
+    cells: HashMap<(i64, i64), Vec<usize>>,
+    positions: Vec<[f64; 2]>,
+}
+
+impl SpatialGrid {
+    pub fn build(positions: &[[f64; 2]], cell_size: f64) -> SpatialGrid {
+        let mut grid = SpatialGrid {
+            cell_size,
+            cells: HashMap::new(),
+            positions: positions.to_vec(),
+        };
+
+        for (index, position) in positions.iter().enumerate() {
+            let cell = grid.cell_of(*position);
+            grid.cells.entry(cell).or_default().push(index);
+        }
+
+        grid
+    }
+
+    fn cell_of(&self, position: [f64; 2]) -> (i64, i64) {
+        (
+            (position[0] / self.cell_size).floor() as i64,
+            (position[1] / self.cell_size).floor() as i64,
+        )
+    }
+}
+
+impl NeighborQuery for SpatialGrid {
+    fn within_radius(&self, point: [f64; 2], radius: f64) -> Vec<usize> {
+        let (min_x, min_y) = self.cell_of([point[0] - radius, point[1] - radius]);
+        let (max_x, max_y) = self.cell_of([point[0] + radius, point[1] + radius]);
+        let mut found = Vec::new();
+
+        for cx in min_x..=max_x {
+            for cy in min_y..=max_y {
+                if let Some(indices) = self.cells.get(&(cx, cy)) {
+                    found.extend(indices.iter().copied().filter(|&i| {
+                        distance_squared(self.positions[i], point) <= radius * radius
+                    }));
+                }
+            }
+        }
+
+        found
+    }
+}
+
+// Deeper than this, coincident points would keep subdividing forever.
+const MAX_DEPTH: usize = 16;
+
+struct Node {
+    // [x, y, width, height]
+    bounds: [f64; 4],
+    indices: Vec<usize>,
+    children: Option<Box<[Node; 4]>>,
+    depth: usize,
+    // Total mass and its center, for trees built with masses.
+    mass: f64,
+    center: [f64; 2],
+}
+
+impl Node {
+    fn new(bounds: [f64; 4], depth: usize) -> Node {
+        Node {
+            bounds,
+            indices: Vec::new(),
+            children: None,
+            depth,
+            mass: 0.0,
+            center: [0.0, 0.0],
+        }
+    }
+
+    fn aggregate(&mut self, positions: &[[f64; 2]], masses: &[f64]) {
+        let (mut mass, mut moment) = (0.0, [0.0, 0.0]);
+        let mut add = |m: f64, [x, y]: [f64; 2]| {
+            mass += m;
+            moment = [moment[0] + m * x, moment[1] + m * y];
+        };
+
+        match self.children.as_mut() {
+            Some(children) => {
+                for child in children.iter_mut() {
+                    child.aggregate(positions, masses);
+                    add(child.mass, child.center);
+                }
+            }
+            None => {
+                for &i in self.indices.iter() {
+                    add(masses[i], positions[i]);
+                }
+            }
+        }
+
+        self.mass = mass;
+        if mass > 0.0 {
+            self.center = [moment[0] / mass, moment[1] / mass];
+        }
+    }
+
+    fn contains(&self, point: [f64; 2]) -> bool {
+        let [x, y, w, h] = self.bounds;
+
+        point[0] >= x && point[0] <= x + w && point[1] >= y && point[1] <= y + h
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn gravity(
+        &self,
+        point: [f64; 2],
+        exclude: Option<usize>,
+        theta: f64,
+        strength: f64,
+        softening: f64,
+        tree: &QuadTree,
+        total: &mut [f64; 2],
+    ) {
+        if self.mass <= 0.0 {
+            return;
+        }
+
+        let mut add = |[ax, ay]: [f64; 2]| *total = [total[0] + ax, total[1] + ay];
+        let size = self.bounds[2].max(self.bounds[3]);
+        let distance = distance_squared(point, self.center).sqrt();
+
+        // Never lumped together with the point itself, which would pull on itself.
+        if !self.contains(point) && size < theta * distance {
+            add(softened_pull(
+                point,
+                self.center,
+                self.mass,
+                strength,
+                softening,
+            ));
+            return;
+        }
+
+        match &self.children {
+            Some(children) => {
+                for child in children.iter() {
+                    child.gravity(point, exclude, theta, strength, softening, tree, total);
+                }
+            }
+            None => {
+                for &i in self.indices.iter().filter(|&&i| Some(i) != exclude) {
+                    add(softened_pull(
+                        point,
+                        tree.positions[i],
+                        tree.masses[i],
+                        strength,
+                        softening,
+                    ));
+                }
+            }
+        }
+    }
+
+    fn quadrant(&self, position: [f64; 2]) -> usize {
+        let [x, y, w, h] = self.bounds;
+        let right = position[0] >= x + w / 2.0;
+        let bottom = position[1] >= y + h / 2.0;
+
+        match (right, bottom) {
+            (false, false) => 0,
+            (true, false) => 1,
+            (false, true) => 2,
+            (true, true) => 3,
+        }
+    }
+
+    fn insert(&mut self, index: usize, positions: &[[f64; 2]], capacity: usize) {
+        let quadrant = self.quadrant(positions[index]);
+
+        if let Some(children) = self.children.as_mut() {
+            children[quadrant].insert(index, positions, capacity);
+            return;
+        }
+
+        self.indices.push(index);
+
+        if self.indices.len() > capacity && self.depth < MAX_DEPTH {
+            self.subdivide(positions, capacity);
+        }
+    }
+
+    fn subdivide(&mut self, positions: &[[f64; 2]], capacity: usize) {
+        let [x, y, w, h] = self.bounds;
+        let (hw, hh) = (w / 2.0, h / 2.0);
+        let depth = self.depth + 1;
+
+        self.children = Some(Box::new([
+            Node::new([x, y, hw, hh], depth),
+            Node::new([x + hw, y, hw, hh], depth),
+            Node::new([x, y + hh, hw, hh], depth),
+            Node::new([x + hw, y + hh, hw, hh], depth),
+        ]));
+
+        for index in std::mem::take(&mut self.indices) {
+            self.insert(index, positions, capacity);
+        }
+    }
+
+    fn intersects_circle(&self, point: [f64; 2], radius: f64) -> bool {
+        let [x, y, w, h] = self.bounds;
+        let closest = [point[0].max(x).min(x + w), point[1].max(y).min(y + h)];
+
+        distance_squared(closest, point) <= radius * radius
+    }
+
+    fn query(&self, point: [f64; 2], radius: f64, positions: &[[f64; 2]], found: &mut Vec<usize>) {
+        if !self.intersects_circle(point, radius) {
+            return;
+        }
+
+        match &self.children {
+            Some(children) => {
+                for child in children.iter() {
+                    child.query(point, radius, positions, found);
+                }
+            }
+            None => found.extend(
+                self.indices
+                    .iter()
+                    .copied()
+                    .filter(|&i| distance_squared(positions[i], point) <= radius * radius),
+            ),
+        }
+    }
+
+    fn count(&self) -> usize {
+        match &self.children {
+            Some(children) => 1 + children.iter().map(Node::count).sum::<usize>(),
+            None => 1,
+        }
+    }
+}
+
+// Adaptive tree that subdivides crowded nodes, good for clumped distributions.
+pub struct QuadTree {
+    root: Node,
+    capacity: usize,
+    positions: Vec<[f64; 2]>,
+    // One per position for trees built with `build_weighted`, empty otherwise.
+    masses: Vec<f64>,
+}
+
+impl QuadTree {
+    // Points inserted later must lie within `bounds` ([x, y, width, height]).
+    pub fn new(bounds: [f64; 4], capacity: usize) -> QuadTree {
+        QuadTree {
+            root: Node::new(bounds, 0),
+            capacity: capacity.max(1),
+            positions: Vec::new(),
+            masses: Vec::new(),
+        }
+    }
+
+    // Like `build`, with every node also keeping the total mass under it and its center
+    // for `gravity`.
+    pub fn build_weighted(positions: &[[f64; 2]], masses: &[f64], capacity: usize) -> QuadTree {
+        let mut tree = QuadTree::build(positions, capacity);
+
+        tree.masses = masses.to_vec();
+        tree.masses.resize(positions.len(), 0.0);
+        tree.root.aggregate(&tree.positions, &tree.masses);
+
+        tree
+    }
+
+    // Barnes-Hut approximation of the acceleration at `point` from every weighted point
+    // but `exclude`, see `softened_pull`. Nodes smaller than `theta` times their distance
+    // pull as one body at their center of mass, so 0 gives the exact sum.
+    pub fn gravity(
+        &self,
+        point: [f64; 2],
+        exclude: Option<usize>,
+        theta: f64,
+        strength: f64,
+        softening: f64,
+    ) -> [f64; 2] {
+        let mut total = [0.0, 0.0];
+
+        self.root
+            .gravity(point, exclude, theta, strength, softening, self, &mut total);
+
+        total
+    }
+
+    // Sizes the root to the bounding box of the points.
+    pub fn build(positions: &[[f64; 2]], capacity: usize) -> QuadTree {
+        let mut min = [f64::INFINITY, f64::INFINITY];
+        let mut max = [f64::NEG_INFINITY, f64::NEG_INFINITY];
+
+        for position in positions {
+            min = [min[0].min(position[0]), min[1].min(position[1])];
+            max = [max[0].max(position[0]), max[1].max(position[1])];
+        }
+
+        let bounds = if positions.is_empty() {
+            [0.0, 0.0, 1.0, 1.0]
+        } else {
+            [min[0], min[1], max[0] - min[0], max[1] - min[1]]
+        };
+        let mut tree = QuadTree::new(bounds, capacity);
+
+        for position in positions {
+            tree.insert(*position);
+        }
+
+        tree
+    }
+
+    // Returns the index of the inserted point.
+    pub fn insert(&mut self, position: [f64; 2]) -> usize {
+        let index = self.positions.len();
+
+        self.positions.push(position);
+        self.root.insert(index, &self.positions, self.capacity);
+
+        index
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.root.count()
+    }
+}
+
+impl NeighborQuery for QuadTree {
+    fn within_radius(&self, point: [f64; 2], radius: f64) -> Vec<usize> {
+        let mut found = Vec::new();
+
+        self.root.query(point, radius, &self.positions, &mut found);
+
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::*;
+    use crate::forces::softened_pull;
+
+    #[test]
+    fn quadtree_subdivides_past_capacity_and_queries_across_nodes() {
+        let mut tree = QuadTree::new([0.0, 0.0, 100.0, 100.0], 2);
+
+        tree.insert([10.0, 10.0]);
+        tree.insert([90.0, 10.0]);
+        assert_eq!(tree.node_count(), 1);
+
+        tree.insert([45.0, 45.0]);
+        assert!(tree.node_count() > 1);
+        tree.insert([55.0, 55.0]);
+        tree.insert([90.0, 90.0]);
+
+        // Around the center, each point found lies in a different quadrant.
+        let mut found = tree.within_radius([50.0, 50.0], 10.0);
+        found.sort_unstable();
+        assert_eq!(found, vec![2, 3]);
+
+        let mut found = tree.within_radius([90.0, 50.0], 45.0);
+        found.sort_unstable();
+        assert_eq!(found, vec![1, 3, 4]);
+    }
+
+    #[test]
+    fn barnes_hut_stays_close_to_the_exact_sum() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let positions: Vec<[f64; 2]> = (0..200)
+            .map(|_| [rng.gen_range(0.0..500.0), rng.gen_range(0.0..500.0)])
+            .collect();
+        let masses: Vec<f64> = (0..200).map(|_| rng.gen_range(1.0..10.0)).collect();
+        let tree = QuadTree::build_weighted(&positions, &masses, 4);
+
+        for index in (0..200).step_by(17) {
+            let point = positions[index];
+            let mut exact = [0.0, 0.0];
+            for (other, (&position, &mass)) in positions.iter().zip(masses.iter()).enumerate() {
+                if other != index {
+                    let [ax, ay] = softened_pull(point, position, mass, 50.0, 1.0);
+                    exact = [exact[0] + ax, exact[1] + ay];
+                }
+            }
+            let error = |[ax, ay]: [f64; 2]| {
+                ((ax - exact[0]).powi(2) + (ay - exact[1]).powi(2)).sqrt()
+                    / (exact[0].powi(2) + exact[1].powi(2)).sqrt()
+            };
+
+            assert!(error(tree.gravity(point, Some(index), 0.0, 50.0, 1.0)) < 1e-9);
+            assert!(error(tree.gravity(point, Some(index), 0.5, 50.0, 1.0)) < 0.05);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::f64::consts::SQRT_2;
+
+use crate::entity::Physics;
+
+// Space left between the edges of two fresh children so they don't collide right away.
+const SPLIT_GAP: f64 = 0.5;
+
+// Divides an entity in two on collision, or once it grows to `split_size`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Split {
+    // Children smaller than this are never made.
+    pub min_size: f64,
+    pub split_size: Option<f64>,
+    // Speed each child moves away from the other at.
+    pub speed: f64,
+}
+
+impl Split {
+    pub fn triggered(&self, physics: &Physics, collided: bool) -> bool {
+        let grown = self.split_size.is_some_and(|size| physics.size >= size);
+
+        (collided || grown) && physics.size / SQRT_2 >= self.min_size
+    }
+
+    // Two children with half the parent's area each, pushed apart across its heading.
+    pub fn children(&self, parent: &Physics) -> (Physics, Physics) {
+        let size = parent.size / SQRT_2;
+        let speed = (parent.vx * parent.vx + parent.vy * parent.vy).sqrt();
+        let (nx, ny) = if speed > 0.0 {
+            (-parent.vy / speed, parent.vx / speed)
+        } else {
+            (-parent.rotation.sin(), parent.rotation.cos())
+        };
+        let offset = size / 2.0 + SPLIT_GAP;
+        let child = |side: f64| Physics {
+            x: parent.x + side * nx * offset,
+            y: parent.y + side * ny * offset,
+            size,
+            rotation: parent.rotation,
+            vx: parent.vx + side * nx * self.speed,
+            vy: parent.vy + side * ny * self.speed,
+            angular_velocity: parent.angular_velocity,
+        };
+
+        (child(1.0), child(-1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn children_share_the_parent_area_and_move_apart() {
+        let split = Split {
+            min_size: 1.0,
+            split_size: None,
+            speed: 3.0,
+        };
+        let mut parent = Physics::new(50.0, 50.0, 10.0).unwrap();
+        parent.vx = 4.0;
+
+        assert!(split.triggered(&parent, true));
+        let (a, b) = split.children(&parent);
+
+        assert!((a.size * a.size + b.size * b.size - parent.size * parent.size).abs() < 1e-9);
+        let offset = [a.x - b.x, a.y - b.y];
+        let closing = [a.vx - b.vx, a.vy - b.vy];
+        assert!(offset[0] * closing[0] + offset[1] * closing[1] > 0.0);
+        assert!(offset[0].hypot(offset[1]) > a.size);
+        // Along the heading they keep the parent's velocity.
+        assert_eq!((a.vx, b.vx), (4.0, 4.0));
+    }
+}
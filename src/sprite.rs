@@ -0,0 +1,142 @@
+use graphics::math::identity;
+use graphics::Transformed;
+use opengl_graphics::{Texture, TextureSettings};
+
+use crate::entity::Physics;
+use crate::error::NatureError;
+use crate::shapes::{DrawCommand, DrawCommands, Geometry};
+
+// Handle to an atlas texture loaded on the app.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct AtlasId(pub usize);
+
+// Grid of equally sized cells in an atlas image, numbered row by row from the top-left.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AtlasLayout {
+    pub columns: usize,
+    pub rows: usize,
+    // In pixels.
+    pub cell_size: [f64; 2],
+}
+
+impl AtlasLayout {
+    // Pixel rectangle `[x, y, width, height]` of a cell, none past the last one.
+    pub fn source_rect(&self, index: usize) -> Option<[f64; 4]> {
+        if index >= self.columns * self.rows {
+            return None;
+        }
+
+        let [width, height] = self.cell_size;
+        let (column, row) = (index % self.columns, index / self.columns);
+
+        Some([column as f64 * width, row as f64 * height, width, height])
+    }
+
+    pub fn texture_size(&self) -> [f64; 2] {
+        [
+            self.columns as f64 * self.cell_size[0],
+            self.rows as f64 * self.cell_size[1],
+        ]
+    }
+}
+
+// Texture coordinates `[u, v, width, height]` of a pixel rectangle, 0 to 1 across the
+// texture with v growing downwards.
+pub fn uv_rect(source: [f64; 4], texture_size: [f64; 2]) -> [f64; 4] {
+    [
+        source[0] / texture_size[0],
+        source[1] / texture_size[1],
+        source[2] / texture_size[0],
+        source[3] / texture_size[1],
+    ]
+}
+
+pub struct Atlas {
+    pub texture: Texture,
+    pub layout: AtlasLayout,
+}
+
+impl Atlas {
+    pub fn load(path: &str, layout: AtlasLayout) -> Result<Atlas, NatureError> {
+        let texture = Texture::from_path(path, &TextureSettings::new())
+            .map_err(|error| NatureError::Texture(format!("{}: {}", path, error)))?;
+
+        Ok(Atlas { texture, layout })
+    }
+}
+
+// One cell of an atlas drawn as a square of the entity's size, so every sprite of the
+// atlas shares its texture.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Sprite {
+    pub atlas: AtlasId,
+    // Texture coordinates of the cell, see `uv_rect`.
+    pub uv: [f64; 4],
+}
+
+impl Sprite {
+    pub fn new(atlas: AtlasId, layout: &AtlasLayout, index: usize) -> Result<Sprite, NatureError> {
+        let source = layout.source_rect(index).ok_or_else(|| {
+            NatureError::InvalidParameter(format!(
+                "atlas cell {} is past the {}x{} layout",
+                index, layout.columns, layout.rows
+            ))
+        })?;
+
+        Ok(Sprite {
+            atlas,
+            uv: uv_rect(source, layout.texture_size()),
+        })
+    }
+}
+
+impl Geometry for Sprite {
+    // Textures have no single color to swap, `color` is ignored.
+    fn geometry(&self, physics: Physics, _color: Option<[f32; 4]>) -> DrawCommands {
+        let size = physics.size;
+        let transform = identity()
+            .trans(physics.x, physics.y)
+            .rot_rad(physics.rotation)
+            .trans(-size / 2.0, -size / 2.0);
+        let [u, v, w, h] = self.uv;
+        let corners = [[0.0, 0.0], [size, 0.0], [size, size], [0.0, size]];
+        let uvs = [[u, v], [u + w, v], [u + w, v + h], [u, v + h]];
+        let triangles = [0, 1, 2, 0, 2, 3];
+
+        vec![DrawCommand::Textured {
+            atlas: self.atlas,
+            positions: triangles.iter().map(|&i| corners[i]).collect(),
+            uvs: triangles
+                .iter()
+                .map(|&i| [uvs[i][0] as f32, uvs[i][1] as f32])
+                .collect(),
+            transform,
+        }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sprite_cells_map_to_their_atlas_uvs() {
+        let layout = AtlasLayout {
+            columns: 4,
+            rows: 2,
+            cell_size: [32.0, 64.0],
+        };
+
+        assert_eq!(layout.texture_size(), [128.0, 128.0]);
+        assert_eq!(layout.source_rect(6), Some([64.0, 64.0, 32.0, 64.0]));
+        assert_eq!(layout.source_rect(8), None);
+
+        let sprite = Sprite::new(AtlasId(0), &layout, 6).unwrap();
+        assert_eq!(sprite.uv, [0.5, 0.5, 0.25, 0.5]);
+        assert_eq!(
+            Sprite::new(AtlasId(0), &layout, 0).unwrap().uv,
+            [0.0, 0.0, 0.25, 0.5]
+        );
+        assert!(Sprite::new(AtlasId(0), &layout, 8).is_err());
+    }
+}
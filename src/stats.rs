@@ -0,0 +1,104 @@
+// Work done by the simulation, summed until reset. The app resets it every frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub steps: u64,
+    pub entities_updated: u64,
+    // Overlapping pairs bounced or merged.
+    pub collisions: u64,
+    pub spawned: u64,
+    pub despawned: u64,
+    pub neighbor_queries: u64,
+}
+
+impl Stats {
+    pub fn reset(&mut self) {
+        *self = Stats::default();
+    }
+}
+
+// Aggregate state of the world after a tick, see `csv_row`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Metrics {
+    pub tick: u64,
+    pub entities: usize,
+    pub mean_speed: f64,
+    pub centroid: [f64; 2],
+    // Root mean square distance from the centroid.
+    pub spread: f64,
+    pub collisions: u64,
+}
+
+// Columns of `Metrics::csv_row`.
+pub const METRICS_CSV_HEADER: &str =
+    "tick,entities,mean_speed,centroid_x,centroid_y,spread,collisions";
+
+impl Metrics {
+    // Metrics of entities at `positions` moving at `velocities`, which line up. All
+    // zero but the tick and collisions without entities.
+    pub fn measure(
+        tick: u64,
+        positions: &[[f64; 2]],
+        velocities: &[[f64; 2]],
+        collisions: u64,
+    ) -> Metrics {
+        let mut metrics = Metrics {
+            tick,
+            entities: positions.len(),
+            collisions,
+            ..Metrics::default()
+        };
+        if positions.is_empty() {
+            return metrics;
+        }
+
+        let count = positions.len() as f64;
+        let [x, y] = positions
+            .iter()
+            .fold([0.0, 0.0], |[x, y], point| [x + point[0], y + point[1]]);
+        let centroid = [x / count, y / count];
+        let squared: f64 = positions
+            .iter()
+            .map(|point| (point[0] - centroid[0]).powi(2) + (point[1] - centroid[1]).powi(2))
+            .sum();
+
+        metrics.centroid = centroid;
+        metrics.spread = (squared / count).sqrt();
+        metrics.mean_speed = velocities
+            .iter()
+            .map(|velocity| velocity[0].hypot(velocity[1]))
+            .sum::<f64>()
+            / count;
+        metrics
+    }
+
+    // One line of the metrics CSV, without the line break.
+    pub fn csv_row(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{}",
+            self.tick,
+            self.entities,
+            self.mean_speed,
+            self.centroid[0],
+            self.centroid[1],
+            self.spread,
+            self.collisions
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_row_lines_up_with_the_header() {
+        let metrics = Metrics::measure(7, &[[0.0, 0.0], [6.0, 8.0]], &[[3.0, 4.0], [0.0, 0.0]], 2);
+
+        assert_eq!(metrics.csv_row(), "7,2,2.5,3,4,5,2");
+        assert_eq!(
+            metrics.csv_row().split(',').count(),
+            METRICS_CSV_HEADER.split(',').count()
+        );
+        assert_eq!(Metrics::measure(3, &[], &[], 1).csv_row(), "3,0,0,0,0,0,1");
+    }
+}
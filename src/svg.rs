@@ -0,0 +1,95 @@
+use std::collections::BTreeMap;
+
+use crate::color::color_from_id;
+use crate::coords::CoordinateSystem;
+
+// Positions of each entity over a run, by id so the output keeps a stable order.
+pub type Trajectories = BTreeMap<u32, Vec<[f64; 2]>>;
+
+const STROKE_WIDTH: f64 = 1.0;
+
+// Rounded to hundredths, finer than any screen shows.
+fn coordinate(value: f64) -> f64 {
+    let rounded = (value * 100.0).round() / 100.0;
+
+    // Keeps -0 from showing up as "-0".
+    if rounded == 0.0 {
+        0.0
+    } else {
+        rounded
+    }
+}
+
+// `d` attribute of a polyline through the points, "M x y L x y ...". Empty for no points.
+pub fn path_data(points: &[[f64; 2]]) -> String {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, &[x, y])| {
+            let command = if i == 0 { 'M' } else { 'L' };
+            format!("{} {} {}", command, coordinate(x), coordinate(y))
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+// "#rrggbb" of the color `color_from_id` gives.
+pub fn id_color_hex(id: u32) -> String {
+    let [r, g, b, _] = color_from_id(id);
+    let byte = |channel: f32| (channel.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+    format!("#{:02x}{:02x}{:02x}", byte(r), byte(g), byte(b))
+}
+
+// Document the size of the world with one path per entity, colored by id. Points are
+// moved to the top-left system first, which is the one SVG uses.
+pub fn trajectories_svg(
+    trajectories: &Trajectories,
+    width: f64,
+    height: f64,
+    coordinates: CoordinateSystem,
+) -> String {
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">\n",
+        width, height, width, height
+    );
+
+    for (&id, points) in trajectories.iter().filter(|(_, points)| !points.is_empty()) {
+        let points: Vec<[f64; 2]> = points
+            .iter()
+            .map(|&point| coordinates.to_top_left(point, width, height))
+            .collect();
+
+        svg.push_str(&format!(
+            "  <path d=\"{}\" fill=\"none\" stroke=\"{}\" stroke-width=\"{}\"/>\n",
+            path_data(&points),
+            id_color_hex(id),
+            STROKE_WIDTH
+        ));
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_data_traces_the_points_in_order() {
+        assert_eq!(
+            path_data(&[[0.0, 0.0], [10.5, 20.0], [-0.001, 4.56789]]),
+            "M 0 0 L 10.5 20 L 0 4.57"
+        );
+        assert_eq!(path_data(&[]), "");
+
+        let mut trajectories = Trajectories::new();
+        trajectories.insert(7, vec![[1.0, 2.0], [3.0, 4.0]]);
+        let svg = trajectories_svg(&trajectories, 100.0, 50.0, CoordinateSystem::TopLeft);
+        assert!(svg.contains(&format!(
+            "<path d=\"M 1 2 L 3 4\" fill=\"none\" stroke=\"{}\"",
+            id_color_hex(7)
+        )));
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::VecDeque;
+
+use graphics::{line, Context};
+use opengl_graphics::GlGraphics;
+use serde::Deserialize;
+
+// Alpha left at the tail of an exponential trail, e^-4 being under 2%.
+const EXPONENTIAL_RATE: f32 = 4.0;
+
+// How the alpha of a trail fades from the head back to the tail.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TrailDecay {
+    #[default]
+    Linear,
+    // Stays bright near the head then falls off quickly, the punchier look.
+    Exponential,
+    // Fades faster than linear, softer towards the tail.
+    Quadratic,
+}
+
+impl TrailDecay {
+    // Alpha at `t` along the trail, 0 at the tail and 1 at the head, t clamped to that
+    // range.
+    pub fn alpha(&self, t: f32) -> f32 {
+        let t = t.clamp(0.0, 1.0);
+
+        match self {
+            TrailDecay::Linear => t,
+            TrailDecay::Exponential => (-EXPONENTIAL_RATE * (1.0 - t)).exp(),
+            TrailDecay::Quadratic => t * t,
+        }
+    }
+}
+
+// Recent positions of an entity, oldest first.
+pub struct Trail {
+    pub points: VecDeque<[f64; 2]>,
+    pub capacity: usize,
+    pub color: [f32; 4],
+    pub width: f64,
+    pub decay: TrailDecay,
+}
+
+impl Trail {
+    pub fn new(capacity: usize, color: [f32; 4], width: f64) -> Trail {
+        Trail {
+            points: VecDeque::with_capacity(capacity + 1),
+            capacity,
+            color,
+            width,
+            decay: TrailDecay::Linear,
+        }
+    }
+
+    pub fn with_decay(mut self, decay: TrailDecay) -> Trail {
+        self.decay = decay;
+        self
+    }
+
+    pub fn record(&mut self, position: [f64; 2]) {
+        self.points.push_back(position);
+
+        while self.points.len() > self.capacity {
+            self.points.pop_front();
+        }
+    }
+
+    // Line segments from tail to head, with alpha rising along the decay curve to 1 at the
+    // head.
+    pub fn segments(&self) -> Vec<([f64; 4], f32)> {
+        let count = self.points.len().saturating_sub(1);
+
+        self.points
+            .iter()
+            .zip(self.points.iter().skip(1))
+            .enumerate()
+            .map(|(i, (from, to))| {
+                let alpha = self.decay.alpha((i + 1) as f32 / count as f32);
+                ([from[0], from[1], to[0], to[1]], alpha)
+            })
+            .collect()
+    }
+
+    pub fn draw(&self, gl: &mut GlGraphics, c: Context) {
+        for (segment, alpha) in self.segments() {
+            let color = [
+                self.color[0],
+                self.color[1],
+                self.color[2],
+                self.color[3] * alpha,
+            ];
+
+            line(color, self.width / 2.0, segment, c.transform, gl);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn segments_join_the_recent_points_brightest_at_the_head() {
+        let mut trail = Trail::new(3, [1.0; 4], 1.0);
+
+        assert!(trail.segments().is_empty());
+        for x in 0..4 {
+            trail.record([x as f64, 0.0]);
+        }
+
+        assert_eq!(
+            trail.segments(),
+            vec![([1.0, 0.0, 2.0, 0.0], 0.5), ([2.0, 0.0, 3.0, 0.0], 1.0)]
+        );
+    }
+
+    #[test]
+    fn decay_curves_at_the_head_middle_and_tail() {
+        for decay in &[
+            TrailDecay::Linear,
+            TrailDecay::Exponential,
+            TrailDecay::Quadratic,
+        ] {
+            assert_eq!(decay.alpha(1.0), 1.0);
+            assert_eq!(decay.alpha(2.0), 1.0);
+        }
+
+        assert_eq!(TrailDecay::Linear.alpha(0.5), 0.5);
+        assert_eq!(TrailDecay::Quadratic.alpha(0.5), 0.25);
+        assert!(
+            (TrailDecay::Exponential.alpha(0.5) - (-EXPONENTIAL_RATE * 0.5).exp()).abs() < 1e-6
+        );
+
+        assert_eq!(TrailDecay::Linear.alpha(0.0), 0.0);
+        assert_eq!(TrailDecay::Quadratic.alpha(-1.0), 0.0);
+        assert!((TrailDecay::Exponential.alpha(0.0) - (-EXPONENTIAL_RATE).exp()).abs() < 1e-6);
+        // Quadratic fades faster than linear.
+        assert!(TrailDecay::Quadratic.alpha(0.9) < TrailDecay::Linear.alpha(0.9));
+    }
+}
@@ -0,0 +1,70 @@
+// Global settings that can be tuned live from the HUD.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Tunable {
+    TimeScale,
+    Damping,
+    WindStrength,
+}
+
+impl Tunable {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Tunable::TimeScale => "time scale",
+            Tunable::Damping => "damping",
+            Tunable::WindStrength => "wind strength",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Parameter {
+    pub tunable: Tunable,
+    pub value: f64,
+    // Change per key press.
+    pub step: f64,
+    pub min: f64,
+    pub max: f64,
+}
+
+impl Parameter {
+    pub fn new(tunable: Tunable, value: f64, step: f64, min: f64, max: f64) -> Parameter {
+        Parameter {
+            tunable,
+            value: value.clamp(min, max),
+            step,
+            min,
+            max,
+        }
+    }
+
+    // Value after moving by `steps` steps, negative to decrease, kept within [min, max].
+    pub fn stepped(&self, steps: i32) -> f64 {
+        (self.value + steps as f64 * self.step).clamp(self.min, self.max)
+    }
+
+    pub fn nudge(&mut self, steps: i32) {
+        self.value = self.stepped(steps);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_stay_within_min_and_max() {
+        let mut parameter = Parameter::new(Tunable::TimeScale, 1.0, 0.25, 0.0, 2.0);
+
+        parameter.nudge(2);
+        assert_eq!(parameter.value, 1.5);
+        parameter.nudge(10);
+        assert_eq!(parameter.value, 2.0);
+        parameter.nudge(-3);
+        assert_eq!(parameter.value, 1.25);
+        assert_eq!(parameter.stepped(-100), 0.0);
+        assert_eq!(
+            Parameter::new(Tunable::Damping, 5.0, 0.1, 0.0, 1.0).value,
+            1.0
+        );
+    }
+}
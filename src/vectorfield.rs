@@ -0,0 +1,131 @@
+use crate::error::NatureError;
+
+// Grid of directions for flow art, sampled at the corners of its cells row by row from
+// `origin` and blended bilinearly in between. Unlike lanes and flow fields the vectors
+// keep their length, so weak spots of the field steer less.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VectorField {
+    // First sample, the one with the smallest coordinates.
+    pub origin: [f64; 2],
+    // Distance between neighboring samples.
+    pub cell_size: f64,
+    pub columns: usize,
+    // `columns` per row.
+    vectors: Vec<[f64; 2]>,
+}
+
+impl VectorField {
+    // A partial last row is rejected.
+    pub fn new(
+        origin: [f64; 2],
+        cell_size: f64,
+        columns: usize,
+        vectors: Vec<[f64; 2]>,
+    ) -> Result<VectorField, NatureError> {
+        if !(cell_size.is_finite() && cell_size > 0.0) {
+            return Err(NatureError::InvalidParameter(format!(
+                "vector field cells need a positive size, got {}",
+                cell_size
+            )));
+        }
+        if columns == 0 || vectors.is_empty() || !vectors.len().is_multiple_of(columns) {
+            return Err(NatureError::InvalidParameter(format!(
+                "{} field vectors don't fill rows of {}",
+                vectors.len(),
+                columns
+            )));
+        }
+
+        Ok(VectorField {
+            origin,
+            cell_size,
+            columns,
+            vectors,
+        })
+    }
+
+    // One sample per pixel, the first row of the image at `origin`. Red and green are
+    // the x and y of the direction, from -1 at 0 to 1 at 255, scaled by the alpha so
+    // transparent pixels have none.
+    pub fn from_image(
+        path: &str,
+        origin: [f64; 2],
+        cell_size: f64,
+    ) -> Result<VectorField, NatureError> {
+        let image = image::open(path)
+            .map_err(|error| NatureError::Texture(format!("{}: {}", path, error)))?
+            .to_rgba8();
+        let vectors = image.pixels().map(|pixel| pixel_vector(pixel.0)).collect();
+
+        VectorField::new(origin, cell_size, image.width() as usize, vectors)
+    }
+
+    pub fn rows(&self) -> usize {
+        self.vectors.len() / self.columns
+    }
+
+    // Blended vector at `point`, None outside the samples.
+    pub fn sample(&self, point: [f64; 2]) -> Option<[f64; 2]> {
+        let grid = [
+            (point[0] - self.origin[0]) / self.cell_size,
+            (point[1] - self.origin[1]) / self.cell_size,
+        ];
+
+        if !(grid[0] >= 0.0
+            && grid[1] >= 0.0
+            && grid[0] <= (self.columns - 1) as f64
+            && grid[1] <= (self.rows() - 1) as f64)
+        {
+            return None;
+        }
+
+        Some(bilinear(&self.vectors, self.columns, grid))
+    }
+}
+
+// Direction an image pixel stands for, see `VectorField::from_image`.
+pub fn pixel_vector([red, green, _, alpha]: [u8; 4]) -> [f64; 2] {
+    let channel = |value: u8| value as f64 / 127.5 - 1.0;
+    let alpha = alpha as f64 / 255.0;
+
+    [channel(red) * alpha, channel(green) * alpha]
+}
+
+// Blend of the four samples around `grid`, given in samples from the first and within
+// the rows of `columns` of `vectors`.
+pub fn bilinear(vectors: &[[f64; 2]], columns: usize, grid: [f64; 2]) -> [f64; 2] {
+    let rows = vectors.len() / columns;
+    let column = (grid[0].floor() as usize).min(columns.saturating_sub(2));
+    let row = (grid[1].floor() as usize).min(rows.saturating_sub(2));
+    let next_column = (column + 1).min(columns - 1);
+    let next_row = (row + 1).min(rows - 1);
+    let tx = (grid[0] - column as f64).clamp(0.0, 1.0);
+    let ty = (grid[1] - row as f64).clamp(0.0, 1.0);
+
+    let at = |column: usize, row: usize| vectors[row * columns + column];
+    let mix =
+        |a: [f64; 2], b: [f64; 2], t: f64| [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t];
+
+    let bottom = mix(at(column, row), at(next_column, row), tx);
+    let top = mix(at(column, next_row), at(next_column, next_row), tx);
+
+    mix(bottom, top, ty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bilinear_center_of_a_cell_averages_its_corners() {
+        let vectors = vec![[1.0, 0.0], [3.0, 2.0], [5.0, 4.0], [-1.0, 6.0]];
+
+        assert_eq!(bilinear(&vectors, 2, [0.5, 0.5]), [2.0, 3.0]);
+        assert_eq!(bilinear(&vectors, 2, [1.0, 0.0]), [3.0, 2.0]);
+        assert_eq!(bilinear(&vectors, 2, [0.5, 0.0]), [2.0, 1.0]);
+
+        let field = VectorField::new([10.0, 10.0], 4.0, 2, vectors).unwrap();
+        assert_eq!(field.sample([12.0, 12.0]), Some([2.0, 3.0]));
+        assert_eq!(field.sample([15.0, 12.0]), None);
+    }
+}
@@ -0,0 +1,1933 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use serde::Deserialize;
+
+use crate::behavior::{
+    centroid, compute_steerings, step_rng, turn_towards, BehaviorContext, Snapshot, Steering, AI,
+};
+use crate::budget::{in_window, TickBudget};
+use crate::changelog::TickLog;
+use crate::collision::{
+    bounce, bounce_off, collisions, free_position, layers_collide, mass, merged, overlapping,
+    reflect_off_wall, CollisionResponse, SpawnOverlap, Wall,
+};
+use crate::color::{flash_color, lerp_color, multiply_color, Daylight};
+use crate::coords::CoordinateSystem;
+use crate::emitter::Emitter;
+use crate::entity::{Entity, EntityKind, KindId, Physics, Renderer};
+use crate::epidemic::Epidemic;
+use crate::error::NatureError;
+use crate::field::ScalarField;
+use crate::flowfield::FlowField;
+use crate::forces::{drag_keep, Attractor, Wind};
+use crate::integrator::{substep_count, Integrator};
+use crate::lanes::LaneField;
+use crate::life::{next_generation, Cell, Life};
+use crate::links::{Link, Linking};
+use crate::pathfinding::NavGrid;
+use crate::pheromone::PheromoneGrid;
+use crate::portal::PortalPair;
+use crate::reaction::Reaction;
+use crate::regions::RegionMap;
+use crate::save::SavedWorld;
+use crate::shapes::Drawable;
+use crate::spatial::{NeighborQuery, SpatialIndexKind};
+use crate::stats::Stats;
+use crate::vectorfield::VectorField;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Boundary {
+    // The world rectangle, its corners set by the coordinate system.
+    Rect,
+    Circle { center: [f64; 2], radius: f64 },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BoundaryMode {
+    Bounce,
+    Wrap,
+    // Entities whose center leaves are despawned.
+    Destroy,
+}
+
+impl Boundary {
+    pub fn contains(&self, point: [f64; 2], bounds: ([f64; 2], [f64; 2])) -> bool {
+        match *self {
+            Boundary::Rect => {
+                let (min, max) = bounds;
+                point[0] >= min[0] && point[0] <= max[0] && point[1] >= min[1] && point[1] <= max[1]
+            }
+            Boundary::Circle { center, radius } => {
+                let [dx, dy] = [point[0] - center[0], point[1] - center[1]];
+                dx * dx + dy * dy <= radius * radius
+            }
+        }
+    }
+
+    pub fn enforce(&self, physics: &mut Physics, mode: BoundaryMode, bounds: ([f64; 2], [f64; 2])) {
+        match *self {
+            Boundary::Rect => enforce_rect(physics, mode, bounds),
+            Boundary::Circle { center, radius } => enforce_circle(physics, mode, center, radius),
+        }
+    }
+}
+
+pub fn enforce_rect(physics: &mut Physics, mode: BoundaryMode, bounds: ([f64; 2], [f64; 2])) {
+    let (min, max) = bounds;
+
+    match mode {
+        BoundaryMode::Bounce => {
+            let radius = physics.size / 2.0;
+
+            if physics.x < min[0] + radius {
+                physics.x = min[0] + radius;
+                physics.vx = physics.vx.abs();
+            } else if physics.x > max[0] - radius {
+                physics.x = max[0] - radius;
+                physics.vx = -physics.vx.abs();
+            }
+
+            if physics.y < min[1] + radius {
+                physics.y = min[1] + radius;
+                physics.vy = physics.vy.abs();
+            } else if physics.y > max[1] - radius {
+                physics.y = max[1] - radius;
+                physics.vy = -physics.vy.abs();
+            }
+        }
+        BoundaryMode::Wrap => {
+            physics.x = min[0] + (physics.x - min[0]).rem_euclid(max[0] - min[0]);
+            physics.y = min[1] + (physics.y - min[1]).rem_euclid(max[1] - min[1]);
+        }
+        BoundaryMode::Destroy => {}
+    }
+}
+
+pub fn enforce_circle(physics: &mut Physics, mode: BoundaryMode, center: [f64; 2], radius: f64) {
+    let dx = physics.x - center[0];
+    let dy = physics.y - center[1];
+    let distance = (dx * dx + dy * dy).sqrt();
+    let limit = match mode {
+        BoundaryMode::Bounce => (radius - physics.size / 2.0).max(0.0),
+        BoundaryMode::Wrap | BoundaryMode::Destroy => radius,
+    };
+
+    if distance <= limit || distance == 0.0 {
+        return;
+    }
+
+    let (nx, ny) = (dx / distance, dy / distance);
+
+    match mode {
+        BoundaryMode::Bounce => {
+            physics.x = center[0] + nx * limit;
+            physics.y = center[1] + ny * limit;
+
+            // Reflect the outward part of the velocity about the boundary normal.
+            let outward = physics.vx * nx + physics.vy * ny;
+            if outward > 0.0 {
+                physics.vx -= 2.0 * outward * nx;
+                physics.vy -= 2.0 * outward * ny;
+            }
+        }
+        BoundaryMode::Wrap => {
+            physics.x = center[0] - nx * limit;
+            physics.y = center[1] - ny * limit;
+        }
+        BoundaryMode::Destroy => {}
+    }
+}
+
+// Entities slower than `speed` for `ticks` steps in a row stop being updated.
+#[derive(Clone, Copy, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Sleep {
+    pub speed: f64,
+    pub ticks: u32,
+}
+
+// What makes an entity flash.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlashEvent {
+    Collision,
+    Spawn,
+    Selection,
+}
+
+// Entities briefly drawn in `color` on the `events`, fading back over `duration` seconds
+// of simulated time.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Flash {
+    pub color: [f32; 4],
+    pub duration: f64,
+    pub events: Vec<FlashEvent>,
+}
+
+pub struct World {
+    pub entities: Vec<Entity>,
+    pub kinds: Vec<EntityKind>,
+    pub width: f64,
+    pub height: f64,
+    pub boundary: Boundary,
+    pub boundary_mode: BoundaryMode,
+    // Convention for every position in the world, see `set_coordinates` to change it.
+    pub coordinates: CoordinateSystem,
+    pub spatial_index: SpatialIndexKind,
+    pub collision_response: CollisionResponse,
+    // Bounciness of collisions, from 0 for none to 1 for perfectly elastic.
+    pub restitution: f64,
+    // Whether clicks, emitters and splits may spawn into an existing entity.
+    pub spawn_overlap: SpawnOverlap,
+    pub integrator: Integrator,
+    // Most substeps a step may be split into so fast entities don't tunnel, 1 for none.
+    pub max_substeps: usize,
+    // Segments every entity bounces off, with the collision restitution.
+    pub walls: Vec<Wall>,
+    // Entities entering one end of a pair come out of the other.
+    pub portals: Vec<PortalPair>,
+    pub wind: Option<Wind>,
+    pub attractors: Vec<Attractor>,
+    // Waypoints flocks migrate along, see `Flock::migration`.
+    pub migration_path: Vec<[f64; 2]>,
+    pub emitters: Vec<Emitter>,
+    // Flow directions followed by lane behaviors, in world coordinates as they are.
+    pub lanes: Option<LaneField>,
+    pub life: Option<Life>,
+    pub epidemic: Option<Epidemic>,
+    pub reaction: Option<Reaction>,
+    // Springs formed and broken between nearby entities, see `Linking`.
+    pub linking: Option<Linking>,
+    // Areas within the boundary entities are kept in, crossing only through openings.
+    pub regions: Option<RegionMap>,
+    pub links: Vec<Link>,
+    // Scalar quantity gradient-following behaviors climb, in world coordinates as is.
+    pub field: Option<ScalarField>,
+    // Directions to a shared goal followed by pathing behaviors, in world coordinates as is.
+    pub flow_field: Option<FlowField>,
+    // Followed by flow art behaviors, in world coordinates as is.
+    pub vector_field: Option<VectorField>,
+    // Searched for paths around its blocked cells, in world coordinates as is.
+    pub nav_grid: Option<NavGrid>,
+    // Laid and followed by ants, fading every step. In world coordinates as is.
+    pub pheromones: Option<PheromoneGrid>,
+    // Seconds simulated so far.
+    pub sim_time: f64,
+    // Number of completed steps.
+    pub tick: u64,
+    // Ids removed at the end of the current step, a set as it's checked against every
+    // entity.
+    pub despawn_queue: HashSet<u32>,
+    // Point of interest set interactively, sought by goal-seeking behaviors.
+    pub goal: Option<[f64; 2]>,
+    // Fraction of its velocity an entity loses per second.
+    pub damping: f64,
+    // Air drag, an extra loss per second for each unit of size so bigger entities slow
+    // down faster.
+    pub drag: f64,
+    // Seconds over which new entities grow from nothing to full size on screen, 0 to
+    // show them at full size right away.
+    pub spawn_ease: f64,
+    // One entry per step while set, left off by default as the diff costs a pass over
+    // every entity.
+    pub changelog: Option<Vec<TickLog>>,
+    // Counters since the last reset, see `take_stats`.
+    pub stats: Stats,
+    // Feedback on events, none by default.
+    pub flash: Option<Flash>,
+    // Tints every drawn color with the time of day, none by default.
+    pub daylight: Option<Daylight>,
+    // Lets resting entities sleep when set, off by default.
+    pub sleep: Option<Sleep>,
+    // Spawning beyond this many entities evicts the oldest.
+    pub max_entities: Option<usize>,
+    // Entities older than this many seconds are despawned at the end of the step,
+    // whatever their lifetime, so endless emitters stay bounded.
+    pub max_age: Option<f64>,
+    // Seeds every random draw of the simulation when set, for reproducible runs.
+    pub seed: Option<u64>,
+    // Updates entities by increasing id rather than storage order, so a step doesn't
+    // depend on how spawns and despawns shuffled `entities`.
+    pub deterministic_order: bool,
+    // Limits the time behaviors take per step when set, updating entities in turns.
+    pub budget: Option<TickBudget>,
+    // Position in `entities` of each id, kept in sync by spawn and despawn.
+    index_of: HashMap<u32, usize>,
+    next_id: u32,
+}
+
+impl World {
+    pub fn new(entities: Vec<Entity>, width: f64, height: f64) -> Result<World, NatureError> {
+        if !(width.is_finite() && width > 0.0 && height.is_finite() && height > 0.0) {
+            return Err(NatureError::InvalidParameter(format!(
+                "world size must be positive, got {}x{}",
+                width, height
+            )));
+        }
+
+        let next_id = entities.iter().map(|e| e._id + 1).max().unwrap_or(0);
+        let mut world = World {
+            entities,
+            kinds: Vec::new(),
+            width,
+            height,
+            boundary: Boundary::Rect,
+            boundary_mode: BoundaryMode::Bounce,
+            coordinates: CoordinateSystem::TopLeft,
+            spatial_index: SpatialIndexKind::Grid { cell_size: 50.0 },
+            collision_response: CollisionResponse::Ignore,
+            restitution: 1.0,
+            spawn_overlap: SpawnOverlap::Allow,
+            integrator: Integrator::Euler,
+            max_substeps: 1,
+            walls: Vec::new(),
+            portals: Vec::new(),
+            wind: None,
+            attractors: Vec::new(),
+            migration_path: Vec::new(),
+            emitters: Vec::new(),
+            lanes: None,
+            life: None,
+            epidemic: None,
+            reaction: None,
+            linking: None,
+            regions: None,
+            links: Vec::new(),
+            field: None,
+            flow_field: None,
+            vector_field: None,
+            nav_grid: None,
+            pheromones: None,
+            sim_time: 0.0,
+            tick: 0,
+            despawn_queue: HashSet::new(),
+            goal: None,
+            damping: 0.0,
+            drag: 0.0,
+            spawn_ease: 0.0,
+            changelog: None,
+            stats: Stats::default(),
+            max_entities: None,
+            max_age: None,
+            seed: None,
+            deterministic_order: false,
+            budget: None,
+            flash: None,
+            daylight: None,
+            sleep: None,
+            index_of: HashMap::new(),
+            next_id,
+        };
+
+        world.rebuild_index();
+        Ok(world)
+    }
+
+    // Entity states and the clock as TOML, see `SavedWorld` for what is kept.
+    pub fn save(&self) -> Result<String, NatureError> {
+        SavedWorld::capture(self).to_toml()
+    }
+
+    pub fn load(text: &str) -> Result<World, NatureError> {
+        SavedWorld::from_toml(text)?.restore()
+    }
+
+    pub fn bounds(&self) -> ([f64; 2], [f64; 2]) {
+        self.coordinates.bounds(self.width, self.height)
+    }
+
+    // Converts every stored position to another coordinate system. Velocities and
+    // rotations keep their on-screen direction, so y components flip along with y.
+    pub fn set_coordinates(&mut self, coordinates: CoordinateSystem) {
+        if coordinates == self.coordinates {
+            return;
+        }
+
+        let (from, width, height) = (self.coordinates, self.width, self.height);
+        let convert = |point: [f64; 2]| {
+            coordinates.from_top_left(from.to_top_left(point, width, height), width, height)
+        };
+
+        for entity in self.entities.iter_mut() {
+            let physics = &mut entity.physics;
+            let [x, y] = convert([physics.x, physics.y]);
+
+            physics.x = x;
+            physics.y = y;
+            physics.vy = -physics.vy;
+            physics.rotation = -physics.rotation;
+            entity.acceleration = None;
+
+            if let Some(trail) = entity.trail.as_mut() {
+                for point in trail.points.iter_mut() {
+                    *point = convert(*point);
+                }
+            }
+        }
+
+        for emitter in self.emitters.iter_mut() {
+            emitter.position = convert(emitter.position);
+            emitter.direction = -emitter.direction;
+        }
+        for attractor in self.attractors.iter_mut() {
+            attractor.position = convert(attractor.position);
+        }
+        if let Some(wind) = self.wind.as_mut() {
+            wind.direction = -wind.direction;
+        }
+        self.goal = self.goal.map(convert);
+        if let Boundary::Circle { center, radius } = self.boundary {
+            self.boundary = Boundary::Circle {
+                center: convert(center),
+                radius,
+            };
+        }
+
+        self.coordinates = coordinates;
+    }
+
+    pub fn states(&self) -> HashMap<u32, Physics> {
+        self.entities
+            .iter()
+            .map(|entity| (entity._id, entity.physics))
+            .collect()
+    }
+
+    // Call after editing `entities` directly.
+    pub fn rebuild_index(&mut self) {
+        self.index_of = self
+            .entities
+            .iter()
+            .enumerate()
+            .map(|(index, entity)| (entity._id, index))
+            .collect();
+    }
+
+    // Puts `entities` in increasing id order, leaving them alone when already there.
+    pub fn sort_by_id(&mut self) {
+        if self
+            .entities
+            .windows(2)
+            .all(|pair| pair[0]._id < pair[1]._id)
+        {
+            return;
+        }
+
+        self.entities.sort_by_key(|entity| entity._id);
+        self.rebuild_index();
+    }
+
+    pub fn index_of(&self, id: u32) -> Option<usize> {
+        self.index_of.get(&id).copied()
+    }
+
+    pub fn get_by_id(&self, id: u32) -> Option<&Entity> {
+        self.index_of(id).map(|index| &self.entities[index])
+    }
+
+    pub fn get_by_id_mut(&mut self, id: u32) -> Option<&mut Entity> {
+        self.index_of(id)
+            .map(move |index| &mut self.entities[index])
+    }
+
+    // Adds the entity under a fresh id, which is returned.
+    pub fn spawn(&mut self, mut entity: Entity) -> u32 {
+        let id = self.next_id;
+
+        self.next_id += 1;
+        entity._id = id;
+        self.index_of.insert(id, self.entities.len());
+        self.entities.push(entity);
+        self.stats.spawned += 1;
+        self.flash(id, FlashEvent::Spawn);
+        self.evict_over_cap();
+
+        id
+    }
+
+    // Spawns unless the entity overlaps another and `spawn_overlap` can't place it.
+    pub fn try_spawn(&mut self, mut entity: Entity) -> Option<u32> {
+        if self.spawn_overlap != SpawnOverlap::Allow {
+            let others: Vec<Physics> = self
+                .entities
+                .iter()
+                .filter(|other| !self.despawn_queue.contains(&other._id))
+                .map(|other| other.physics)
+                .collect();
+
+            if others
+                .iter()
+                .any(|other| overlapping(&entity.physics, other))
+            {
+                match (self.spawn_overlap, free_position(&entity.physics, &others)) {
+                    (SpawnOverlap::Nudge, Some([x, y])) => {
+                        entity.physics.x = x;
+                        entity.physics.y = y;
+                    }
+                    _ => return None,
+                }
+            }
+        }
+
+        Some(self.spawn(entity))
+    }
+
+    // Removes the lowest ids, which are the oldest entities, until within `max_entities`.
+    fn evict_over_cap(&mut self) {
+        let cap = match self.max_entities {
+            Some(cap) if self.entities.len() > cap => cap,
+            _ => return,
+        };
+
+        let mut ids: Vec<u32> = self.entities.iter().map(|entity| entity._id).collect();
+        ids.sort_unstable();
+        let evicted = &ids[..self.entities.len() - cap];
+        self.stats.despawned += evicted.len() as u64;
+
+        self.entities
+            .retain(|entity| evicted.binary_search(&entity._id).is_err());
+        self.rebuild_index();
+    }
+
+    pub fn add_kind(&mut self, kind: EntityKind) -> KindId {
+        self.kinds.push(kind);
+        KindId(self.kinds.len() - 1)
+    }
+
+    // Entity of a shared kind, sized from the kind's default size.
+    pub fn entity_of_kind(&self, id: u32, kind: KindId, x: f64, y: f64) -> Entity {
+        let physics = Physics {
+            x,
+            y,
+            size: self.kinds[kind.0].size,
+            rotation: 0.0,
+            vx: 0.0,
+            vy: 0.0,
+            angular_velocity: 0.0,
+        };
+
+        Entity::new(id, physics, Renderer::Kind(kind))
+    }
+
+    // Moves an owned renderer into a new kind so other entities can share it.
+    pub fn share_renderer(&mut self, index: usize) -> KindId {
+        let next = KindId(self.kinds.len());
+        let entity = &mut self.entities[index];
+
+        match std::mem::replace(&mut entity.renderer, Renderer::Kind(next)) {
+            Renderer::Owned(renderer) => {
+                let size = entity.physics.size;
+                self.add_kind(EntityKind { renderer, size })
+            }
+            Renderer::Kind(kind) => {
+                entity.renderer = Renderer::Kind(kind);
+                kind
+            }
+        }
+    }
+
+    pub fn renderer_of<'a>(&'a self, entity: &'a Entity) -> &'a dyn Drawable {
+        match &entity.renderer {
+            Renderer::Owned(renderer) => renderer.as_ref(),
+            Renderer::Kind(kind) => self.kinds[kind.0].renderer.as_ref(),
+        }
+    }
+
+    // Color the entity is drawn with, when its renderer has one.
+    pub fn color_of(&self, entity: &Entity) -> Option<[f32; 4]> {
+        entity
+            .color_override()
+            .or_else(|| self.renderer_of(entity).color())
+    }
+
+    // Color to draw the entity with in place of the renderer's, showing its health in an
+    // epidemic or its hue cycle, blending in its flash and tinted by the daylight.
+    pub fn draw_color(&self, entity: &Entity) -> Option<[f32; 4]> {
+        let base = match (self.epidemic.as_ref(), entity.health) {
+            (Some(epidemic), Some(health)) => Some(epidemic.color(health)),
+            _ => self.reaction_color(entity).or_else(|| {
+                entity
+                    .hue_cycle
+                    .map(|cycle| cycle.color(entity._id, self.sim_time))
+            }),
+        };
+        let color = match (self.flash.as_ref(), entity.flashed_at) {
+            (Some(flash), Some(at)) if self.sim_time - at < flash.duration => base
+                .or_else(|| self.color_of(entity))
+                .map(|base| flash_color(base, flash.color, self.sim_time - at, flash.duration)),
+            _ => base.or_else(|| entity.color_override()),
+        };
+
+        match self.daylight {
+            Some(daylight) => color
+                .or_else(|| self.color_of(entity))
+                .map(|color| multiply_color(color, daylight.tint(self.sim_time))),
+            None => color,
+        }
+    }
+
+    // Color of an entity kept by the reaction, from the concentration under it.
+    fn reaction_color(&self, entity: &Entity) -> Option<[f32; 4]> {
+        let reaction = self.reaction.as_ref()?;
+        if !matches!(entity.renderer, Renderer::Kind(kind) if kind == reaction.kind) {
+            return None;
+        }
+
+        let (low, high) = reaction.colors;
+        let concentration = reaction
+            .grid
+            .concentration_at([entity.physics.x, entity.physics.y]);
+
+        Some(lerp_color(low, high, concentration))
+    }
+
+    // Ids of the entities past `max_age`.
+    pub fn aged_out(&self) -> Vec<u32> {
+        let max_age = match self.max_age {
+            Some(max_age) => max_age,
+            None => return Vec::new(),
+        };
+
+        self.entities
+            .iter()
+            .filter(|entity| entity.age > max_age)
+            .map(|entity| entity._id)
+            .collect()
+    }
+
+    // Brings every entity back within the boundary, or queues it for despawning in the
+    // destroy mode. Runs last in `step`, after collisions, splits and spawns have had
+    // their say, so none ends a step outside.
+    fn enforce_boundary(&mut self) {
+        let bounds = self.bounds();
+
+        for entity in self.entities.iter_mut() {
+            let mode = entity.boundary_mode.unwrap_or(self.boundary_mode);
+            let center = [entity.physics.x, entity.physics.y];
+
+            if mode == BoundaryMode::Destroy {
+                if !self.boundary.contains(center, bounds) {
+                    self.despawn_queue.insert(entity._id);
+                }
+                continue;
+            }
+
+            self.boundary.enforce(&mut entity.physics, mode, bounds);
+            if let Some(regions) = self.regions.as_ref() {
+                entity.region = regions.confine(&mut entity.physics, entity.region);
+            }
+        }
+    }
+
+    // Starts a flash on the entity if the world flashes on `event`.
+    pub fn flash(&mut self, id: u32, event: FlashEvent) {
+        let flashes = self
+            .flash
+            .as_ref()
+            .is_some_and(|flash| flash.events.contains(&event));
+        let now = self.sim_time;
+
+        if let Some(entity) = self.get_by_id_mut(id).filter(|_| flashes) {
+            entity.flashed_at = Some(now);
+        }
+    }
+
+    // Ids of the entities whose center lies in the rectangle, edges included.
+    pub fn entities_in_rect(&self, min: [f64; 2], max: [f64; 2]) -> Vec<u32> {
+        self.entities
+            .iter()
+            .filter(|entity| {
+                let (x, y) = (entity.physics.x, entity.physics.y);
+                x >= min[0] && x <= max[0] && y >= min[1] && y <= max[1]
+            })
+            .map(|entity| entity._id)
+            .collect()
+    }
+
+    pub fn despawn(&mut self, id: u32) {
+        self.despawn_queue.insert(id);
+    }
+
+    pub fn flush_despawns(&mut self) {
+        if self.despawn_queue.is_empty() {
+            return;
+        }
+
+        let queue = std::mem::take(&mut self.despawn_queue);
+        let count = self.entities.len();
+
+        self.entities.retain(|entity| !queue.contains(&entity._id));
+        self.stats.despawned += (count - self.entities.len()) as u64;
+        self.rebuild_index();
+    }
+
+    // Index pairs of the overlapping entities, see `collision::collisions`.
+    pub fn collisions(&self) -> Vec<(usize, usize)> {
+        let bodies: Vec<Physics> = self.entities.iter().map(|entity| entity.physics).collect();
+
+        collisions(&bodies, self.neighbor_index().as_ref())
+    }
+
+    // Returns the counters gathered so far and starts over.
+    pub fn take_stats(&mut self) -> Stats {
+        std::mem::take(&mut self.stats)
+    }
+
+    // `collisions` for use within a step, counting its queries. Pairs whose collision
+    // masks keep them apart pass through each other.
+    fn step_collisions(&mut self) -> Vec<(usize, usize)> {
+        self.stats.neighbor_queries += self.entities.len() as u64;
+        let layers = |index: usize| {
+            let entity = &self.entities[index];
+            (entity.collision_layer, entity.collision_mask)
+        };
+
+        self.collisions()
+            .into_iter()
+            .filter(|&(i, j)| layers_collide(layers(i), layers(j)))
+            .collect()
+    }
+
+    // The heavier of two merging entities absorbs the other, which is despawned.
+    fn resolve_collisions(&mut self) {
+        if self.collision_response == CollisionResponse::Ignore {
+            return;
+        }
+
+        // Entities already merged away, by an earlier substep or just now.
+        let mut absorbed: Vec<bool> = self
+            .entities
+            .iter()
+            .map(|entity| self.despawn_queue.contains(&entity._id))
+            .collect();
+        let restitution = self.restitution;
+
+        for (i, j) in self.step_collisions() {
+            if absorbed[i] || absorbed[j] {
+                continue;
+            }
+
+            let frozen = (self.entities[i].frozen, self.entities[j].frozen);
+            if frozen == (true, true) {
+                continue;
+            }
+
+            self.stats.collisions += 1;
+
+            let same_kind = match (&self.entities[i].renderer, &self.entities[j].renderer) {
+                (Renderer::Kind(a), Renderer::Kind(b)) => a == b,
+                _ => false,
+            };
+
+            // Frozen entities are never merged away or moved, only bounced off.
+            if frozen.0 || frozen.1 {
+                let (moving, obstacle) = if frozen.0 { (j, i) } else { (i, j) };
+                let obstacle_physics = self.entities[obstacle].physics;
+
+                bounce_off(
+                    &mut self.entities[moving].physics,
+                    &obstacle_physics,
+                    restitution,
+                );
+                self.entities[moving].wake();
+                for index in [moving, obstacle] {
+                    let id = self.entities[index]._id;
+                    self.flash(id, FlashEvent::Collision);
+                }
+            } else if self.collision_response == CollisionResponse::Merge && same_kind {
+                let (a, b) = (self.entities[i].physics, self.entities[j].physics);
+                let (keep, gone) = if mass(&a) >= mass(&b) { (i, j) } else { (j, i) };
+
+                self.entities[keep].physics = merged(&a, &b);
+                self.entities[keep].wake();
+                let id = self.entities[keep]._id;
+                self.flash(id, FlashEvent::Collision);
+                absorbed[gone] = true;
+                self.despawn_queue.insert(self.entities[gone]._id);
+            } else {
+                let (left, right) = self.entities.split_at_mut(j);
+                bounce(&mut left[i].physics, &mut right[0].physics, restitution);
+                left[i].wake();
+                right[0].wake();
+                let ids = [left[i]._id, right[0]._id];
+                for id in ids {
+                    self.flash(id, FlashEvent::Collision);
+                }
+            }
+        }
+    }
+
+    // How many substeps `dt` is split into, so that no entity moves further than its
+    // radius in one. Always one unless `max_substeps` allows more.
+    fn substeps(&self, steerings: &[Option<Steering>], dt: f64) -> usize {
+        if self.max_substeps <= 1 {
+            return 1;
+        }
+
+        self.entities
+            .iter()
+            .zip(steerings)
+            .filter(|(entity, _)| !entity.frozen)
+            .map(|(entity, steering)| {
+                let [vx, vy] = steering
+                    .and_then(|steering| steering.velocity)
+                    .unwrap_or([entity.physics.vx, entity.physics.vy]);
+                let displacement = (vx * vx + vy * vy).sqrt() * dt;
+
+                substep_count(displacement, entity.physics.size / 2.0, self.max_substeps)
+            })
+            .max()
+            .unwrap_or(1)
+    }
+
+    // Integrates one substep of `step_dt` and keeps entities out of the boundary, walls
+    // and portals. Steering is applied on the first substep, and trails, pheromones and
+    // sleep only follow the last, which returns the marks to leave.
+    fn advance_entities(
+        &mut self,
+        steerings: &[Option<Steering>],
+        wind: Option<[f64; 2]>,
+        step_dt: f64,
+        substep: usize,
+        substeps: usize,
+    ) -> Vec<(usize, [f64; 2], f64)> {
+        let dt = step_dt / substeps as f64;
+        let (first, last) = (substep == 0, substep + 1 == substeps);
+        let keep = (1.0 - self.damping * dt).max(0.0);
+        let bounds = self.bounds();
+        let attractors = &self.attractors;
+        let forces = |point: [f64; 2]| {
+            let mut total = wind.unwrap_or([0.0, 0.0]);
+
+            for attractor in attractors.iter() {
+                let [ax, ay] = attractor.acceleration(point);
+                total[0] += ax;
+                total[1] += ay;
+            }
+
+            total
+        };
+
+        let mut marks = Vec::new();
+        for (index, (entity, &steering)) in self.entities.iter_mut().zip(steerings).enumerate() {
+            if entity.frozen {
+                entity.age += dt;
+                continue;
+            }
+
+            // Sleepers are only woken by collisions or forces strong enough to get them
+            // past the sleep speed within the step.
+            if entity.sleeping {
+                let [ax, ay] = forces([entity.physics.x, entity.physics.y]);
+
+                match self.sleep {
+                    Some(sleep) if (ax * ax + ay * ay).sqrt() * step_dt < sleep.speed => {
+                        entity.age += dt;
+                        continue;
+                    }
+                    _ => entity.wake(),
+                }
+            }
+
+            // Steering is for the whole step, given all at once.
+            if let (true, Some(mut steering)) = (first, steering) {
+                if let (Some(rate), Some(rotation)) = (entity.max_turn_rate, steering.rotation) {
+                    steering.rotation = Some(turn_towards(
+                        entity.physics.rotation,
+                        rotation,
+                        rate * step_dt,
+                    ));
+                }
+                steering.apply(&mut entity.physics, step_dt);
+            }
+            if first {
+                if let Some(mut behavior) = entity.behavior.take() {
+                    behavior.update(entity, step_dt);
+                    entity.behavior = Some(behavior);
+                }
+            }
+
+            let keep = keep * drag_keep(entity.physics.size, self.drag, dt);
+            entity.acceleration = Some(self.integrator.advance(
+                &mut entity.physics,
+                entity.acceleration,
+                keep,
+                dt,
+                forces,
+            ));
+            entity.physics.spin(dt);
+            debug_assert!(
+                entity.physics.is_valid(),
+                "entity {} left in an invalid state: {:?}",
+                entity._id,
+                entity.physics
+            );
+
+            let mode = entity.boundary_mode.unwrap_or(self.boundary_mode);
+            self.boundary.enforce(&mut entity.physics, mode, bounds);
+            if let Some(regions) = self.regions.as_ref() {
+                entity.region = regions.confine(&mut entity.physics, entity.region);
+            }
+            for wall in self.walls.iter() {
+                reflect_off_wall(&mut entity.physics, wall, self.restitution);
+            }
+            if let Some(previous) = entity.previous {
+                if self
+                    .portals
+                    .iter()
+                    .any(|pair| pair.teleport(&mut entity.physics, previous))
+                {
+                    // Not drawn sliding across from the entry.
+                    entity.previous = None;
+                }
+            }
+
+            if !last {
+                entity.age += dt;
+                continue;
+            }
+
+            if let Some(trail) = entity.trail.as_mut() {
+                trail.record([entity.physics.x, entity.physics.y]);
+            }
+
+            if let (Some(grid), Some(steering)) = (self.pheromones.as_mut(), steering) {
+                if steering.deposit > 0.0 {
+                    grid.deposit(
+                        [entity.physics.x, entity.physics.y],
+                        steering.deposit * step_dt,
+                    );
+                }
+            }
+
+            if let Some(size) = steering.and_then(|steering| steering.mark) {
+                marks.push((index, [entity.physics.x, entity.physics.y], size));
+            }
+
+            if let Some(sleep) = self.sleep {
+                entity.settle(sleep.speed, sleep.ticks);
+            }
+
+            entity.age += dt;
+        }
+
+        marks
+    }
+
+    // Dots at rest with no behavior, one per `(index, position, size)`, drawn like the
+    // entity at `index`. Spawning may evict, so every renderer is shared first.
+    fn leave_marks(&mut self, marks: Vec<(usize, [f64; 2], f64)>) {
+        let dots: Vec<Entity> = marks
+            .into_iter()
+            .filter_map(|(index, [x, y], size)| {
+                let kind = self.share_renderer(index);
+                let physics = Physics::new(x, y, size).ok()?;
+                let mut dot = Entity::new(0, physics, Renderer::Kind(kind));
+
+                dot.layer = self.entities[index].layer;
+                Some(dot)
+            })
+            .collect();
+
+        for dot in dots {
+            self.spawn(dot);
+        }
+    }
+
+    // The parent carries on as the first child, the second is spawned beside it. Behaviors
+    // can't be cloned so the second child starts without one. A second child refused by
+    // `spawn_overlap` is lost.
+    fn split_entities(&mut self) {
+        if self.entities.iter().all(|entity| entity.split.is_none()) {
+            return;
+        }
+
+        let mut children = Vec::new();
+        let mut collided = vec![false; self.entities.len()];
+        for (i, j) in self.step_collisions() {
+            collided[i] = true;
+            collided[j] = true;
+        }
+
+        for (index, collided) in collided.into_iter().enumerate() {
+            let split = match self.entities[index].split {
+                Some(split) if split.triggered(&self.entities[index].physics, collided) => split,
+                _ => continue,
+            };
+
+            // Already merged into another entity this step.
+            if self.despawn_queue.contains(&self.entities[index]._id) {
+                continue;
+            }
+
+            let (first, second) = split.children(&self.entities[index].physics);
+            let kind = self.share_renderer(index);
+            let parent = &mut self.entities[index];
+
+            parent.physics = first;
+
+            let mut child = Entity::new(0, second, Renderer::Kind(kind)).with_split(split);
+            child.layer = parent.layer;
+            child.lifetime = parent.lifetime;
+            child.life_colors = parent.life_colors;
+            child.glow = parent.glow;
+            child.hue_cycle = parent.hue_cycle;
+            child.tags = parent.tags.clone();
+            children.push(child);
+        }
+
+        // Spawning may evict, so only once the indices above are no longer needed.
+        for child in children {
+            self.try_spawn(child);
+        }
+    }
+
+    // Enters the stage each entity has aged into: it's tagged with the stage name in
+    // place of the last one's, resized and given the stage's behavior, built for the
+    // entity's slot in its group like the one it spawned with.
+    fn run_lifecycles(&mut self) {
+        for entity in self.entities.iter_mut() {
+            let age = entity.age;
+            let previous = entity
+                .lifecycle
+                .as_ref()
+                .and_then(|lifecycle| lifecycle.current())
+                .and_then(|stage| stage.name.clone());
+            let (stage, slot) = match entity.lifecycle.as_mut().and_then(|lifecycle| {
+                let slot = lifecycle.slot();
+                lifecycle.advance(age).map(|stage| (stage.clone(), slot))
+            }) {
+                Some(entered) => entered,
+                None => continue,
+            };
+
+            if let Some(name) = previous {
+                entity.tags.retain(|tag| *tag != name);
+            }
+            if let Some(name) = stage.name {
+                entity.tags.push(name);
+            }
+            if let Some(size) = stage.size {
+                entity.physics.size = size;
+            }
+            if let Some(behavior) = stage.behavior.as_ref() {
+                entity.behavior = behavior.build_nth(slot).ok();
+            }
+        }
+    }
+
+    fn run_emitters(&mut self, dt: f64) {
+        // Past every entity id, so emitters draw from their own stream.
+        let mut rng = step_rng(self.seed, self.tick, u64::MAX);
+
+        for index in 0..self.emitters.len() {
+            let emitter = self.emitters[index];
+            let velocities = self.emitters[index].emit(dt, &mut rng);
+
+            for [vx, vy] in velocities {
+                let [x, y] = emitter.position;
+                let mut entity = self.entity_of_kind(0, emitter.kind, x, y);
+
+                entity.physics.vx = vx;
+                entity.physics.vy = vy;
+                self.try_spawn(entity);
+            }
+        }
+    }
+
+    // Spreads and cures the epidemic among the entities with a health, drawing from a
+    // stream of its own.
+    fn run_epidemic(&mut self, dt: f64) {
+        let epidemic = match self.epidemic {
+            Some(epidemic) => epidemic,
+            None => return,
+        };
+
+        if self.entities.iter().all(|entity| entity.health.is_none()) {
+            return;
+        }
+
+        let health: Vec<_> = self.entities.iter().map(|entity| entity.health).collect();
+        let positions = self.positions();
+        let index = self.spatial_index.build(&positions);
+        let mut rng = step_rng(self.seed, self.tick, u64::MAX - 2);
+        let next = epidemic.spread(
+            &health,
+            &positions,
+            index.as_ref(),
+            self.sim_time + dt,
+            &mut rng,
+        );
+
+        for (entity, health) in self.entities.iter_mut().zip(next) {
+            entity.health = health;
+        }
+    }
+
+    // Steps the automaton on generation ticks, despawning the entities in cells that die
+    // and spawning one in the center of each cell born.
+    fn run_life(&mut self) {
+        let life = match self.life.as_ref() {
+            Some(life) if life.every > 0 && self.tick.is_multiple_of(life.every) => life.clone(),
+            _ => return,
+        };
+        let mut occupants: HashMap<Cell, Vec<u32>> = HashMap::new();
+
+        for entity in self.entities.iter() {
+            if matches!(entity.renderer, Renderer::Kind(kind) if kind == life.kind)
+                && !self.despawn_queue.contains(&entity._id)
+            {
+                occupants
+                    .entry(life.cell_of([entity.physics.x, entity.physics.y]))
+                    .or_default()
+                    .push(entity._id);
+            }
+        }
+
+        let alive = occupants.keys().copied().collect();
+        let next = next_generation(&alive, &life.rule);
+
+        for (cell, ids) in occupants.iter() {
+            if !next.contains(cell) {
+                self.despawn_queue.extend(ids);
+            }
+        }
+
+        for cell in next.difference(&alive) {
+            let [x, y] = life.center_of(*cell);
+            let entity = self.entity_of_kind(0, life.kind, x, y);
+            self.spawn(entity);
+        }
+    }
+
+    // Snaps overstretched links, forms new ones between nearby entities with room for
+    // more, then has every link pull its two ends together or push them apart. Frozen
+    // ends don't move.
+    fn run_links(&mut self, dt: f64) {
+        let linking = match self.linking {
+            Some(linking) => linking,
+            None => return,
+        };
+
+        let position = |id: u32| {
+            self.get_by_id(id)
+                .filter(|entity| !self.despawn_queue.contains(&entity._id))
+                .map(|entity| [entity.physics.x, entity.physics.y])
+        };
+        let mut links = linking.break_links(&self.links, position);
+        let ids: Vec<u32> = self.entities.iter().map(|entity| entity._id).collect();
+        let positions = self.positions();
+        let index = self.spatial_index.build(&positions);
+        links.extend(linking.form_links(&links, &ids, &positions, index.as_ref()));
+
+        for link in links.iter() {
+            let (a, b) = match (self.index_of(link.a), self.index_of(link.b)) {
+                (Some(a), Some(b)) => (a, b),
+                _ => continue,
+            };
+            let [ax, ay] = linking.acceleration(link, positions[a], positions[b]);
+
+            for (index, sign) in [(a, 1.0), (b, -1.0)] {
+                let entity = &mut self.entities[index];
+                if entity.frozen || (ax == 0.0 && ay == 0.0) {
+                    continue;
+                }
+                entity.physics.vx += sign * ax * dt;
+                entity.physics.vy += sign * ay * dt;
+                entity.wake();
+            }
+        }
+
+        self.links = links;
+    }
+
+    // Updates the reaction grid, then despawns its entities from the cells that dropped
+    // below the threshold and spawns one on each cell that rose above it.
+    fn run_reaction(&mut self) {
+        let mut reaction = match self.reaction.take() {
+            Some(reaction) => reaction,
+            None => return,
+        };
+
+        for _ in 0..reaction.iterations {
+            reaction.grid.step(1.0);
+        }
+
+        let next = reaction.occupied();
+        let mut occupied = HashSet::new();
+
+        for entity in self.entities.iter() {
+            if !matches!(entity.renderer, Renderer::Kind(kind) if kind == reaction.kind)
+                || self.despawn_queue.contains(&entity._id)
+            {
+                continue;
+            }
+
+            match reaction.grid.cell_of([entity.physics.x, entity.physics.y]) {
+                Some(cell) if next.contains(&cell) && occupied.insert(cell) => {}
+                Some(_) => {
+                    self.despawn_queue.insert(entity._id);
+                }
+                None => {}
+            }
+        }
+
+        for cell in next.difference(&occupied) {
+            let [x, y] = reaction.grid.center_of(*cell);
+            let entity = self.entity_of_kind(0, reaction.kind, x, y);
+            self.spawn(entity);
+        }
+
+        self.reaction = Some(reaction);
+    }
+
+    // Gives each listed entity a fresh behavior from `make`.
+    pub fn set_behavior<F>(&mut self, ids: &[u32], make: F)
+    where
+        F: Fn() -> Option<Box<dyn AI>>,
+    {
+        for &id in ids {
+            if let Some(entity) = self.get_by_id_mut(id) {
+                entity.behavior = make();
+            }
+        }
+    }
+
+    pub fn positions(&self) -> Vec<[f64; 2]> {
+        self.entities
+            .iter()
+            .map(|entity| [entity.physics.x, entity.physics.y])
+            .collect()
+    }
+
+    // Neighbor index over the current entity positions, indices match `entities`.
+    pub fn neighbor_index(&self) -> Box<dyn NeighborQuery> {
+        self.spatial_index.build(&self.positions())
+    }
+
+    pub fn step(&mut self, dt: f64) {
+        if self.deterministic_order {
+            self.sort_by_id();
+        }
+        for entity in self.entities.iter_mut() {
+            entity.previous = Some([entity.physics.x, entity.physics.y]);
+        }
+
+        let wind = self.wind.map(|wind| wind.acceleration(self.sim_time));
+        let snapshot: Vec<Snapshot> = self
+            .entities
+            .iter()
+            .map(|entity| Snapshot {
+                id: entity._id,
+                physics: entity.physics,
+            })
+            .collect();
+        let positions: Vec<[f64; 2]> = snapshot
+            .iter()
+            .map(|other| [other.physics.x, other.physics.y])
+            .collect();
+        let neighbor_index = self.spatial_index.build(&positions);
+        let colors: Vec<Option<[f32; 4]>> = self
+            .entities
+            .iter()
+            .map(|entity| self.color_of(entity))
+            .collect();
+        let tags: Vec<&[String]> = self
+            .entities
+            .iter()
+            .map(|entity| entity.tags.as_slice())
+            .collect();
+        let mass_tree = OnceLock::new();
+        let context = BehaviorContext {
+            dt,
+            width: self.width,
+            height: self.height,
+            bounds: self.bounds(),
+            coordinates: self.coordinates,
+            sim_time: self.sim_time,
+            tick: self.tick,
+            snapshot: &snapshot,
+            index_of: &self.index_of,
+            centroid: centroid(&snapshot),
+            goal: self.goal,
+            tags: &tags,
+            colors: &colors,
+            neighbor_index: neighbor_index.as_ref(),
+            lanes: self.lanes.as_ref(),
+            field: self.field.as_ref(),
+            flow_field: self.flow_field.as_ref(),
+            vector_field: self.vector_field.as_ref(),
+            nav_grid: self.nav_grid.as_ref(),
+            pheromones: self.pheromones.as_ref(),
+            attractors: &self.attractors,
+            migration_path: &self.migration_path,
+            seed: self.seed,
+            mass_tree: &mass_tree,
+        };
+
+        let len = self.entities.len();
+        let window = self
+            .budget
+            .map(|budget| (budget.cursor(), budget.count(len)));
+        let started = Instant::now();
+        let steerings = {
+            let behaviors: Vec<Option<&dyn AI>> = self
+                .entities
+                .iter()
+                .enumerate()
+                .map(|(index, entity)| {
+                    entity.behavior.as_deref().filter(|_| {
+                        entity.behavior_due(self.tick)
+                            && !entity.sleeping
+                            && !entity.frozen
+                            && window
+                                .is_none_or(|(cursor, count)| in_window(index, cursor, count, len))
+                    })
+                })
+                .collect();
+
+            self.stats.neighbor_queries += behaviors
+                .iter()
+                .flatten()
+                .filter(|ai| ai.perception_radius().is_some())
+                .count() as u64;
+            compute_steerings(&behaviors, &context)
+        };
+
+        if let (Some(budget), Some((_, count))) = (self.budget.as_mut(), window) {
+            budget.record(count, len, started.elapsed());
+        }
+
+        let substeps = self.substeps(&steerings, dt);
+        let mut marks = Vec::new();
+        for substep in 0..substeps {
+            marks = self.advance_entities(&steerings, wind, dt, substep, substeps);
+
+            // The last substep's collisions are resolved with the rest of the step below.
+            if substep + 1 < substeps {
+                self.resolve_collisions();
+            }
+        }
+
+        if let Some(grid) = self.pheromones.as_mut() {
+            grid.fade(dt);
+        }
+
+        self.leave_marks(marks);
+
+        self.stats.steps += 1;
+        self.stats.entities_updated += self.entities.len() as u64;
+        self.resolve_collisions();
+        self.split_entities();
+        self.run_emitters(dt);
+        self.run_lifecycles();
+        self.run_life();
+        self.run_epidemic(dt);
+        self.run_reaction();
+        self.run_links(dt);
+        self.enforce_boundary();
+        for id in self.aged_out() {
+            self.despawn(id);
+        }
+        self.flush_despawns();
+
+        if self.changelog.is_some() {
+            let before = snapshot
+                .iter()
+                .map(|other| (other.id, other.physics))
+                .collect();
+            let log = TickLog::diff(self.tick, &before, &self.states());
+
+            if let Some(changelog) = self.changelog.as_mut() {
+                changelog.push(log);
+            }
+        }
+
+        self.sim_time += dt;
+        self.tick += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+    use crate::behavior::FormationShape;
+    use crate::config::BehaviorConfig;
+    use crate::forces::Wind;
+    use crate::lifecycle::{Lifecycle, Stage};
+    use crate::regions::{Opening, Region};
+    use crate::shapes::{Circle, Square};
+
+    fn dot(id: u32, x: f64, y: f64) -> Entity {
+        Entity::new(
+            id,
+            Physics::new(x, y, 2.0).unwrap(),
+            Renderer::Owned(Box::new(Circle::new([1.0; 4]))),
+        )
+    }
+
+    #[test]
+    fn circle_boundary_reflects_outward_motion_inward() {
+        let mut physics = Physics::new(108.0, 50.0, 2.0).unwrap();
+        physics.vx = 5.0;
+        physics.vy = 1.0;
+
+        enforce_circle(&mut physics, BoundaryMode::Bounce, [50.0, 50.0], 50.0);
+
+        let distance = (physics.x - 50.0).hypot(physics.y - 50.0);
+        assert!((distance - 49.0).abs() < 1e-9);
+        assert_eq!(physics.vx, -5.0);
+        assert_eq!(physics.vy, 1.0);
+    }
+
+    #[test]
+    fn entities_of_a_kind_share_one_renderer() {
+        let mut world = World::new(Vec::new(), 100.0, 100.0).unwrap();
+        let kind = world.add_kind(EntityKind {
+            renderer: Box::new(Square::new([1.0, 0.0, 0.0, 1.0])),
+            size: 4.0,
+        });
+        let a = world.entity_of_kind(0, kind, 20.0, 20.0);
+        let b = world.entity_of_kind(1, kind, 20.0, 20.0);
+
+        assert_eq!(world.kinds.len(), 1);
+        assert_eq!(a.physics.size, 4.0);
+        assert!(std::ptr::eq(
+            world.renderer_of(&a) as *const dyn Drawable as *const u8,
+            world.renderer_of(&b) as *const dyn Drawable as *const u8,
+        ));
+        assert_eq!(
+            world.renderer_of(&a).geometry(a.physics, None),
+            world.renderer_of(&b).geometry(b.physics, None)
+        );
+    }
+
+    #[test]
+    fn constant_wind_adds_its_acceleration_every_tick() {
+        let mut world = World::new(vec![dot(0, 50.0, 50.0)], 100.0, 100.0).unwrap();
+        world.wind = Some(Wind {
+            direction: std::f64::consts::FRAC_PI_2,
+            strength: 10.0,
+            period: None,
+        });
+
+        for tick in 1..=3 {
+            world.step(0.1);
+
+            let physics = world.entities[0].physics;
+            assert!(physics.vx.abs() < 1e-9);
+            assert!((physics.vy - tick as f64).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn tick_counts_the_steps_taken() {
+        let mut world = World::new(vec![dot(0, 50.0, 50.0)], 100.0, 100.0).unwrap();
+        assert_eq!(world.tick, 0);
+
+        for tick in 1..=5 {
+            world.step(0.1);
+            assert_eq!(world.tick, tick);
+        }
+    }
+
+    #[test]
+    fn entities_in_rect_include_the_edges() {
+        let world = World::new(
+            vec![dot(0, 10.0, 10.0), dot(1, 20.0, 15.0), dot(2, 50.0, 50.0)],
+            100.0,
+            100.0,
+        )
+        .unwrap();
+
+        assert_eq!(world.entities_in_rect([5.0, 5.0], [30.0, 30.0]), vec![0, 1]);
+        assert_eq!(
+            world.entities_in_rect([10.0, 10.0], [20.0, 15.0]),
+            vec![0, 1]
+        );
+        assert_eq!(
+            world.entities_in_rect([60.0, 60.0], [90.0, 90.0]),
+            Vec::<u32>::new()
+        );
+    }
+
+    #[test]
+    fn set_behavior_and_despawn_touch_only_the_selected_ids() {
+        let mut world = World::new(
+            vec![dot(0, 10.0, 10.0), dot(1, 20.0, 20.0), dot(2, 30.0, 30.0)],
+            100.0,
+            100.0,
+        )
+        .unwrap();
+
+        world.set_behavior(&[0, 2], || {
+            Some(Box::new(crate::behavior::Containment {
+                margin: 1.0,
+                strength: 1.0,
+            }))
+        });
+        let with_behavior: Vec<u32> = world
+            .entities
+            .iter()
+            .filter(|entity| entity.behavior.is_some())
+            .map(|entity| entity._id)
+            .collect();
+        assert_eq!(with_behavior, vec![0, 2]);
+
+        world.despawn(1);
+        world.flush_despawns();
+        let ids: Vec<u32> = world.entities.iter().map(|entity| entity._id).collect();
+        assert_eq!(ids, vec![0, 2]);
+    }
+
+    #[test]
+    fn index_of_follows_spawns_and_despawns() {
+        let mut world =
+            World::new(vec![dot(0, 10.0, 10.0), dot(1, 20.0, 20.0)], 100.0, 100.0).unwrap();
+        let spawned = world.spawn(dot(0, 30.0, 30.0));
+        assert_eq!(world.index_of(spawned), Some(2));
+
+        world.despawn(0);
+        world.flush_despawns();
+
+        assert_eq!(world.index_of(0), None);
+        for id in [1, spawned] {
+            let index = world.index_of(id).unwrap();
+            assert_eq!(world.entities[index]._id, id);
+        }
+        assert_eq!(world.get_by_id(spawned).unwrap().physics.x, 30.0);
+    }
+
+    #[test]
+    fn spawning_past_the_cap_evicts_the_oldest() {
+        let mut world =
+            World::new(vec![dot(0, 10.0, 10.0), dot(1, 20.0, 20.0)], 100.0, 100.0).unwrap();
+        world.max_entities = Some(3);
+
+        for i in 0..4 {
+            world.spawn(dot(0, 30.0 + 10.0 * i as f64, 30.0));
+            assert!(world.entities.len() <= 3);
+        }
+
+        let mut ids: Vec<u32> = world.entities.iter().map(|entity| entity._id).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, vec![3, 4, 5]);
+        assert_eq!(world.index_of(0), None);
+    }
+
+    #[test]
+    fn merging_conserves_mass_and_momentum() {
+        let mut world = World::new(Vec::new(), 100.0, 100.0).unwrap();
+        world.collision_response = CollisionResponse::Merge;
+        let kind = world.add_kind(EntityKind {
+            renderer: Box::new(Circle::new([1.0; 4])),
+            size: 4.0,
+        });
+        let mut a = world.entity_of_kind(0, kind, 48.0, 50.0);
+        a.physics.vx = 3.0;
+        let mut b = world.entity_of_kind(0, kind, 51.0, 50.0);
+        b.physics.size = 2.0;
+        b.physics.vy = -6.0;
+        let (a, b) = (world.spawn(a), world.spawn(b));
+        let (a, b) = (
+            world.get_by_id(a).unwrap().physics,
+            world.get_by_id(b).unwrap().physics,
+        );
+
+        world.step(0.01);
+
+        assert_eq!(world.entities.len(), 1);
+        let merged = world.entities[0].physics;
+        let total = mass(&a) + mass(&b);
+        assert!((mass(&merged) - total).abs() < 1e-9);
+        assert!((mass(&merged) * merged.vx - (mass(&a) * a.vx + mass(&b) * b.vx)).abs() < 1e-9);
+        assert!((mass(&merged) * merged.vy - (mass(&a) * a.vy + mass(&b) * b.vy)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn stats_count_the_collisions_of_a_step() {
+        let mut world = World::new(
+            vec![dot(0, 50.0, 50.0), dot(1, 51.0, 50.0), dot(2, 80.0, 80.0)],
+            100.0,
+            100.0,
+        )
+        .unwrap();
+        world.collision_response = CollisionResponse::Bounce;
+
+        world.step(0.01);
+        let stats = world.take_stats();
+        assert_eq!(stats.steps, 1);
+        assert_eq!(stats.collisions, 1);
+
+        // Pushed apart, they don't collide again.
+        world.step(0.01);
+        assert_eq!(world.take_stats().collisions, 0);
+    }
+
+    #[test]
+    fn resting_entities_sleep_until_hit() {
+        let mut world = World::new(vec![dot(0, 50.0, 50.0)], 100.0, 100.0).unwrap();
+        world.collision_response = CollisionResponse::Bounce;
+        world.sleep = Some(Sleep {
+            speed: 0.5,
+            ticks: 3,
+        });
+
+        world.step(0.1);
+        world.step(0.1);
+        assert!(!world.entities[0].sleeping);
+        world.step(0.1);
+        assert!(world.entities[0].sleeping);
+
+        let mut bullet = dot(0, 40.3, 50.0);
+        bullet.physics.vx = 20.0;
+        let bullet = world.spawn(bullet);
+        for _ in 0..5 {
+            world.step(0.1);
+        }
+
+        let sleeper = world.get_by_id(0).unwrap();
+        assert!(!sleeper.sleeping);
+        assert!(sleeper.physics.vx > 0.0);
+        assert!(world.get_by_id(bullet).unwrap().physics.x < sleeper.physics.x);
+    }
+
+    #[test]
+    fn deterministic_order_steps_the_same_whatever_the_storage_order() {
+        let pile = |order: [u32; 3]| {
+            let start = [[50.0, 50.0], [51.0, 50.5], [50.5, 51.0]];
+            let entities = order
+                .iter()
+                .map(|&id| {
+                    let [x, y] = start[id as usize];
+                    let mut entity = dot(id, x, y);
+                    entity.physics.vx = 5.0 - 5.0 * id as f64;
+                    entity
+                })
+                .collect();
+            let mut world = World::new(entities, 100.0, 100.0).unwrap();
+            world.collision_response = CollisionResponse::Bounce;
+            world.deterministic_order = true;
+
+            for _ in 0..10 {
+                world.step(0.1);
+            }
+
+            assert!(world
+                .entities
+                .windows(2)
+                .all(|pair| pair[0]._id < pair[1]._id));
+            world
+                .entities
+                .iter()
+                .map(|entity| entity.physics)
+                .collect::<Vec<Physics>>()
+        };
+
+        let sorted = pile([0, 1, 2]);
+        assert_eq!(pile([2, 0, 1]), sorted);
+        assert_eq!(pile([1, 2, 0]), sorted);
+    }
+
+    #[test]
+    fn entity_pushed_out_by_a_collision_ends_the_step_in_bounds() {
+        let mut edge = dot(0, 98.8, 50.0);
+        edge.physics.vx = 1.0;
+        let pusher = Entity::new(
+            1,
+            Physics::new(97.5, 50.0, 4.0).unwrap(),
+            Renderer::Owned(Box::new(Circle::new([1.0; 4]))),
+        );
+        let mut world = World::new(vec![edge, pusher], 100.0, 100.0).unwrap();
+        world.collision_response = CollisionResponse::Bounce;
+
+        // Separating the pair alone takes the small one past the wall.
+        world.step(0.01);
+
+        let physics = &world.entities[0].physics;
+        assert!(physics.x <= 99.0);
+        assert!(physics.vx <= 0.0);
+        assert!(world.entities[1].physics.x <= 98.0);
+    }
+
+    #[test]
+    fn entity_boundary_modes_override_the_world() {
+        let mut projectile = dot(0, 99.5, 50.0);
+        projectile.physics.vx = 10.0;
+        projectile.boundary_mode = Some(BoundaryMode::Destroy);
+        let mut walker = dot(1, 99.5, 20.0);
+        walker.physics.vx = 10.0;
+        walker.boundary_mode = Some(BoundaryMode::Wrap);
+        let mut world = World::new(vec![projectile, walker], 100.0, 100.0).unwrap();
+        assert_eq!(world.boundary_mode, BoundaryMode::Bounce);
+
+        world.step(0.1);
+
+        assert_eq!(world.entities.len(), 1);
+        assert_eq!(world.entities[0]._id, 1);
+        assert!((world.entities[0].physics.x - 0.5).abs() < 1e-9);
+        assert_eq!(world.entities[0].physics.vx, 10.0);
+    }
+
+    #[test]
+    fn spawning_onto_an_occupied_spot_rejects_or_nudges() {
+        let mut world = World::new(vec![dot(0, 50.0, 50.0)], 100.0, 100.0).unwrap();
+
+        world.spawn_overlap = SpawnOverlap::Reject;
+        assert_eq!(world.try_spawn(dot(1, 50.5, 50.0)), None);
+        assert_eq!(world.entities.len(), 1);
+        assert!(world.try_spawn(dot(1, 60.0, 50.0)).is_some());
+        assert_eq!(world.entities.len(), 2);
+
+        world.spawn_overlap = SpawnOverlap::Nudge;
+        let id = world.try_spawn(dot(2, 50.5, 50.0)).unwrap();
+        let nudged = world.entities[world.index_of(id).unwrap()].physics;
+        let original = world.entities[0].physics;
+
+        assert!(!overlapping(&nudged, &original));
+        let [dx, dy] = [nudged.x - 50.5, nudged.y - 50.0];
+        assert!((dx * dx + dy * dy).sqrt() <= 3.0);
+    }
+
+    #[test]
+    fn frozen_entities_stay_put_but_still_get_hit() {
+        let mut wall = dot(0, 50.0, 50.0);
+        wall.physics.vx = 5.0;
+        wall.frozen = true;
+        let mut mover = dot(1, 45.0, 50.0);
+        mover.physics.vx = 20.0;
+        let mut world = World::new(vec![wall, mover], 100.0, 100.0).unwrap();
+        world.collision_response = CollisionResponse::Bounce;
+
+        for _ in 0..5 {
+            world.step(0.1);
+        }
+
+        let wall = &world.entities[0].physics;
+        assert_eq!([wall.x, wall.y], [50.0, 50.0]);
+        let mover = &world.entities[1].physics;
+        assert!(mover.vx < 0.0);
+        assert!(mover.x <= 48.0);
+    }
+
+    #[test]
+    fn substeps_keep_fast_entities_from_tunneling() {
+        let hits = |max_substeps| {
+            let mut bullet = dot(0, 20.0, 50.0);
+            bullet.physics.vx = 300.0;
+            let target = Entity::new(
+                1,
+                Physics::new(35.0, 50.0, 4.0).unwrap(),
+                Renderer::Owned(Box::new(Circle::new([1.0; 4]))),
+            );
+            let mut world = World::new(vec![bullet, target], 100.0, 100.0).unwrap();
+            world.collision_response = CollisionResponse::Bounce;
+            world.max_substeps = max_substeps;
+
+            // 30 units in one step, straight over the target.
+            world.step(0.1);
+            world.entities[1].physics.vx > 0.0
+        };
+
+        assert!(!hits(1));
+        assert!(hits(30));
+    }
+
+    #[test]
+    fn collision_masks_decide_which_pairs_resolve() {
+        assert!(layers_collide((1, u32::MAX), (2, u32::MAX)));
+        assert!(!layers_collide((1, 0b01), (2, 0b01)));
+        assert!(!layers_collide((1, 0b01), (2, 0b11)));
+
+        let pair = |layers: [(u32, u32); 2]| {
+            let entities = layers
+                .iter()
+                .enumerate()
+                .map(|(id, &(layer, mask))| {
+                    let mut entity = dot(id as u32, 50.0 + id as f64, 50.0);
+                    entity.physics.vx = if id == 0 { 1.0 } else { -1.0 };
+                    entity.collision_layer = layer;
+                    entity.collision_mask = mask;
+                    entity
+                })
+                .collect();
+            let mut world = World::new(entities, 100.0, 100.0).unwrap();
+            world.collision_response = CollisionResponse::Bounce;
+            world.step(0.1);
+            world.entities[0].physics.vx
+        };
+
+        // Ghosts on layer 2 ignore walkers on layer 1, and go through them.
+        assert_eq!(pair([(0b01, 0b01), (0b10, 0b10)]), 1.0);
+        assert!(pair([(0b01, 0b11), (0b10, 0b01)]) < 0.0);
+    }
+
+    #[test]
+    fn max_turn_rate_limits_a_half_turn_request() {
+        struct TurnAround;
+        impl AI for TurnAround {
+            fn compute(&self, _: &Snapshot, _: &[Snapshot], _: &BehaviorContext) -> Steering {
+                Steering {
+                    rotation: Some(PI),
+                    ..Steering::default()
+                }
+            }
+        }
+
+        let mut entity = dot(0, 50.0, 50.0).with_behavior(Box::new(TurnAround));
+        entity.max_turn_rate = Some(1.0);
+        let mut world = World::new(vec![entity], 100.0, 100.0).unwrap();
+        let mut rotations = vec![0.0];
+
+        for _ in 0..8 {
+            world.step(0.5);
+            rotations.push(world.entities[0].physics.rotation);
+        }
+
+        // Half a radian a tick until facing the other way, after 3.14 radians.
+        for pair in rotations[..7].windows(2) {
+            assert!(((pair[1] - pair[0]).abs() - 0.5).abs() < 1e-12);
+        }
+        assert!((rotations[7].abs() - PI).abs() < 1e-12);
+        assert_eq!(rotations[8], rotations[7]);
+    }
+
+    #[test]
+    fn near_entities_link_and_the_link_breaks_once_overstretched() {
+        let mut world = World::new(
+            vec![dot(1, 20.0, 20.0), dot(2, 25.0, 20.0), dot(3, 80.0, 80.0)],
+            100.0,
+            100.0,
+        )
+        .unwrap();
+        world.linking = Some(Linking {
+            radius: 10.0,
+            max_links: 2,
+            stiffness: 0.0,
+            break_length: 20.0,
+        });
+
+        world.step(0.1);
+        assert_eq!(
+            world.links,
+            vec![Link {
+                a: 1,
+                b: 2,
+                rest_length: 5.0
+            }]
+        );
+
+        world.get_by_id_mut(2).unwrap().physics.x = 45.0;
+        world.step(0.1);
+        assert!(world.links.is_empty());
+    }
+
+    #[test]
+    fn regions_confine_entities_except_through_an_opening() {
+        let mut entities = vec![dot(1, 40.0, 20.0), dot(2, 40.0, 50.0)];
+        for entity in entities.iter_mut() {
+            entity.physics.vx = 20.0;
+        }
+        let mut world = World::new(entities, 100.0, 100.0).unwrap();
+        world.regions = Some(RegionMap {
+            regions: vec![
+                Region::Rect {
+                    min: [10.0, 10.0],
+                    max: [45.0, 90.0],
+                },
+                Region::Rect {
+                    min: [55.0, 10.0],
+                    max: [90.0, 90.0],
+                },
+            ],
+            openings: vec![Opening {
+                center: [50.0, 50.0],
+                radius: 10.0,
+            }],
+        });
+
+        for _ in 0..20 {
+            world.step(0.1);
+            let x = world.get_by_id(1).unwrap().physics.x;
+            assert!((10.0..=45.0).contains(&x));
+        }
+
+        let walled = world.get_by_id(1).unwrap();
+        let crossed = world.get_by_id(2).unwrap();
+        assert_eq!(walled.region, Some(0));
+        assert_eq!(crossed.region, Some(1));
+        assert!(crossed.physics.x > 60.0);
+    }
+
+    #[test]
+    fn growing_up_swaps_the_behavior_and_size_keeping_the_slot() {
+        let stages = vec![
+            Stage {
+                age: 0.0,
+                name: Some("juvenile".to_string()),
+                size: Some(2.0),
+                behavior: None,
+            },
+            Stage {
+                age: 0.25,
+                name: Some("adult".to_string()),
+                size: Some(5.0),
+                behavior: Some(BehaviorConfig::Formation {
+                    leader_id: 0,
+                    shape: FormationShape::Line,
+                    slot: None,
+                    spacing: 4.0,
+                    speed: 10.0,
+                }),
+            },
+        ];
+        let mut entity = dot(1, 50.0, 50.0);
+        entity.lifecycle = Some(Lifecycle::new(stages).with_slot(3));
+        let mut world = World::new(vec![entity], 100.0, 100.0).unwrap();
+
+        world.step(0.1);
+        let young = world.get_by_id(1).unwrap();
+        assert_eq!(young.tags, ["juvenile"]);
+        assert_eq!(young.physics.size, 2.0);
+        assert!(young.behavior.is_none());
+
+        world.step(0.1);
+        world.step(0.1);
+        let adult = world.get_by_id(1).unwrap();
+        assert_eq!(adult.tags, ["adult"]);
+        assert_eq!(adult.physics.size, 5.0);
+        assert!(matches!(
+            adult
+                .behavior
+                .as_ref()
+                .and_then(|behavior| behavior.to_tag()),
+            Some(BehaviorConfig::Formation { slot: Some(3), .. })
+        ));
+    }
+
+    #[test]
+    fn entities_past_max_age_are_gone_by_the_end_of_the_step() {
+        // Opaque and well within its lifetime, so only the max age can take it.
+        let mut old = dot(2, 60.0, 50.0);
+        old.age = 0.2;
+        old.lifetime = Some(1000.0);
+        let mut world = World::new(vec![dot(1, 40.0, 50.0), old], 100.0, 100.0).unwrap();
+        world.max_age = Some(0.25);
+        assert!(world.aged_out().is_empty());
+
+        world.step(0.1);
+        assert!(world.get_by_id(1).is_some());
+        assert!(world.get_by_id(2).is_none());
+        assert!(world.aged_out().is_empty());
+
+        world.step(0.1);
+        world.step(0.1);
+        assert!(world.entities.is_empty());
+    }
+}